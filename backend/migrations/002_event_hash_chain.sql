@@ -0,0 +1,8 @@
+-- Tamper-evident event log
+-- Each event links to the hash of the event before it
+
+ALTER TABLE events ADD COLUMN IF NOT EXISTS prev_hash TEXT;
+ALTER TABLE events ADD COLUMN IF NOT EXISTS hash TEXT;
+
+-- Events written before this migration keep NULL hashes and precede the chain
+CREATE UNIQUE INDEX IF NOT EXISTS idx_events_hash ON events(hash);
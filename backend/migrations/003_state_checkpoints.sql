@@ -0,0 +1,25 @@
+-- Per-epoch signed state checkpoints
+-- Merkle root over balances, pending conversions and the treasury
+
+CREATE TABLE IF NOT EXISTS state_checkpoints (
+    epoch INTEGER PRIMARY KEY,
+    merkle_root TEXT NOT NULL,
+    leaf_count INTEGER NOT NULL,
+    last_event_id BIGINT NOT NULL,
+    last_event_hash TEXT NOT NULL,
+    snapshot JSONB NOT NULL,
+    signature TEXT NOT NULL,
+    public_key TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Leaves in tree order, kept for inclusion proofs
+CREATE TABLE IF NOT EXISTS checkpoint_leaves (
+    epoch INTEGER NOT NULL REFERENCES state_checkpoints(epoch),
+    leaf_index INTEGER NOT NULL,
+    leaf_key TEXT NOT NULL,
+    leaf_data TEXT NOT NULL,
+    PRIMARY KEY (epoch, leaf_index)
+);
+
+CREATE INDEX IF NOT EXISTS idx_checkpoint_leaves_key ON checkpoint_leaves(epoch, leaf_key);
@@ -0,0 +1,4 @@
+-- Versioned checkpoint leaf sets
+-- Version 2 adds a leaf per UBI claim, so wallets can prove their claims
+
+ALTER TABLE state_checkpoints ADD COLUMN IF NOT EXISTS leaf_version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,22 @@
+-- Forks and constitutional parameters
+
+-- Parent chain heads this deployment was forked from
+CREATE TABLE IF NOT EXISTS fork_genesis (
+    parent_head_hash TEXT PRIMARY KEY,
+    parent_head_event_id BIGINT NOT NULL,
+    events_imported BIGINT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- Constitutional parameters, in force from effective_epoch until the next amendment
+-- Epochs before the first row use the constants in src/constants.rs
+CREATE TABLE IF NOT EXISTS constitution_parameters (
+    effective_epoch INTEGER PRIMARY KEY,
+    ue_mint_per_epoch TEXT NOT NULL,
+    conversion_fee_bps INTEGER NOT NULL,
+    conversion_cap_ue TEXT NOT NULL,
+    conversion_delay_epochs INTEGER NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    CHECK (conversion_fee_bps BETWEEN 0 AND 10000),
+    CHECK (conversion_delay_epochs >= 0)
+);
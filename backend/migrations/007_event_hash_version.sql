@@ -0,0 +1,4 @@
+-- Versioned event hashes
+-- Existing events keep version 1; new events are hashed over the canonical encoding (version 2)
+
+ALTER TABLE events ADD COLUMN IF NOT EXISTS hash_version INTEGER NOT NULL DEFAULT 1;
@@ -0,0 +1,14 @@
+-- Peer-to-peer UE payments
+
+CREATE TABLE IF NOT EXISTS ue_payments (
+    id BIGSERIAL PRIMARY KEY,
+    from_wallet TEXT NOT NULL,
+    to_wallet TEXT NOT NULL,
+    amount_ue TEXT NOT NULL,
+    memo TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    CHECK (from_wallet <> to_wallet)
+);
+
+CREATE INDEX IF NOT EXISTS idx_ue_payments_from ON ue_payments(from_wallet);
+CREATE INDEX IF NOT EXISTS idx_ue_payments_to ON ue_payments(to_wallet);
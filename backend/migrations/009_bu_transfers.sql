@@ -0,0 +1,14 @@
+-- BU transfers between wallets
+
+CREATE TABLE IF NOT EXISTS bu_transfers (
+    id BIGSERIAL PRIMARY KEY,
+    from_wallet TEXT NOT NULL,
+    to_wallet TEXT NOT NULL,
+    amount_bu TEXT NOT NULL,
+    memo TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    CHECK (from_wallet <> to_wallet)
+);
+
+CREATE INDEX IF NOT EXISTS idx_bu_transfers_from ON bu_transfers(from_wallet);
+CREATE INDEX IF NOT EXISTS idx_bu_transfers_to ON bu_transfers(to_wallet);
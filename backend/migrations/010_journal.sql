@@ -0,0 +1,90 @@
+-- Double-entry journal
+-- ue_balances, bu_balances and treasury become projections of the postings
+
+CREATE TABLE IF NOT EXISTS journal_entries (
+    id BIGSERIAL PRIMARY KEY,
+    event_id BIGINT UNIQUE REFERENCES events(id), -- NULL for opening balances
+    description TEXT NOT NULL,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE TABLE IF NOT EXISTS journal_postings (
+    id BIGSERIAL PRIMARY KEY,
+    entry_id BIGINT NOT NULL REFERENCES journal_entries(id),
+    account TEXT NOT NULL,
+    unit TEXT NOT NULL CHECK (unit IN ('UE', 'BU')),
+    amount NUMERIC(78, 0) NOT NULL CHECK (amount <> 0) -- negative = debit, positive = credit
+);
+
+CREATE INDEX IF NOT EXISTS idx_journal_postings_entry ON journal_postings(entry_id);
+CREATE INDEX IF NOT EXISTS idx_journal_postings_account ON journal_postings(account, unit);
+
+-- Every entry must balance per unit by the time its transaction commits
+CREATE OR REPLACE FUNCTION check_journal_entry_balanced() RETURNS TRIGGER AS $$
+BEGIN
+    IF EXISTS (
+        SELECT 1 FROM journal_postings
+        WHERE entry_id = NEW.entry_id
+        GROUP BY unit
+        HAVING SUM(amount) <> 0
+    ) THEN
+        RAISE EXCEPTION 'Journal entry % does not balance', NEW.entry_id;
+    END IF;
+    RETURN NULL;
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS journal_entry_balanced ON journal_postings;
+CREATE CONSTRAINT TRIGGER journal_entry_balanced
+AFTER INSERT ON journal_postings
+DEFERRABLE INITIALLY DEFERRED
+FOR EACH ROW EXECUTE FUNCTION check_journal_entry_balanced();
+
+-- The journal is append-only
+CREATE OR REPLACE FUNCTION reject_journal_change() RETURNS TRIGGER AS $$
+BEGIN
+    RAISE EXCEPTION 'Journal is append-only';
+END;
+$$ LANGUAGE plpgsql;
+
+DROP TRIGGER IF EXISTS journal_entries_append_only ON journal_entries;
+CREATE TRIGGER journal_entries_append_only
+BEFORE UPDATE OR DELETE ON journal_entries
+FOR EACH ROW EXECUTE FUNCTION reject_journal_change();
+
+DROP TRIGGER IF EXISTS journal_postings_append_only ON journal_postings;
+CREATE TRIGGER journal_postings_append_only
+BEFORE UPDATE OR DELETE ON journal_postings
+FOR EACH ROW EXECUTE FUNCTION reject_journal_change();
+
+-- Open the journal with the balances held before it existed.
+-- Mint and burn are recovered from claims and conversions; anything the
+-- old tables cannot explain lands in `adjustment`, where replay reports it.
+WITH opening AS (
+    INSERT INTO journal_entries (description)
+    SELECT 'Opening balances'
+    WHERE NOT EXISTS (SELECT 1 FROM journal_entries)
+    RETURNING id
+),
+balances (account, unit, amount) AS (
+    SELECT 'wallet:' || wallet_address, 'UE', balance::NUMERIC FROM ue_balances
+    UNION ALL
+    SELECT 'mint', 'UE', -COALESCE((SELECT SUM(amount_ue::NUMERIC) FROM ubi_claims), 0)
+    UNION ALL
+    SELECT 'burn', 'UE', COALESCE((SELECT SUM(amount_ue::NUMERIC) FROM pending_conversions), 0)
+    UNION ALL
+    SELECT 'wallet:' || wallet_address, 'BU', balance::NUMERIC FROM bu_balances
+    UNION ALL
+    SELECT 'treasury', 'BU', COALESCE((SELECT balance_bu::NUMERIC FROM treasury ORDER BY id DESC LIMIT 1), 0)
+    UNION ALL
+    SELECT 'genesis', 'BU', -COALESCE((SELECT balance_bu::NUMERIC FROM treasury ORDER BY id LIMIT 1), 0)
+),
+plugged (account, unit, amount) AS (
+    SELECT account, unit, amount FROM balances
+    UNION ALL
+    SELECT 'adjustment', unit, -SUM(amount) FROM balances GROUP BY unit
+)
+INSERT INTO journal_postings (entry_id, account, unit, amount)
+SELECT opening.id, plugged.account, plugged.unit, plugged.amount
+FROM opening, plugged
+WHERE plugged.amount <> 0;
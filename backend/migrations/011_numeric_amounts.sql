@@ -0,0 +1,62 @@
+-- Exact amounts: every WAD value becomes a NUMERIC(78,0) integer with a real
+-- non-negativity check (the old TEXT checks compared strings lexically).
+-- Fractional base units written by earlier builds are truncated.
+
+-- UE balances
+ALTER TABLE ue_balances DROP CONSTRAINT IF EXISTS ue_balances_balance_check;
+ALTER TABLE ue_balances ALTER COLUMN balance DROP DEFAULT;
+ALTER TABLE ue_balances ALTER COLUMN balance TYPE NUMERIC(78, 0) USING trunc(balance::NUMERIC);
+ALTER TABLE ue_balances ALTER COLUMN balance SET DEFAULT 0;
+ALTER TABLE ue_balances ADD CONSTRAINT ue_balances_balance_check CHECK (balance >= 0);
+
+-- BU balances
+ALTER TABLE bu_balances DROP CONSTRAINT IF EXISTS bu_balances_balance_check;
+ALTER TABLE bu_balances ALTER COLUMN balance DROP DEFAULT;
+ALTER TABLE bu_balances ALTER COLUMN balance TYPE NUMERIC(78, 0) USING trunc(balance::NUMERIC);
+ALTER TABLE bu_balances ALTER COLUMN balance SET DEFAULT 0;
+ALTER TABLE bu_balances ADD CONSTRAINT bu_balances_balance_check CHECK (balance >= 0);
+
+-- Treasury
+ALTER TABLE treasury DROP CONSTRAINT IF EXISTS treasury_balance_bu_check;
+ALTER TABLE treasury ALTER COLUMN balance_bu DROP DEFAULT;
+ALTER TABLE treasury ALTER COLUMN balance_bu TYPE NUMERIC(78, 0) USING trunc(balance_bu::NUMERIC);
+ALTER TABLE treasury ALTER COLUMN balance_bu SET DEFAULT 0;
+ALTER TABLE treasury ADD CONSTRAINT treasury_balance_bu_check CHECK (balance_bu >= 0);
+
+-- UBI claims
+ALTER TABLE ubi_claims ALTER COLUMN amount_ue TYPE NUMERIC(78, 0) USING trunc(amount_ue::NUMERIC);
+ALTER TABLE ubi_claims ADD CONSTRAINT ubi_claims_amount_ue_check CHECK (amount_ue > 0);
+
+-- Conversions
+ALTER TABLE pending_conversions ALTER COLUMN amount_ue TYPE NUMERIC(78, 0) USING trunc(amount_ue::NUMERIC);
+ALTER TABLE pending_conversions ALTER COLUMN amount_bu TYPE NUMERIC(78, 0) USING trunc(amount_bu::NUMERIC);
+ALTER TABLE pending_conversions ALTER COLUMN rate_index TYPE NUMERIC(78, 0) USING trunc(rate_index::NUMERIC);
+ALTER TABLE pending_conversions ADD CONSTRAINT pending_conversions_amount_ue_check CHECK (amount_ue > 0);
+ALTER TABLE pending_conversions ADD CONSTRAINT pending_conversions_amount_bu_check CHECK (amount_bu >= 0);
+ALTER TABLE pending_conversions ADD CONSTRAINT pending_conversions_rate_index_check CHECK (rate_index > 0);
+
+ALTER TABLE converted_this_epoch ALTER COLUMN amount_ue TYPE NUMERIC(78, 0) USING trunc(amount_ue::NUMERIC);
+ALTER TABLE converted_this_epoch ADD CONSTRAINT converted_this_epoch_amount_ue_check CHECK (amount_ue >= 0);
+
+-- Rate index and oracle data (inflation may be negative)
+ALTER TABLE rate_index ALTER COLUMN rate_index_wad TYPE NUMERIC(78, 0) USING trunc(rate_index_wad::NUMERIC);
+ALTER TABLE rate_index ALTER COLUMN current_decay_rate_wad TYPE NUMERIC(78, 0) USING trunc(current_decay_rate_wad::NUMERIC);
+ALTER TABLE rate_index ADD CONSTRAINT rate_index_rate_index_wad_check CHECK (rate_index_wad >= 0);
+ALTER TABLE rate_index ADD CONSTRAINT rate_index_current_decay_rate_wad_check CHECK (current_decay_rate_wad >= 0);
+
+ALTER TABLE region_oracle_data ALTER COLUMN current_basket_index_wad TYPE NUMERIC(78, 0) USING trunc(current_basket_index_wad::NUMERIC);
+ALTER TABLE region_oracle_data ALTER COLUMN current_inflation_rate_wad TYPE NUMERIC(78, 0) USING trunc(current_inflation_rate_wad::NUMERIC);
+ALTER TABLE region_oracle_data ADD CONSTRAINT region_oracle_data_current_basket_index_wad_check CHECK (current_basket_index_wad >= 0);
+
+-- Constitution
+ALTER TABLE constitution_parameters ALTER COLUMN ue_mint_per_epoch TYPE NUMERIC(78, 0) USING trunc(ue_mint_per_epoch::NUMERIC);
+ALTER TABLE constitution_parameters ALTER COLUMN conversion_cap_ue TYPE NUMERIC(78, 0) USING trunc(conversion_cap_ue::NUMERIC);
+ALTER TABLE constitution_parameters ADD CONSTRAINT constitution_parameters_ue_mint_per_epoch_check CHECK (ue_mint_per_epoch >= 0);
+ALTER TABLE constitution_parameters ADD CONSTRAINT constitution_parameters_conversion_cap_ue_check CHECK (conversion_cap_ue >= 0);
+
+-- Payments and transfers
+ALTER TABLE ue_payments ALTER COLUMN amount_ue TYPE NUMERIC(78, 0) USING trunc(amount_ue::NUMERIC);
+ALTER TABLE ue_payments ADD CONSTRAINT ue_payments_amount_ue_check CHECK (amount_ue > 0);
+
+ALTER TABLE bu_transfers ALTER COLUMN amount_bu TYPE NUMERIC(78, 0) USING trunc(amount_bu::NUMERIC);
+ALTER TABLE bu_transfers ADD CONSTRAINT bu_transfers_amount_bu_check CHECK (amount_bu > 0);
@@ -0,0 +1,16 @@
+-- Merchant invoices, settled by a UE payment
+
+CREATE TABLE IF NOT EXISTS invoices (
+    id BIGSERIAL PRIMARY KEY,
+    merchant_wallet TEXT NOT NULL,
+    amount_ue NUMERIC(78, 0) NOT NULL CHECK (amount_ue > 0),
+    reference TEXT NOT NULL,
+    expiry_epoch INTEGER NOT NULL, -- last epoch in which it can be paid
+    status TEXT NOT NULL DEFAULT 'open' CHECK (status IN ('open', 'paid', 'expired')),
+    payment_id BIGINT REFERENCES ue_payments(id),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    CHECK ((status = 'paid') = (payment_id IS NOT NULL))
+);
+
+CREATE INDEX IF NOT EXISTS idx_invoices_merchant ON invoices(merchant_wallet);
+CREATE INDEX IF NOT EXISTS idx_invoices_open ON invoices(expiry_epoch) WHERE status = 'open';
@@ -0,0 +1,28 @@
+-- Standing orders: recurring UE payments, once per epoch
+
+CREATE TABLE IF NOT EXISTS standing_orders (
+    id BIGSERIAL PRIMARY KEY,
+    person_id BYTEA NOT NULL, -- payer; pays from their current wallet
+    to_wallet TEXT NOT NULL,
+    amount_ue NUMERIC(78, 0) NOT NULL CHECK (amount_ue > 0),
+    memo TEXT,
+    offset_seconds INTEGER NOT NULL CHECK (offset_seconds >= 0), -- point in the epoch to run at
+    next_epoch INTEGER NOT NULL, -- first epoch not yet executed or skipped
+    status TEXT NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'cancelled')),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_standing_orders_person ON standing_orders(person_id);
+CREATE INDEX IF NOT EXISTS idx_standing_orders_due ON standing_orders(next_epoch) WHERE status = 'active';
+
+-- One row per order and epoch: the payment made, or why it was skipped
+CREATE TABLE IF NOT EXISTS standing_order_executions (
+    id BIGSERIAL PRIMARY KEY,
+    order_id BIGINT NOT NULL REFERENCES standing_orders(id),
+    epoch INTEGER NOT NULL,
+    payment_id BIGINT REFERENCES ue_payments(id),
+    skip_reason TEXT,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    UNIQUE (order_id, epoch),
+    CHECK ((payment_id IS NULL) <> (skip_reason IS NULL))
+);
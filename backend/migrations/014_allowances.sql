@@ -0,0 +1,27 @@
+-- UE allowances: a person lets another wallet spend up to an amount per epoch
+
+CREATE TABLE IF NOT EXISTS ue_allowances (
+    id BIGSERIAL PRIMARY KEY,
+    person_id BYTEA NOT NULL, -- grantor; spends come from their current wallet
+    spender_wallet TEXT NOT NULL,
+    amount_per_epoch NUMERIC(78, 0) NOT NULL CHECK (amount_per_epoch > 0),
+    status TEXT NOT NULL DEFAULT 'active' CHECK (status IN ('active', 'revoked')),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+-- At most one active allowance per grantor and spender; approving again changes its amount
+CREATE UNIQUE INDEX IF NOT EXISTS idx_ue_allowances_active
+    ON ue_allowances(person_id, spender_wallet) WHERE status = 'active';
+CREATE INDEX IF NOT EXISTS idx_ue_allowances_spender ON ue_allowances(spender_wallet);
+
+-- Payments made under an allowance
+CREATE TABLE IF NOT EXISTS allowance_spends (
+    id BIGSERIAL PRIMARY KEY,
+    allowance_id BIGINT NOT NULL REFERENCES ue_allowances(id),
+    epoch INTEGER NOT NULL,
+    payment_id BIGINT NOT NULL UNIQUE REFERENCES ue_payments(id),
+    amount_ue NUMERIC(78, 0) NOT NULL CHECK (amount_ue > 0),
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW()
+);
+
+CREATE INDEX IF NOT EXISTS idx_allowance_spends_epoch ON allowance_spends(allowance_id, epoch);
@@ -0,0 +1,16 @@
+-- Idempotency keys: the stored response to a POST, replayed for retries
+-- Scoped to the requesting wallet and the endpoint; rows expire after the retention window
+
+CREATE TABLE IF NOT EXISTS idempotency_keys (
+    wallet_address TEXT NOT NULL, -- caller: the bearer token's wallet, else wallet_address:... or person_id:... from the body
+    endpoint TEXT NOT NULL, -- method and path
+    idempotency_key TEXT NOT NULL,
+    request_hash TEXT NOT NULL, -- SHA-256 of the request body
+    status_code INTEGER, -- NULL while the first request is in flight
+    content_type TEXT,
+    response_body BYTEA,
+    created_at TIMESTAMP WITH TIME ZONE NOT NULL DEFAULT NOW(),
+    PRIMARY KEY (wallet_address, endpoint, idempotency_key)
+);
+
+CREATE INDEX IF NOT EXISTS idx_idempotency_keys_created ON idempotency_keys(created_at);
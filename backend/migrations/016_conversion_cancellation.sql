@@ -0,0 +1,9 @@
+-- Cancelling pending conversions during the delay window
+
+ALTER TABLE pending_conversions DROP CONSTRAINT IF EXISTS pending_conversions_status_check;
+ALTER TABLE pending_conversions ADD CONSTRAINT pending_conversions_status_check
+    CHECK (status IN ('pending', 'unlocked', 'claimed', 'cancelled'));
+
+-- Whether a cancelled conversion's fee goes back to the person (genesis: kept)
+ALTER TABLE constitution_parameters
+    ADD COLUMN IF NOT EXISTS cancellation_refunds_fee BOOLEAN NOT NULL DEFAULT false;
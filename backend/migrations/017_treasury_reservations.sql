@@ -0,0 +1,11 @@
+-- Treasury BU reserved for conversions when they are requested
+-- balance_bu is what the treasury can still promise; reserved_bu is owed
+-- to requested conversions and leaves on claim or returns on cancellation
+
+ALTER TABLE treasury ADD COLUMN IF NOT EXISTS reserved_bu NUMERIC(78, 0) NOT NULL DEFAULT 0;
+ALTER TABLE treasury DROP CONSTRAINT IF EXISTS treasury_reserved_bu_check;
+ALTER TABLE treasury ADD CONSTRAINT treasury_reserved_bu_check CHECK (reserved_bu >= 0);
+
+-- Conversions requested before reservations reserved nothing; they draw
+-- on balance_bu when claimed
+ALTER TABLE pending_conversions ADD COLUMN IF NOT EXISTS reserved BOOLEAN NOT NULL DEFAULT false;
@@ -0,0 +1,11 @@
+-- Rationing conversions when the treasury runs low
+-- Below the threshold, requests wait as 'queued' until their epoch ends and
+-- are then filled pro rata; a request that gets nothing ends 'refunded'
+
+ALTER TABLE pending_conversions DROP CONSTRAINT IF EXISTS pending_conversions_status_check;
+ALTER TABLE pending_conversions ADD CONSTRAINT pending_conversions_status_check
+    CHECK (status IN ('queued', 'pending', 'unlocked', 'claimed', 'cancelled', 'refunded'));
+
+-- Unreserved treasury BU below which conversions are rationed (genesis: 100,000 BU)
+ALTER TABLE constitution_parameters
+    ADD COLUMN IF NOT EXISTS rationing_threshold_bu NUMERIC(78, 0) NOT NULL DEFAULT 100000000000000000000000;
@@ -0,0 +1,7 @@
+-- Settlement lookups for per-epoch supply
+-- /api/supply/epochs finds when each conversion was claimed or cancelled
+-- by the conversion_id in the event payload
+
+CREATE INDEX IF NOT EXISTS idx_events_conversion_settled
+    ON events (((event_data->>'conversion_id')::BIGINT), created_at)
+    WHERE event_type IN ('ConversionClaimed', 'ConversionCancelled');
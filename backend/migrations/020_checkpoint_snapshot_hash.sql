@@ -0,0 +1,6 @@
+-- Checkpoints that sign their full snapshot
+-- From leaf version 3 the signed message also carries the SHA-256 of the
+-- snapshot's canonical encoding, so replay can trust all of it, not only
+-- the parts the leaves commit to
+
+ALTER TABLE state_checkpoints ADD COLUMN IF NOT EXISTS snapshot_hash TEXT;
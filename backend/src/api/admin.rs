@@ -1,6 +1,10 @@
 //! Admin endpoints (forkability)
 
 use actix_web::{get, post, web, HttpResponse, Result};
+use crate::services::replay::ReplayService;
+use crate::services::event_chain::EventChainService;
+use crate::config::Config;
+use crate::utils::canonical::to_canonical_bytes;
 use sqlx::PgPool;
 use serde::{Deserialize, Serialize};
 use log::info;
@@ -16,9 +20,19 @@ struct SystemState {
     events: Vec<serde_json::Value>,
 }
 
+#[derive(Deserialize)]
+pub struct ExportQuery {
+    format: Option<String>, // "canonical" for the canonical byte encoding
+}
+
+/// Full state dump in one response
+///
+/// Loads every table into memory; large deployments should follow
+/// GET /api/events instead
 #[get("/api/admin/export-state")]
 pub async fn export_state(
     pool: web::Data<PgPool>,
+    query: web::Query<ExportQuery>,
 ) -> Result<HttpResponse> {
     // Export all state
     let users = sqlx::query!("SELECT * FROM users")
@@ -67,6 +81,62 @@ pub async fn export_state(
     };
     
     info!("State exported");
+    
+    if query.format.as_deref() == Some("canonical") {
+        let bytes = to_canonical_bytes(&state)
+            .map_err(|e| actix_web::error::ErrorInternalServerError(e))?;
+        return Ok(HttpResponse::Ok().content_type("application/json").body(bytes));
+    }
     Ok(HttpResponse::Ok().json(state))
 }
 
+#[derive(Deserialize)]
+pub struct ReplayQuery {
+    from_checkpoint: Option<i32>, // epoch of a checkpoint to start from
+}
+
+#[get("/api/admin/replay")]
+pub async fn replay_events(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    query: web::Query<ReplayQuery>,
+) -> Result<HttpResponse> {
+    let replay_service = ReplayService::new(pool.get_ref().clone(), config.genesis_timestamp);
+    
+    let result = match query.from_checkpoint {
+        Some(epoch) => replay_service.verify_from_checkpoint(epoch).await,
+        None => replay_service.verify().await,
+    };
+    
+    match result {
+        Ok(report) => {
+            info!("Replay finished: {} divergences", report.divergences.len());
+            Ok(HttpResponse::Ok().json(report))
+        }
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
+#[get("/api/admin/verify-chain")]
+pub async fn verify_event_chain(
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse> {
+    let chain_service = EventChainService::new(pool.get_ref().clone());
+    
+    match chain_service.verify().await {
+        Ok(report) => {
+            info!("Event chain verified: valid = {}", report.valid);
+            Ok(HttpResponse::Ok().json(report))
+        }
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
@@ -0,0 +1,146 @@
+//! UE allowance endpoints
+
+use actix_web::{delete, get, post, web, HttpResponse, Result};
+use crate::config::Config;
+use crate::models::allowance::AllowanceRequest;
+use crate::models::payment::PaymentRequest;
+use crate::services::registry::RegistryService;
+use crate::services::allowance::AllowanceService;
+use crate::utils::auth::WalletAddress;
+use sqlx::PgPool;
+use log::info;
+
+/// Let another wallet spend the requesting wallet's person's UE
+#[post("/api/allowances")]
+pub async fn approve_allowance(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    wallet: WalletAddress,
+    req: web::Json<AllowanceRequest>,
+) -> Result<HttpResponse> {
+    let registry = RegistryService::new(pool.get_ref().clone());
+    let allowances = AllowanceService::new(pool.get_ref().clone(), registry, config.genesis_timestamp);
+    
+    match allowances.approve(&wallet.to_string(), req.into_inner()).await {
+        Ok(allowance) => {
+            info!("Allowance {} approved by {}", allowance.id, wallet.to_string());
+            Ok(HttpResponse::Ok().json(allowance))
+        }
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
+/// Allowances granted by the requesting wallet's person
+#[get("/api/allowances")]
+pub async fn list_allowances(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    wallet: WalletAddress,
+) -> Result<HttpResponse> {
+    let registry = RegistryService::new(pool.get_ref().clone());
+    let allowances = AllowanceService::new(pool.get_ref().clone(), registry, config.genesis_timestamp);
+    
+    match allowances.granted(&wallet.to_string()).await {
+        Ok(granted) => Ok(HttpResponse::Ok().json(granted)),
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
+/// Active allowances the requesting wallet may spend from
+#[get("/api/allowances/received")]
+pub async fn list_received_allowances(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    wallet: WalletAddress,
+) -> Result<HttpResponse> {
+    let registry = RegistryService::new(pool.get_ref().clone());
+    let allowances = AllowanceService::new(pool.get_ref().clone(), registry, config.genesis_timestamp);
+    
+    match allowances.received(&wallet.to_string()).await {
+        Ok(received) => Ok(HttpResponse::Ok().json(received)),
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
+/// Payments made under an allowance
+#[get("/api/allowances/{id}/spends")]
+pub async fn get_allowance_spends(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    wallet: WalletAddress,
+    path: web::Path<i64>,
+) -> Result<HttpResponse> {
+    let registry = RegistryService::new(pool.get_ref().clone());
+    let allowances = AllowanceService::new(pool.get_ref().clone(), registry, config.genesis_timestamp);
+    
+    match allowances.spends(&wallet.to_string(), path.into_inner()).await {
+        Ok(spends) => Ok(HttpResponse::Ok().json(spends)),
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
+/// Pay from the grantor's wallet under an allowance held by the requesting wallet
+#[post("/api/allowances/{id}/spend")]
+pub async fn spend_allowance(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    wallet: WalletAddress,
+    path: web::Path<i64>,
+    req: web::Json<PaymentRequest>,
+) -> Result<HttpResponse> {
+    let registry = RegistryService::new(pool.get_ref().clone());
+    let allowances = AllowanceService::new(pool.get_ref().clone(), registry, config.genesis_timestamp);
+    
+    match allowances.spend(&wallet.to_string(), path.into_inner(), req.into_inner()).await {
+        Ok(payment) => Ok(HttpResponse::Ok().json(payment)),
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
+/// Revoke an allowance granted by the requesting wallet's person
+#[delete("/api/allowances/{id}")]
+pub async fn revoke_allowance(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    wallet: WalletAddress,
+    path: web::Path<i64>,
+) -> Result<HttpResponse> {
+    let allowance_id = path.into_inner();
+    let registry = RegistryService::new(pool.get_ref().clone());
+    let allowances = AllowanceService::new(pool.get_ref().clone(), registry, config.genesis_timestamp);
+    
+    match allowances.revoke(&wallet.to_string(), allowance_id).await {
+        Ok(()) => {
+            info!("Allowance {} revoked by {}", allowance_id, wallet.to_string());
+            Ok(HttpResponse::Ok().json(serde_json::json!({
+                "status": "ok"
+            })))
+        }
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
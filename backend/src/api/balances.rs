@@ -1,6 +1,7 @@
 //! Balance query endpoints
 
 use actix_web::{get, web, HttpResponse, Result};
+use rust_decimal::Decimal;
 use sqlx::PgPool;
 
 #[get("/api/balances/ue")]
@@ -18,7 +19,7 @@ pub async fn get_ue_balance(
     
     Ok(HttpResponse::Ok().json(serde_json::json!({
         "wallet": wallet.to_string(),
-        "balance": balance.unwrap_or_else(|| "0".to_string())
+        "balance": balance.unwrap_or(Decimal::ZERO)
     })))
 }
 
@@ -37,7 +38,7 @@ pub async fn get_bu_balance(
     
     Ok(HttpResponse::Ok().json(serde_json::json!({
         "wallet": wallet.to_string(),
-        "balance": balance.unwrap_or_else(|| "0".to_string())
+        "balance": balance.unwrap_or(Decimal::ZERO)
     })))
 }
 
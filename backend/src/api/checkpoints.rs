@@ -0,0 +1,52 @@
+//! Checkpoint endpoints
+//! 
+//! Signed per-epoch commitments to system state
+
+use actix_web::{get, web, HttpResponse, Result};
+use crate::models::checkpoint::CheckpointResponse;
+use crate::services::checkpoint::CheckpointService;
+use crate::config::Config;
+use sqlx::PgPool;
+
+#[get("/api/checkpoints/latest")]
+pub async fn get_latest_checkpoint(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    let checkpoint_service = CheckpointService::new(pool.get_ref().clone(), config.genesis_timestamp, None);
+    
+    match checkpoint_service.latest_checkpoint().await {
+        Ok(Some(checkpoint)) => Ok(HttpResponse::Ok().json(CheckpointResponse::from(checkpoint))),
+        Ok(None) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": "No checkpoints yet"
+        }))),
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
+#[get("/api/checkpoints/{epoch}")]
+pub async fn get_checkpoint(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    path: web::Path<i32>,
+) -> Result<HttpResponse> {
+    let epoch = path.into_inner();
+    let checkpoint_service = CheckpointService::new(pool.get_ref().clone(), config.genesis_timestamp, None);
+    
+    match checkpoint_service.get_checkpoint(epoch).await {
+        Ok(Some(checkpoint)) => Ok(HttpResponse::Ok().json(CheckpointResponse::from(checkpoint))),
+        Ok(None) => Ok(HttpResponse::NotFound().json(serde_json::json!({
+            "error": format!("No checkpoint for epoch {}", epoch)
+        }))),
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
@@ -1,19 +1,22 @@
 //! Conversion endpoints
 
-use actix_web::{post, web, HttpResponse, Result};
+use actix_web::{get, post, web, HttpResponse, Result};
 use crate::models::conversion::{ConversionRequest, ConversionResponse};
 use crate::services::conversion::ConversionService;
 use crate::services::registry::RegistryService;
 use crate::services::rate_index::RateIndexService;
 use crate::config::Config;
+use crate::utils::auth::WalletAddress;
 use sqlx::PgPool;
+use serde::Deserialize;
+use rust_decimal::Decimal;
 use log::info;
 
 #[post("/api/conversion/request")]
 pub async fn request_conversion(
     pool: web::Data<PgPool>,
     config: web::Data<Config>,
-    wallet: web::Header<String>,
+    wallet: WalletAddress,
     req: web::Json<ConversionRequest>,
 ) -> Result<HttpResponse> {
     let registry = RegistryService::new(pool.get_ref().clone());
@@ -42,7 +45,7 @@ pub async fn request_conversion(
 pub async fn claim_conversion(
     pool: web::Data<PgPool>,
     config: web::Data<Config>,
-    wallet: web::Header<String>,
+    wallet: WalletAddress,
     conversion_id: web::Path<i64>,
 ) -> Result<HttpResponse> {
     let registry = RegistryService::new(pool.get_ref().clone());
@@ -69,3 +72,67 @@ pub async fn claim_conversion(
     }
 }
 
+/// Cancel a conversion still in its delay window and get the UE back
+#[post("/api/conversion/cancel/{id}")]
+pub async fn cancel_conversion(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    wallet: WalletAddress,
+    conversion_id: web::Path<i64>,
+) -> Result<HttpResponse> {
+    let registry = RegistryService::new(pool.get_ref().clone());
+    let rate_index = RateIndexService::new(pool.get_ref().clone(), config.genesis_timestamp);
+    let conversion_service = ConversionService::new(
+        pool.get_ref().clone(),
+        registry,
+        rate_index,
+        config.genesis_timestamp,
+    );
+    
+    match conversion_service.cancel_conversion(&wallet.to_string(), conversion_id.into_inner()).await {
+        Ok(refund_ue) => {
+            info!("Conversion cancelled: {} UE refunded to {}", refund_ue, wallet.to_string());
+            Ok(HttpResponse::Ok().json(serde_json::json!({
+                "refund_ue": refund_ue
+            })))
+        }
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
+#[derive(Deserialize)]
+pub struct ConversionQuoteQuery {
+    amount_ue: Decimal,
+}
+
+/// Preview a conversion request without making it
+#[get("/api/conversion/quote")]
+pub async fn quote_conversion(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    wallet: WalletAddress,
+    query: web::Query<ConversionQuoteQuery>,
+) -> Result<HttpResponse> {
+    let registry = RegistryService::new(pool.get_ref().clone());
+    let rate_index = RateIndexService::new(pool.get_ref().clone(), config.genesis_timestamp);
+    let conversion_service = ConversionService::new(
+        pool.get_ref().clone(),
+        registry,
+        rate_index,
+        config.genesis_timestamp,
+    );
+    
+    match conversion_service.quote(&wallet.to_string(), query.amount_ue).await {
+        Ok(quote) => Ok(HttpResponse::Ok().json(quote)),
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
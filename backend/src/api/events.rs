@@ -0,0 +1,128 @@
+//! Event feed endpoints
+//! 
+//! NDJSON pages and a Server-Sent Events live tail over the event log.
+//! Consumers resume from the id of the last event they processed.
+
+use actix_web::{get, web, HttpRequest, HttpResponse, Result};
+use actix_web::web::Bytes;
+use crate::events::Event;
+use crate::services::event_feed::EventFeedService;
+use futures::stream;
+use serde::Deserialize;
+use sqlx::PgPool;
+use std::time::Duration;
+
+/// Default and maximum number of events returned by one NDJSON request
+const FEED_DEFAULT_LIMIT: i64 = 1000;
+const FEED_MAX_LIMIT: i64 = 10_000;
+
+/// Events read from the database per round trip
+const FEED_PAGE_SIZE: i64 = 500;
+
+/// How often the live tail polls for new events
+const STREAM_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Idle polls between SSE keep-alive comments
+const STREAM_KEEPALIVE_POLLS: u32 = 15;
+
+#[derive(Debug, Deserialize)]
+pub struct EventFeedQuery {
+    pub since_id: Option<i64>,
+    pub limit: Option<i64>,
+}
+
+#[get("/api/events")]
+pub async fn get_events(
+    pool: web::Data<PgPool>,
+    query: web::Query<EventFeedQuery>,
+) -> Result<HttpResponse> {
+    let since_id = query.since_id.unwrap_or(0);
+    let limit = query.limit.unwrap_or(FEED_DEFAULT_LIMIT).clamp(1, FEED_MAX_LIMIT);
+    let feed = EventFeedService::new(pool.get_ref().clone());
+    
+    // Stream page by page so large ranges never sit in memory at once
+    let body = stream::try_unfold((feed, since_id, limit), |(feed, cursor, remaining)| async move {
+        if remaining <= 0 {
+            return Ok(None);
+        }
+        
+        let page = feed
+            .fetch_page(cursor, remaining.min(FEED_PAGE_SIZE))
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+        
+        let last = match page.last() {
+            Some(event) => event.id,
+            None => return Ok(None),
+        };
+        
+        let mut chunk = String::new();
+        for event in &page {
+            chunk.push_str(&serde_json::to_string(event)?);
+            chunk.push('\n');
+        }
+        
+        let remaining = remaining - page.len() as i64;
+        Ok::<_, actix_web::Error>(Some((Bytes::from(chunk), (feed, last, remaining))))
+    });
+    
+    Ok(HttpResponse::Ok()
+        .content_type("application/x-ndjson")
+        .streaming(body))
+}
+
+#[get("/api/events/stream")]
+pub async fn stream_events(
+    pool: web::Data<PgPool>,
+    query: web::Query<EventFeedQuery>,
+    req: HttpRequest,
+) -> Result<HttpResponse> {
+    // Reconnecting EventSource clients send the last id they saw
+    let since_id = req
+        .headers()
+        .get("Last-Event-ID")
+        .and_then(|v| v.to_str().ok())
+        .and_then(|v| v.parse().ok())
+        .or(query.since_id)
+        .unwrap_or(0);
+    let feed = EventFeedService::new(pool.get_ref().clone());
+    
+    let body = stream::try_unfold((feed, since_id, 0u32), |(feed, cursor, idle_polls)| async move {
+        let page = feed
+            .fetch_page(cursor, FEED_PAGE_SIZE)
+            .await
+            .map_err(actix_web::error::ErrorInternalServerError)?;
+        
+        if let Some(last) = page.last().map(|e| e.id) {
+            let mut chunk = String::new();
+            for event in &page {
+                chunk.push_str(&sse_frame(event)?);
+            }
+            return Ok::<_, actix_web::Error>(Some((Bytes::from(chunk), (feed, last, 0))));
+        }
+        
+        tokio::time::sleep(STREAM_POLL_INTERVAL).await;
+        
+        if idle_polls + 1 >= STREAM_KEEPALIVE_POLLS {
+            Ok(Some((Bytes::from_static(b": keep-alive\n\n"), (feed, cursor, 0))))
+        } else {
+            Ok(Some((Bytes::new(), (feed, cursor, idle_polls + 1))))
+        }
+    });
+    
+    Ok(HttpResponse::Ok()
+        .content_type("text/event-stream")
+        .insert_header(("Cache-Control", "no-cache"))
+        .streaming(body))
+}
+
+/// Format one event as an SSE frame
+fn sse_frame(event: &Event) -> Result<String, serde_json::Error> {
+    Ok(format!(
+        "id: {}\nevent: {}\ndata: {}\n\n",
+        event.id,
+        event.event_type,
+        serde_json::to_string(event)?
+    ))
+}
+
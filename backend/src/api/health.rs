@@ -1,11 +1,31 @@
 //! Health check endpoint
 
-use actix_web::{get, HttpResponse, Result};
+use actix_web::{get, web, HttpResponse, Result};
+use crate::services::supply::SupplyStatus;
 
+/// Fails while any supply invariant is violated
+///
+/// Serves the supply worker's latest check rather than running one, so
+/// probes stay cheap
 #[get("/health")]
-pub async fn health() -> Result<HttpResponse> {
-    Ok(HttpResponse::Ok().json(serde_json::json!({
-        "status": "ok"
-    })))
+pub async fn health(
+    status: web::Data<SupplyStatus>,
+) -> Result<HttpResponse> {
+    match status.latest() {
+        Some(Ok(report)) if report.healthy => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "status": "ok"
+        }))),
+        Some(Ok(report)) => Ok(HttpResponse::ServiceUnavailable().json(serde_json::json!({
+            "status": "invariant_violated",
+            "violations": report.violations
+        }))),
+        Some(Err(e)) => Ok(HttpResponse::ServiceUnavailable().json(serde_json::json!({
+            "status": "error",
+            "error": e
+        }))),
+        None => Ok(HttpResponse::ServiceUnavailable().json(serde_json::json!({
+            "status": "starting"
+        }))),
+    }
 }
 
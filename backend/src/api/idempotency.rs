@@ -0,0 +1,167 @@
+//! Idempotency-Key middleware
+//!
+//! Wraps every POST: a request carrying an Idempotency-Key header runs
+//! once per caller, endpoint and key, and retries get the stored response.
+//! The caller is the wallet authenticated by the bearer token, or on
+//! endpoints used without one, the wallet or person named in the body
+
+use actix_web::body::{self, BoxBody, MessageBody};
+use actix_web::dev::{Payload, ServiceRequest, ServiceResponse};
+use actix_web::http::{header, Method, StatusCode};
+use actix_web::middleware::Next;
+use actix_web::{web, Error, HttpResponse};
+use crate::config::Config;
+use crate::services::idempotency::{IdempotencyOutcome, IdempotencyService, IDEMPOTENCY_KEY_MAX_LEN};
+use crate::utils::auth::authenticate;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use log::error;
+use hex;
+
+/// Request header carrying the client's key
+const IDEMPOTENCY_KEY_HEADER: &str = "idempotency-key";
+
+/// Response header set on replayed responses
+const IDEMPOTENT_REPLAYED_HEADER: &str = "idempotent-replayed";
+
+/// Body fields naming the caller of an endpoint used without a token
+const BODY_IDENTITY_FIELDS: &[&str] = &["wallet_address", "person_id"];
+
+pub async fn idempotency(
+    mut req: ServiceRequest,
+    next: Next<impl MessageBody + 'static>,
+) -> Result<ServiceResponse<BoxBody>, Error> {
+    if req.method() != Method::POST {
+        return Ok(next.call(req).await?.map_into_boxed_body());
+    }
+    let key = match req.headers().get(IDEMPOTENCY_KEY_HEADER) {
+        None => return Ok(next.call(req).await?.map_into_boxed_body()),
+        Some(value) => value.to_str().map(str::trim).unwrap_or_default().to_string(),
+    };
+    if key.is_empty() || key.len() > IDEMPOTENCY_KEY_MAX_LEN {
+        return Ok(respond(req, HttpResponse::BadRequest().json(serde_json::json!({
+            "error": format!("Idempotency-Key must be 1 to {} characters", IDEMPOTENCY_KEY_MAX_LEN)
+        }))));
+    }
+    let Some(pool) = req.app_data::<web::Data<PgPool>>().map(|pool| pool.get_ref().clone()) else {
+        return Ok(next.call(req).await?.map_into_boxed_body());
+    };
+    
+    let endpoint = format!("{} {}", req.method(), req.path());
+    
+    // Read the body to fingerprint it, then put it back for the handler
+    let bytes = req.extract::<web::Bytes>().await?;
+    let request_hash = hex::encode(Sha256::digest(&bytes));
+    let caller = caller_scope(&req, &bytes);
+    req.set_payload(Payload::from(bytes));
+    
+    // An unscoped key would be shared by everyone who picked the same one
+    let Some(scope) = caller else {
+        return Ok(respond(req, HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Idempotency-Key requires a bearer token, or a wallet_address or person_id in the body"
+        }))));
+    };
+    
+    let idempotency = IdempotencyService::new(pool);
+    let outcome = match idempotency.begin(&scope, &endpoint, &key, &request_hash).await {
+        Ok(outcome) => outcome,
+        Err(e) => {
+            error!("Idempotency key lookup failed: {}", e);
+            return Ok(respond(req, HttpResponse::InternalServerError().json(serde_json::json!({
+                "error": e.to_string()
+            }))));
+        }
+    };
+    
+    match outcome {
+        IdempotencyOutcome::Started => {}
+        IdempotencyOutcome::Replay { status_code, content_type, body } => {
+            let status = StatusCode::from_u16(status_code).unwrap_or(StatusCode::OK);
+            let mut response = HttpResponse::build(status);
+            if let Some(content_type) = content_type {
+                response.content_type(content_type);
+            }
+            response.insert_header((IDEMPOTENT_REPLAYED_HEADER, "true"));
+            return Ok(respond(req, response.body(body)));
+        }
+        IdempotencyOutcome::InProgress => {
+            return Ok(respond(req, HttpResponse::Conflict().json(serde_json::json!({
+                "error": "A request with this Idempotency-Key is still in progress"
+            }))));
+        }
+        IdempotencyOutcome::Mismatch => {
+            return Ok(respond(req, HttpResponse::UnprocessableEntity().json(serde_json::json!({
+                "error": "Idempotency-Key was already used for a different request"
+            }))));
+        }
+    }
+    
+    let response = match next.call(req).await {
+        Ok(response) => response,
+        Err(e) => {
+            release(&idempotency, &scope, &endpoint, &key).await;
+            return Err(e);
+        }
+    };
+    
+    // Server errors are not stored, so a retry runs the request again
+    if response.status().is_server_error() {
+        release(&idempotency, &scope, &endpoint, &key).await;
+        return Ok(response.map_into_boxed_body());
+    }
+    
+    let (http_req, response) = response.into_parts();
+    let (head, body) = response.into_parts();
+    let bytes = match body::to_bytes(body).await {
+        Ok(bytes) => bytes,
+        Err(_) => {
+            release(&idempotency, &scope, &endpoint, &key).await;
+            return Ok(ServiceResponse::new(http_req, HttpResponse::InternalServerError().finish()));
+        }
+    };
+    let content_type = head.headers()
+        .get(header::CONTENT_TYPE)
+        .and_then(|value| value.to_str().ok());
+    
+    if let Err(e) = idempotency
+        .complete(&scope, &endpoint, &key, head.status().as_u16(), content_type, &bytes)
+        .await
+    {
+        error!("Storing idempotent response failed: {}", e);
+    }
+    
+    Ok(ServiceResponse::new(http_req, head.set_body(bytes).map_into_boxed_body()))
+}
+
+/// Who a keyed request is scoped to, if anyone
+///
+/// Body identities are prefixed, so naming a wallet in the body never
+/// shares keys with that wallet's authenticated requests
+fn caller_scope(req: &ServiceRequest, body: &[u8]) -> Option<String> {
+    let authenticated = req.app_data::<web::Data<Config>>()
+        .and_then(|config| authenticate(req.headers(), &config.jwt_secret).ok());
+    if let Some(claims) = authenticated {
+        return Some(claims.wallet_address);
+    }
+    
+    let body: serde_json::Value = serde_json::from_slice(body).ok()?;
+    BODY_IDENTITY_FIELDS.iter().find_map(|field| {
+        body.get(field)
+            .and_then(|value| value.as_str())
+            .map(str::trim)
+            .filter(|value| !value.is_empty())
+            .map(|value| format!("{}:{}", field, value))
+    })
+}
+
+fn respond(req: ServiceRequest, response: HttpResponse) -> ServiceResponse<BoxBody> {
+    let (http_req, _) = req.into_parts();
+    ServiceResponse::new(http_req, response)
+}
+
+async fn release(idempotency: &IdempotencyService, scope: &str, endpoint: &str, key: &str) {
+    if let Err(e) = idempotency.release(scope, endpoint, key).await {
+        error!("Releasing idempotency key failed: {}", e);
+    }
+}
+
@@ -0,0 +1,79 @@
+//! Invoice endpoints
+
+use actix_web::{get, post, web, HttpResponse, Result};
+use crate::config::Config;
+use crate::models::invoice::InvoiceRequest;
+use crate::services::invoice::InvoiceService;
+use crate::services::registry::RegistryService;
+use crate::utils::auth::WalletAddress;
+use sqlx::PgPool;
+use log::info;
+
+/// Issue an invoice payable to the requesting wallet
+#[post("/api/invoices")]
+pub async fn create_invoice(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    wallet: WalletAddress,
+    req: web::Json<InvoiceRequest>,
+) -> Result<HttpResponse> {
+    let registry = RegistryService::new(pool.get_ref().clone());
+    let invoice_service = InvoiceService::new(pool.get_ref().clone(), registry, config.genesis_timestamp);
+    
+    match invoice_service.create(&wallet.to_string(), req.into_inner()).await {
+        Ok(invoice) => {
+            info!("Invoice {} issued by {}", invoice.invoice.id, wallet.to_string());
+            Ok(HttpResponse::Ok().json(invoice))
+        }
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
+/// Get an invoice and its payment URI
+#[get("/api/invoices/{id}")]
+pub async fn get_invoice(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    invoice_id: web::Path<i64>,
+) -> Result<HttpResponse> {
+    let registry = RegistryService::new(pool.get_ref().clone());
+    let invoice_service = InvoiceService::new(pool.get_ref().clone(), registry, config.genesis_timestamp);
+    
+    match invoice_service.get(invoice_id.into_inner()).await {
+        Ok(invoice) => Ok(HttpResponse::Ok().json(invoice)),
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
+/// Pay an open invoice from the requesting wallet
+#[post("/api/invoices/{id}/pay")]
+pub async fn pay_invoice(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    wallet: WalletAddress,
+    invoice_id: web::Path<i64>,
+) -> Result<HttpResponse> {
+    let registry = RegistryService::new(pool.get_ref().clone());
+    let invoice_service = InvoiceService::new(pool.get_ref().clone(), registry, config.genesis_timestamp);
+    
+    match invoice_service.pay(&wallet.to_string(), invoice_id.into_inner()).await {
+        Ok(invoice) => {
+            info!("Invoice {} paid by {}", invoice.invoice.id, wallet.to_string());
+            Ok(HttpResponse::Ok().json(invoice))
+        }
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
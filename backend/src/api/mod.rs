@@ -6,6 +6,18 @@ pub mod admin;
 pub mod health;
 pub mod balances;
 pub mod pending_conversions;
+pub mod events;
+pub mod checkpoints;
+pub mod proofs;
+pub mod webhooks;
+pub mod payments;
+pub mod transfers;
+pub mod invoices;
+pub mod standing_orders;
+pub mod wallets;
+pub mod supply;
+pub mod allowances;
+pub mod idempotency;
 
 pub use users::*;
 pub use ubi::*;
@@ -15,4 +27,8 @@ pub use admin::*;
 pub use health::*;
 pub use balances::*;
 pub use pending_conversions::*;
+pub use events::*;
+pub use checkpoints::*;
+pub use proofs::*;
+pub use webhooks::*;
 
@@ -0,0 +1,33 @@
+//! Payment endpoints
+
+use actix_web::{post, web, HttpResponse, Result};
+use crate::models::payment::PaymentRequest;
+use crate::services::payment::PaymentService;
+use crate::services::registry::RegistryService;
+use crate::utils::auth::WalletAddress;
+use sqlx::PgPool;
+use log::info;
+
+/// Pay UE from the requesting wallet to another wallet
+#[post("/api/payments")]
+pub async fn create_payment(
+    pool: web::Data<PgPool>,
+    wallet: WalletAddress,
+    req: web::Json<PaymentRequest>,
+) -> Result<HttpResponse> {
+    let registry = RegistryService::new(pool.get_ref().clone());
+    let payment_service = PaymentService::new(pool.get_ref().clone(), registry);
+    
+    match payment_service.pay(&wallet.to_string(), req.into_inner()).await {
+        Ok(payment) => {
+            info!("Payment {} sent from {}", payment.id, wallet.to_string());
+            Ok(HttpResponse::Ok().json(payment))
+        }
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
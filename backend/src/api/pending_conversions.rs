@@ -23,9 +23,9 @@ pub async fn get_pending_conversions(
         let conversions = sqlx::query_as!(
             PendingConversion,
             r#"
-            SELECT id, person_id, amount_ue, amount_bu, rate_index, unlock_epoch, status, created_at
+            SELECT id, person_id, amount_ue, amount_bu, rate_index, unlock_epoch, status, reserved, created_at
             FROM pending_conversions
-            WHERE person_id = $1 AND status IN ('pending', 'unlocked')
+            WHERE person_id = $1 AND status IN ('queued', 'pending', 'unlocked')
             ORDER BY unlock_epoch ASC
             "#,
             user.person_id
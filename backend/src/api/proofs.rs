@@ -0,0 +1,35 @@
+//! Merkle inclusion proof endpoints
+//! 
+//! Lets light clients verify a balance against a signed checkpoint
+//! without downloading the full state
+
+use actix_web::{get, web, HttpResponse, Result};
+use crate::services::checkpoint::CheckpointService;
+use crate::config::Config;
+use sqlx::PgPool;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct ProofQuery {
+    epoch: Option<i32>, // defaults to the latest checkpoint
+}
+
+#[get("/api/proofs/{wallet_address}")]
+pub async fn get_wallet_proofs(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    path: web::Path<String>,
+    query: web::Query<ProofQuery>,
+) -> Result<HttpResponse> {
+    let checkpoint_service = CheckpointService::new(pool.get_ref().clone(), config.genesis_timestamp, None);
+    
+    match checkpoint_service.wallet_proofs(&path.into_inner(), query.epoch).await {
+        Ok(proofs) => Ok(HttpResponse::Ok().json(proofs)),
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
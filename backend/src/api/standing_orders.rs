@@ -0,0 +1,103 @@
+//! Standing order endpoints
+
+use actix_web::{delete, get, post, web, HttpResponse, Result};
+use crate::config::Config;
+use crate::models::standing_order::StandingOrderRequest;
+use crate::services::registry::RegistryService;
+use crate::services::standing_order::StandingOrderService;
+use crate::utils::auth::WalletAddress;
+use sqlx::PgPool;
+use log::info;
+
+/// Set up a recurring payment from the requesting wallet
+#[post("/api/standing-orders")]
+pub async fn create_standing_order(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    wallet: WalletAddress,
+    req: web::Json<StandingOrderRequest>,
+) -> Result<HttpResponse> {
+    let registry = RegistryService::new(pool.get_ref().clone());
+    let standing_orders = StandingOrderService::new(pool.get_ref().clone(), registry, config.genesis_timestamp);
+    
+    match standing_orders.create(&wallet.to_string(), req.into_inner()).await {
+        Ok(order) => {
+            info!("Standing order {} created by {}", order.id, wallet.to_string());
+            Ok(HttpResponse::Ok().json(order))
+        }
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
+/// Standing orders of the requesting wallet's person
+#[get("/api/standing-orders")]
+pub async fn list_standing_orders(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    wallet: WalletAddress,
+) -> Result<HttpResponse> {
+    let registry = RegistryService::new(pool.get_ref().clone());
+    let standing_orders = StandingOrderService::new(pool.get_ref().clone(), registry, config.genesis_timestamp);
+    
+    match standing_orders.list(&wallet.to_string()).await {
+        Ok(orders) => Ok(HttpResponse::Ok().json(orders)),
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
+/// Payments made and skipped by a standing order
+#[get("/api/standing-orders/{id}/executions")]
+pub async fn get_standing_order_executions(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    wallet: WalletAddress,
+    path: web::Path<i64>,
+) -> Result<HttpResponse> {
+    let registry = RegistryService::new(pool.get_ref().clone());
+    let standing_orders = StandingOrderService::new(pool.get_ref().clone(), registry, config.genesis_timestamp);
+    
+    match standing_orders.executions(&wallet.to_string(), path.into_inner()).await {
+        Ok(executions) => Ok(HttpResponse::Ok().json(executions)),
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
+/// Cancel a standing order of the requesting wallet's person
+#[delete("/api/standing-orders/{id}")]
+pub async fn cancel_standing_order(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    wallet: WalletAddress,
+    path: web::Path<i64>,
+) -> Result<HttpResponse> {
+    let order_id = path.into_inner();
+    let registry = RegistryService::new(pool.get_ref().clone());
+    let standing_orders = StandingOrderService::new(pool.get_ref().clone(), registry, config.genesis_timestamp);
+    
+    match standing_orders.cancel(&wallet.to_string(), order_id).await {
+        Ok(()) => {
+            info!("Standing order {} cancelled by {}", order_id, wallet.to_string());
+            Ok(HttpResponse::Ok().json(serde_json::json!({
+                "status": "ok"
+            })))
+        }
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
@@ -0,0 +1,43 @@
+//! Supply accounting endpoints
+
+use actix_web::{get, web, HttpResponse, Result};
+use crate::config::Config;
+use crate::services::supply::SupplyService;
+use sqlx::PgPool;
+
+/// Live UE and BU totals and any violated supply invariants
+#[get("/api/supply")]
+pub async fn get_supply(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    let supply_service = SupplyService::new(pool.get_ref().clone(), config.genesis_timestamp);
+    
+    match supply_service.check().await {
+        Ok(report) => Ok(HttpResponse::Ok().json(report)),
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
+/// Supply for every epoch since genesis
+#[get("/api/supply/epochs")]
+pub async fn get_supply_epochs(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+) -> Result<HttpResponse> {
+    let supply_service = SupplyService::new(pool.get_ref().clone(), config.genesis_timestamp);
+    
+    match supply_service.epochs().await {
+        Ok(epochs) => Ok(HttpResponse::Ok().json(epochs)),
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
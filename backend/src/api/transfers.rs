@@ -0,0 +1,31 @@
+//! BU transfer endpoints
+
+use actix_web::{post, web, HttpResponse, Result};
+use crate::models::transfer::BUTransferRequest;
+use crate::services::transfer::TransferService;
+use crate::utils::auth::WalletAddress;
+use sqlx::PgPool;
+use log::info;
+
+/// Send BU from the requesting wallet to another wallet
+#[post("/api/bu/transfers")]
+pub async fn transfer_bu(
+    pool: web::Data<PgPool>,
+    wallet: WalletAddress,
+    req: web::Json<BUTransferRequest>,
+) -> Result<HttpResponse> {
+    let transfer_service = TransferService::new(pool.get_ref().clone());
+    
+    match transfer_service.transfer_bu(&wallet.to_string(), req.into_inner()).await {
+        Ok(transfer) => {
+            info!("BU transfer {} sent from {}", transfer.id, wallet.to_string());
+            Ok(HttpResponse::Ok().json(transfer))
+        }
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
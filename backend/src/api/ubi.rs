@@ -5,6 +5,7 @@ use crate::models::claim::ClaimResponse;
 use crate::services::ubi::UBIService;
 use crate::services::registry::RegistryService;
 use crate::config::Config;
+use crate::utils::auth::WalletAddress;
 use sqlx::PgPool;
 use log::info;
 
@@ -12,7 +13,7 @@ use log::info;
 pub async fn claim_ubi(
     pool: web::Data<PgPool>,
     config: web::Data<Config>,
-    wallet: web::Header<String>,
+    wallet: WalletAddress,
 ) -> Result<HttpResponse> {
     let registry = RegistryService::new(pool.get_ref().clone());
     let ubi_service = UBIService::new(
@@ -1,8 +1,11 @@
 //! User endpoints
 
 use actix_web::{post, web, HttpResponse, Result};
-use crate::models::user::{RegisterUserRequest, ResetWalletRequest, UserResponse};
+use crate::models::user::{RegisterUserRequest, ResetWalletRequest, TokenRequest, TokenResponse, UserResponse, WalletTokenRequest};
 use crate::services::registry::RegistryService;
+use crate::config::Config;
+use crate::utils::auth::{create_token, key_wallet_address, wallet_token_message, Claims, TokenHolder};
+use crate::utils::signing::verify_hex;
 use crate::utils::errors::UBIError;
 use sqlx::PgPool;
 use log::info;
@@ -32,6 +35,90 @@ pub async fn register_user(
     }
 }
 
+/// How far a key wallet's signed timestamp may be from the server clock
+const WALLET_TOKEN_MAX_SKEW_SECONDS: i64 = 300;
+
+/// Issue a bearer token for the person's current wallet, or one they reset away from
+///
+/// Wallet endpoints require it; a wallet reset revokes every token issued before it
+#[post("/api/users/token")]
+pub async fn issue_token(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    req: web::Json<TokenRequest>,
+) -> Result<HttpResponse> {
+    let registry = RegistryService::new(pool.get_ref().clone());
+    
+    let authorized = match registry.authenticate(&req.person_id, &req.mfa_code).await {
+        Ok(user) => registry
+            .token_wallet(&user, req.wallet_address.as_deref())
+            .await
+            .map(|wallet| (user, wallet)),
+        Err(e) => Err(e),
+    };
+    let (user, wallet) = match authorized {
+        Ok(authorized) => authorized,
+        Err(e) => {
+            return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })));
+        }
+    };
+    
+    let claims = Claims::new(
+        req.person_id.clone(),
+        wallet.clone(),
+        TokenHolder::Person { registry_wallet: user.wallet_address },
+    );
+    let token = create_token(&claims, &config.jwt_secret)
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    
+    info!("Token issued: {} for {}", req.person_id, wallet);
+    Ok(HttpResponse::Ok().json(TokenResponse {
+        token,
+        wallet_address: wallet,
+        expires_at: claims.exp,
+    }))
+}
+
+/// Issue a bearer token for a wallet no person holds, such as a merchant's
+///
+/// The wallet address must be the one `public_key` derives, and the
+/// signature must cover the address and a current timestamp
+#[post("/api/wallets/token")]
+pub async fn issue_wallet_token(
+    config: web::Data<Config>,
+    req: web::Json<WalletTokenRequest>,
+) -> Result<HttpResponse> {
+    let Some(wallet) = key_wallet_address(&req.public_key) else {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Public key must be 32 hex-encoded bytes"
+        })));
+    };
+    if (chrono::Utc::now().timestamp() - req.timestamp).abs() > WALLET_TOKEN_MAX_SKEW_SECONDS {
+        return Ok(HttpResponse::BadRequest().json(serde_json::json!({
+            "error": "Timestamp is too far from the server clock"
+        })));
+    }
+    let message = wallet_token_message(&wallet, req.timestamp);
+    if !verify_hex(req.public_key.trim(), message.as_bytes(), req.signature.trim()) {
+        return Ok(HttpResponse::Unauthorized().json(serde_json::json!({
+            "error": "Invalid signature"
+        })));
+    }
+    
+    let claims = Claims::new(req.public_key.trim().to_lowercase(), wallet.clone(), TokenHolder::WalletKey);
+    let token = create_token(&claims, &config.jwt_secret)
+        .map_err(actix_web::error::ErrorInternalServerError)?;
+    
+    info!("Wallet token issued: {}", wallet);
+    Ok(HttpResponse::Ok().json(TokenResponse {
+        token,
+        wallet_address: wallet,
+        expires_at: claims.exp,
+    }))
+}
+
 #[post("/api/users/reset-wallet")]
 pub async fn reset_wallet(
     pool: web::Data<PgPool>,
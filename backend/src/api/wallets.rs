@@ -0,0 +1,38 @@
+//! Wallet statement endpoints
+
+use actix_web::{get, web, HttpResponse, Result};
+use crate::config::Config;
+use crate::services::statement::{statement_csv, StatementService};
+use sqlx::PgPool;
+use serde::Deserialize;
+
+#[derive(Deserialize)]
+pub struct StatementQuery {
+    from_epoch: Option<i32>, // defaults to genesis
+    to_epoch: Option<i32>, // defaults to the current epoch
+    format: Option<String>, // "csv" for CSV, JSON otherwise
+}
+
+/// Every balance change of a wallet in an epoch range, with running balances
+#[get("/api/wallets/{wallet_address}/statement")]
+pub async fn get_wallet_statement(
+    pool: web::Data<PgPool>,
+    config: web::Data<Config>,
+    path: web::Path<String>,
+    query: web::Query<StatementQuery>,
+) -> Result<HttpResponse> {
+    let statements = StatementService::new(pool.get_ref().clone(), config.genesis_timestamp);
+    
+    match statements.statement(&path.into_inner(), query.from_epoch.unwrap_or(0), query.to_epoch).await {
+        Ok(statement) if query.format.as_deref() == Some("csv") => {
+            Ok(HttpResponse::Ok().content_type("text/csv").body(statement_csv(&statement)))
+        }
+        Ok(statement) => Ok(HttpResponse::Ok().json(statement)),
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
@@ -0,0 +1,99 @@
+//! Webhook admin endpoints
+
+use actix_web::{delete, get, post, web, HttpResponse, Result};
+use crate::models::webhook::WebhookRegistration;
+use crate::services::webhook::{RetryPolicy, WebhookService};
+use sqlx::PgPool;
+use log::info;
+
+#[post("/api/admin/webhooks")]
+pub async fn register_webhook(
+    pool: web::Data<PgPool>,
+    req: web::Json<WebhookRegistration>,
+) -> Result<HttpResponse> {
+    let webhook_service = WebhookService::new(pool.get_ref().clone(), RetryPolicy::default());
+    
+    match webhook_service.register(req.into_inner()).await {
+        Ok(registered) => Ok(HttpResponse::Ok().json(registered)),
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
+#[get("/api/admin/webhooks")]
+pub async fn list_webhooks(
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse> {
+    let webhook_service = WebhookService::new(pool.get_ref().clone(), RetryPolicy::default());
+    
+    match webhook_service.list_endpoints().await {
+        Ok(endpoints) => Ok(HttpResponse::Ok().json(endpoints)),
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
+#[delete("/api/admin/webhooks/{id}")]
+pub async fn deactivate_webhook(
+    pool: web::Data<PgPool>,
+    path: web::Path<i64>,
+) -> Result<HttpResponse> {
+    let endpoint_id = path.into_inner();
+    let webhook_service = WebhookService::new(pool.get_ref().clone(), RetryPolicy::default());
+    
+    match webhook_service.deactivate(endpoint_id).await {
+        Ok(()) => {
+            info!("Webhook endpoint {} deactivated", endpoint_id);
+            Ok(HttpResponse::Ok().json(serde_json::json!({
+                "status": "ok"
+            })))
+        }
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
+#[get("/api/admin/webhooks/dead-letters")]
+pub async fn get_dead_letters(
+    pool: web::Data<PgPool>,
+) -> Result<HttpResponse> {
+    let webhook_service = WebhookService::new(pool.get_ref().clone(), RetryPolicy::default());
+    
+    match webhook_service.dead_letters().await {
+        Ok(dead) => Ok(HttpResponse::Ok().json(dead)),
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
+#[post("/api/admin/webhooks/dead-letters/{id}/retry")]
+pub async fn retry_dead_letter(
+    pool: web::Data<PgPool>,
+    path: web::Path<i64>,
+) -> Result<HttpResponse> {
+    let webhook_service = WebhookService::new(pool.get_ref().clone(), RetryPolicy::default());
+    
+    match webhook_service.retry_dead_letter(path.into_inner()).await {
+        Ok(()) => Ok(HttpResponse::Ok().json(serde_json::json!({
+            "status": "ok"
+        }))),
+        Err(e) => {
+            Ok(HttpResponse::BadRequest().json(serde_json::json!({
+                "error": e.to_string()
+            })))
+        }
+    }
+}
+
@@ -0,0 +1,82 @@
+//! TW-UBI Fork Bootstrapper
+//! 
+//! Usage: ubi-fork <history|-> [--constitution <amendment.json>]
+//! 
+//! Imports a parent deployment's history (an export-state dump or an NDJSON
+//! event stream, `-` for stdin) into the empty database at DATABASE_URL,
+//! rebuilds state by replay and records the parent's head as the fork's
+//! genesis ancestor. The optional amendment sets new constitutional
+//! parameters from its effective_epoch onward.
+//! Exits 0 on success, 1 if the history is rejected, 2 on error.
+
+use std::io::Read;
+use ubi_backend::config::Config;
+use ubi_backend::models::constitution::ConstitutionAmendment;
+use ubi_backend::services::fork::{parse_history, ForkService};
+use sqlx::PgPool;
+
+const USAGE: &str = "Usage: ubi-fork <history|-> [--constitution <amendment.json>]";
+
+#[tokio::main]
+async fn main() {
+    env_logger::init_from_env(env_logger::Env::new().default_filter_or("info"));
+    
+    let args: Vec<String> = std::env::args().skip(1).collect();
+    let (history_path, amendment_path) = match args.as_slice() {
+        [history] => (history.clone(), None),
+        [history, flag, amendment] if flag == "--constitution" => (history.clone(), Some(amendment.clone())),
+        _ => {
+            eprintln!("{}", USAGE);
+            std::process::exit(2);
+        }
+    };
+    
+    let history = read_input(&history_path).unwrap_or_else(|e| {
+        eprintln!("Failed to read {}: {}", history_path, e);
+        std::process::exit(2);
+    });
+    let events = parse_history(&history).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1);
+    });
+    
+    let amendment: Option<ConstitutionAmendment> = amendment_path.map(|path| {
+        let contents = read_input(&path).unwrap_or_else(|e| {
+            eprintln!("Failed to read {}: {}", path, e);
+            std::process::exit(2);
+        });
+        serde_json::from_str(&contents).unwrap_or_else(|e| {
+            eprintln!("Invalid amendment {}: {}", path, e);
+            std::process::exit(2);
+        })
+    });
+    
+    let config = Config::load().unwrap_or_else(|e| {
+        eprintln!("Failed to load configuration: {}", e);
+        std::process::exit(2);
+    });
+    
+    let pool = PgPool::connect(&config.database_url).await.unwrap_or_else(|e| {
+        eprintln!("Failed to connect to database: {}", e);
+        std::process::exit(2);
+    });
+    
+    let fork_service = ForkService::new(pool, config.genesis_timestamp);
+    let report = fork_service.bootstrap(events, amendment).await.unwrap_or_else(|e| {
+        eprintln!("Fork failed: {}", e);
+        std::process::exit(1);
+    });
+    
+    println!("{}", serde_json::to_string_pretty(&report).unwrap());
+}
+
+fn read_input(path: &str) -> std::io::Result<String> {
+    if path == "-" {
+        let mut input = String::new();
+        std::io::stdin().read_to_string(&mut input)?;
+        Ok(input)
+    } else {
+        std::fs::read_to_string(path)
+    }
+}
+
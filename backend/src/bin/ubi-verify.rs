@@ -0,0 +1,35 @@
+//! TW-UBI Event Chain Verifier
+//! 
+//! Walks the full event log and reports the first broken link.
+//! Exits 0 if the chain is intact, 1 if it is broken, 2 on error.
+
+use ubi_backend::config::Config;
+use ubi_backend::services::event_chain::EventChainService;
+use sqlx::PgPool;
+
+#[tokio::main]
+async fn main() {
+    env_logger::init_from_env(env_logger::Env::new().default_filter_or("warn"));
+    
+    let config = Config::load().unwrap_or_else(|e| {
+        eprintln!("Failed to load configuration: {}", e);
+        std::process::exit(2);
+    });
+    
+    let pool = PgPool::connect(&config.database_url).await.unwrap_or_else(|e| {
+        eprintln!("Failed to connect to database: {}", e);
+        std::process::exit(2);
+    });
+    
+    let report = EventChainService::new(pool).verify().await.unwrap_or_else(|e| {
+        eprintln!("Verification failed: {}", e);
+        std::process::exit(2);
+    });
+    
+    println!("{}", serde_json::to_string_pretty(&report).unwrap());
+    
+    if !report.valid {
+        std::process::exit(1);
+    }
+}
+
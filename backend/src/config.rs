@@ -12,6 +12,8 @@ pub struct Config {
     pub mfa_issuer: String,
     pub global_salt: String,
     pub genesis_timestamp: i64,
+    pub checkpoint_signing_key: Option<String>, // hex-encoded Ed25519 seed
+    pub auto_claim_conversions: bool, // settlement credits unlocked BU to wallets
 }
 
 impl Config {
@@ -35,6 +37,10 @@ impl Config {
                 .unwrap_or_else(|_| chrono::Utc::now().timestamp().to_string())
                 .parse()
                 .unwrap_or_else(|_| chrono::Utc::now().timestamp()),
+            checkpoint_signing_key: env::var("CHECKPOINT_SIGNING_KEY").ok(),
+            auto_claim_conversions: env::var("AUTO_CLAIM_CONVERSIONS")
+                .map(|value| value == "true" || value == "1")
+                .unwrap_or(false),
         })
     }
 }
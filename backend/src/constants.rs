@@ -32,6 +32,12 @@ pub const CONVERSION_DELAY_EPOCHS: i32 = 1;
 /// Conversion fee (0.5% = 50 bps)
 pub const CONVERSION_FEE_BPS: u32 = 50;
 
+/// Whether cancelling a pending conversion refunds its fee (no: the fee is kept)
+pub const CANCELLATION_REFUNDS_FEE: bool = false;
+
+/// Unreserved treasury BU below which conversions are rationed (100,000 BU)
+pub const RATIONING_THRESHOLD_BU: &str = "100000000000000000000000";
+
 /// Conversion cap per person per epoch (1000 UE)
 pub const CONVERSION_CAP_UE: &str = "1000000000000000000000";
 
@@ -41,3 +47,6 @@ pub const EPOCH_LENGTH_SECONDS: i64 = 30 * 24 * 60 * 60;
 /// PersonId length (32 bytes)
 pub const PERSON_ID_LENGTH: usize = 32;
 
+/// Total BU supply minted into the treasury at genesis (1,000,000 BU)
+pub const BU_TOTAL_SUPPLY: &str = "1000000000000000000000000";
+
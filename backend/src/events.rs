@@ -4,8 +4,25 @@
 //! State must be reconstructible from events
 
 use serde::{Deserialize, Serialize};
+use serde::de::DeserializeOwned;
 use chrono::{DateTime, Utc};
-use sqlx::FromRow;
+use sha2::{Digest, Sha256};
+use sqlx::{FromRow, PgConnection};
+use crate::utils::canonical::to_canonical_bytes;
+use std::fmt;
+use std::str::FromStr;
+
+/// prev_hash of the first event in the chain
+pub const GENESIS_HASH: &str = "0000000000000000000000000000000000000000000000000000000000000000";
+
+/// Hash format written by this build
+///
+/// 1: fields joined by newlines, event_data as serde_json text
+/// 2: SHA-256 over the canonical encoding (see utils::canonical)
+pub const EVENT_HASH_VERSION: i32 = 2;
+
+/// Advisory lock key serializing event writers, so each event links to the last committed one
+const EVENT_CHAIN_LOCK: i64 = 0x7477_7562_6900_0001;
 
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Event {
@@ -13,10 +30,39 @@ pub struct Event {
     pub event_type: String,
     pub event_data: serde_json::Value,
     pub created_at: DateTime<Utc>,
+    pub prev_hash: Option<String>, // NULL for events written before hash chaining
+    pub hash: Option<String>,
+    #[serde(default = "legacy_hash_version")]
+    pub hash_version: i32, // exports written before versioning are version 1
+}
+
+fn legacy_hash_version() -> i32 {
+    1
+}
+
+impl Event {
+    /// Typed event type, if known to this build
+    pub fn event_type(&self) -> Option<EventType> {
+        self.event_type.parse().ok()
+    }
+    
+    /// Payload schema version (events written before versioning are version 1)
+    pub fn schema_version(&self) -> u32 {
+        self.event_data
+            .get(SCHEMA_VERSION_FIELD)
+            .and_then(|v| v.as_u64())
+            .map_or(1, |v| v as u32)
+    }
+    
+    /// Decode the payload, upcasting older schema versions to the current one
+    pub fn decode<P: EventPayload>(&self) -> Result<P, serde_json::Error> {
+        let data = upcast(P::EVENT_TYPE, self.schema_version(), P::SCHEMA_VERSION, self.event_data.clone());
+        serde_json::from_value(data)
+    }
 }
 
 /// Event types (append-only)
-#[derive(Debug, Clone, Serialize, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
 pub enum EventType {
     PersonRegistered,
     UBIClaimed,
@@ -25,6 +71,305 @@ pub enum EventType {
     WalletReset,
     RateIndexUpdated,
     OracleDataSubmitted,
+    DecayRateUpdated,
+    TreasuryDebited,
+    CheckpointCreated,
+    ForkCreated,
+    ConstitutionAmended,
+    UEPaid,
+    BUTransferred,
+    InvoiceCreated,
+    InvoicePaid,
+    InvoiceExpired,
+    StandingOrderCreated,
+    StandingOrderExecuted,
+    StandingOrderSkipped,
+    StandingOrderCancelled,
+    SupplyInvariantViolated,
+    ConversionCancelled,
+    ConversionUnlocked,
+    ConversionRationed,
+    AllowanceApproved,
+    AllowanceRevoked,
+    AllowanceSpent,
+}
+
+impl EventType {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            EventType::PersonRegistered => "PersonRegistered",
+            EventType::UBIClaimed => "UBIClaimed",
+            EventType::ConversionRequested => "ConversionRequested",
+            EventType::ConversionClaimed => "ConversionClaimed",
+            EventType::WalletReset => "WalletReset",
+            EventType::RateIndexUpdated => "RateIndexUpdated",
+            EventType::OracleDataSubmitted => "OracleDataSubmitted",
+            EventType::DecayRateUpdated => "DecayRateUpdated",
+            EventType::TreasuryDebited => "TreasuryDebited",
+            EventType::CheckpointCreated => "CheckpointCreated",
+            EventType::ForkCreated => "ForkCreated",
+            EventType::ConstitutionAmended => "ConstitutionAmended",
+            EventType::UEPaid => "UEPaid",
+            EventType::BUTransferred => "BUTransferred",
+            EventType::InvoiceCreated => "InvoiceCreated",
+            EventType::InvoicePaid => "InvoicePaid",
+            EventType::InvoiceExpired => "InvoiceExpired",
+            EventType::StandingOrderCreated => "StandingOrderCreated",
+            EventType::StandingOrderExecuted => "StandingOrderExecuted",
+            EventType::StandingOrderSkipped => "StandingOrderSkipped",
+            EventType::StandingOrderCancelled => "StandingOrderCancelled",
+            EventType::SupplyInvariantViolated => "SupplyInvariantViolated",
+            EventType::ConversionCancelled => "ConversionCancelled",
+            EventType::ConversionUnlocked => "ConversionUnlocked",
+            EventType::ConversionRationed => "ConversionRationed",
+            EventType::AllowanceApproved => "AllowanceApproved",
+            EventType::AllowanceRevoked => "AllowanceRevoked",
+            EventType::AllowanceSpent => "AllowanceSpent",
+        }
+    }
+}
+
+impl fmt::Display for EventType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl FromStr for EventType {
+    type Err = String;
+    
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "PersonRegistered" => Ok(EventType::PersonRegistered),
+            "UBIClaimed" => Ok(EventType::UBIClaimed),
+            "ConversionRequested" => Ok(EventType::ConversionRequested),
+            "ConversionClaimed" => Ok(EventType::ConversionClaimed),
+            "WalletReset" => Ok(EventType::WalletReset),
+            "RateIndexUpdated" => Ok(EventType::RateIndexUpdated),
+            "OracleDataSubmitted" => Ok(EventType::OracleDataSubmitted),
+            "DecayRateUpdated" => Ok(EventType::DecayRateUpdated),
+            "TreasuryDebited" => Ok(EventType::TreasuryDebited),
+            "CheckpointCreated" => Ok(EventType::CheckpointCreated),
+            "ForkCreated" => Ok(EventType::ForkCreated),
+            "ConstitutionAmended" => Ok(EventType::ConstitutionAmended),
+            "UEPaid" => Ok(EventType::UEPaid),
+            "BUTransferred" => Ok(EventType::BUTransferred),
+            "InvoiceCreated" => Ok(EventType::InvoiceCreated),
+            "InvoicePaid" => Ok(EventType::InvoicePaid),
+            "InvoiceExpired" => Ok(EventType::InvoiceExpired),
+            "StandingOrderCreated" => Ok(EventType::StandingOrderCreated),
+            "StandingOrderExecuted" => Ok(EventType::StandingOrderExecuted),
+            "StandingOrderSkipped" => Ok(EventType::StandingOrderSkipped),
+            "StandingOrderCancelled" => Ok(EventType::StandingOrderCancelled),
+            "SupplyInvariantViolated" => Ok(EventType::SupplyInvariantViolated),
+            "ConversionCancelled" => Ok(EventType::ConversionCancelled),
+            "ConversionUnlocked" => Ok(EventType::ConversionUnlocked),
+            "ConversionRationed" => Ok(EventType::ConversionRationed),
+            "AllowanceApproved" => Ok(EventType::AllowanceApproved),
+            "AllowanceRevoked" => Ok(EventType::AllowanceRevoked),
+            "AllowanceSpent" => Ok(EventType::AllowanceSpent),
+            _ => Err(format!("Unknown event type: {}", s)),
+        }
+    }
+}
+
+/// Tables and the events allowed to change them
+///
+/// CONSTITUTIONAL: Every write to a table must emit one of its events
+pub const TABLE_EVENT_COVERAGE: &[(&str, &[EventType])] = &[
+    ("users", &[EventType::PersonRegistered, EventType::WalletReset]),
+    ("ue_balances", &[
+        EventType::PersonRegistered,
+        EventType::UBIClaimed,
+        EventType::ConversionRequested,
+        EventType::ConversionCancelled,
+        EventType::ConversionRationed,
+        EventType::WalletReset,
+        EventType::UEPaid,
+    ]),
+    ("bu_balances", &[EventType::ConversionClaimed, EventType::BUTransferred]),
+    ("ubi_claims", &[EventType::UBIClaimed]),
+    ("last_claimed_epoch", &[EventType::UBIClaimed]),
+    ("pending_conversions", &[
+        EventType::ConversionRequested,
+        EventType::ConversionClaimed,
+        EventType::ConversionCancelled,
+        EventType::ConversionUnlocked,
+        EventType::ConversionRationed,
+    ]),
+    ("converted_this_epoch", &[
+        EventType::ConversionRequested,
+        EventType::ConversionCancelled,
+        EventType::ConversionRationed,
+    ]),
+    ("rate_index", &[EventType::RateIndexUpdated, EventType::DecayRateUpdated]),
+    ("region_oracle_data", &[EventType::OracleDataSubmitted]),
+    ("treasury", &[
+        EventType::TreasuryDebited,
+        EventType::ConversionRequested,
+        EventType::ConversionClaimed,
+        EventType::ConversionCancelled,
+        EventType::ConversionRationed,
+    ]),
+    ("state_checkpoints", &[EventType::CheckpointCreated]),
+    ("checkpoint_leaves", &[EventType::CheckpointCreated]),
+    ("fork_genesis", &[EventType::ForkCreated]),
+    ("constitution_parameters", &[EventType::ConstitutionAmended]),
+    ("ue_payments", &[EventType::UEPaid]),
+    ("bu_transfers", &[EventType::BUTransferred]),
+    ("invoices", &[EventType::InvoiceCreated, EventType::InvoicePaid, EventType::InvoiceExpired]),
+    ("standing_orders", &[
+        EventType::StandingOrderCreated,
+        EventType::StandingOrderExecuted,
+        EventType::StandingOrderSkipped,
+        EventType::StandingOrderCancelled,
+    ]),
+    ("standing_order_executions", &[EventType::StandingOrderExecuted, EventType::StandingOrderSkipped]),
+    ("ue_allowances", &[EventType::AllowanceApproved, EventType::AllowanceRevoked]),
+    ("allowance_spends", &[EventType::AllowanceSpent]),
+    ("journal_entries", JOURNALED_EVENTS),
+    ("journal_postings", JOURNALED_EVENTS),
+];
+
+/// Events that post a journal entry
+const JOURNALED_EVENTS: &[EventType] = &[
+    EventType::UBIClaimed,
+    EventType::ConversionRequested,
+    EventType::ConversionClaimed,
+    EventType::ConversionCancelled,
+    EventType::ConversionRationed,
+    EventType::WalletReset,
+    EventType::UEPaid,
+    EventType::BUTransferred,
+];
+
+/// Field added to every payload carrying its schema version
+pub const SCHEMA_VERSION_FIELD: &str = "schema_version";
+
+/// A payload struct bound to its event type and current schema version
+///
+/// Bump SCHEMA_VERSION whenever the struct changes shape, and register
+/// an upcaster from the previous version in UPCASTERS
+pub trait EventPayload: Serialize + DeserializeOwned {
+    const EVENT_TYPE: EventType;
+    const SCHEMA_VERSION: u32;
+}
+
+/// Rewrites a payload from `from_version` to `from_version + 1`
+pub struct Upcaster {
+    pub event_type: EventType,
+    pub from_version: u32,
+    pub upcast: fn(serde_json::Value) -> serde_json::Value,
+}
+
+/// Registered upcasters (append-only: never edit one that has shipped)
+pub const UPCASTERS: &[Upcaster] = &[
+    Upcaster {
+        event_type: EventType::ConversionRequested,
+        from_version: 1,
+        upcast: conversion_requested_v1_to_v2,
+    },
+    Upcaster {
+        event_type: EventType::ConversionRequested,
+        from_version: 2,
+        upcast: conversion_requested_v2_to_v3,
+    },
+    Upcaster {
+        event_type: EventType::ConstitutionAmended,
+        from_version: 1,
+        upcast: constitution_amended_v1_to_v2,
+    },
+    Upcaster {
+        event_type: EventType::ConversionRequested,
+        from_version: 3,
+        upcast: conversion_requested_v3_to_v4,
+    },
+    Upcaster {
+        event_type: EventType::ConstitutionAmended,
+        from_version: 2,
+        upcast: constitution_amended_v2_to_v3,
+    },
+    Upcaster {
+        event_type: EventType::ConversionRequested,
+        from_version: 4,
+        upcast: conversion_requested_v4_to_v5,
+    },
+];
+
+/// Run upcasters until the payload reaches `to_version`
+pub fn upcast(
+    event_type: EventType,
+    from_version: u32,
+    to_version: u32,
+    mut data: serde_json::Value,
+) -> serde_json::Value {
+    if from_version >= to_version {
+        return data;
+    }
+    
+    for version in from_version..to_version {
+        if let Some(upcaster) = UPCASTERS
+            .iter()
+            .find(|u| u.event_type == event_type && u.from_version == version)
+        {
+            data = (upcaster.upcast)(data);
+        }
+    }
+    
+    if let Some(obj) = data.as_object_mut() {
+        obj.insert(SCHEMA_VERSION_FIELD.to_string(), serde_json::json!(to_version));
+    }
+    data
+}
+
+/// v2 added conversion_id; v1 events cannot know it
+fn conversion_requested_v1_to_v2(mut data: serde_json::Value) -> serde_json::Value {
+    if let Some(obj) = data.as_object_mut() {
+        obj.entry("conversion_id").or_insert(serde_json::Value::Null);
+    }
+    data
+}
+
+/// v3 added fee_ue; earlier events burned the whole amount as far as the journal knows
+fn conversion_requested_v2_to_v3(mut data: serde_json::Value) -> serde_json::Value {
+    if let Some(obj) = data.as_object_mut() {
+        obj.entry("fee_ue").or_insert(serde_json::Value::Null);
+    }
+    data
+}
+
+/// v4 added bu_reserved; earlier conversions take their BU from the treasury when claimed
+fn conversion_requested_v3_to_v4(mut data: serde_json::Value) -> serde_json::Value {
+    if let Some(obj) = data.as_object_mut() {
+        obj.entry("bu_reserved").or_insert(serde_json::json!(false));
+    }
+    data
+}
+
+/// v5 added queued; rationing did not exist before it
+fn conversion_requested_v4_to_v5(mut data: serde_json::Value) -> serde_json::Value {
+    if let Some(obj) = data.as_object_mut() {
+        obj.entry("queued").or_insert(serde_json::json!(false));
+    }
+    data
+}
+
+/// v2 added cancellation_refunds_fee; amendments before it kept the genesis rule
+fn constitution_amended_v1_to_v2(mut data: serde_json::Value) -> serde_json::Value {
+    if let Some(obj) = data.as_object_mut() {
+        obj.entry("cancellation_refunds_fee")
+            .or_insert(serde_json::json!(crate::constants::CANCELLATION_REFUNDS_FEE));
+    }
+    data
+}
+
+/// v3 added rationing_threshold_bu; every earlier amendment kept the genesis threshold
+fn constitution_amended_v2_to_v3(mut data: serde_json::Value) -> serde_json::Value {
+    if let Some(obj) = data.as_object_mut() {
+        obj.entry("rationing_threshold_bu")
+            .or_insert(serde_json::json!(crate::constants::RATIONING_THRESHOLD_BU));
+    }
+    data
 }
 
 /// Event data structures
@@ -36,6 +381,11 @@ pub struct PersonRegisteredEvent {
     pub expiry_epoch: i32,
 }
 
+impl EventPayload for PersonRegisteredEvent {
+    const EVENT_TYPE: EventType = EventType::PersonRegistered;
+    const SCHEMA_VERSION: u32 = 1;
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct UBIClaimedEvent {
     pub person_id: String,
@@ -44,14 +394,28 @@ pub struct UBIClaimedEvent {
     pub amount_ue: String,
 }
 
+impl EventPayload for UBIClaimedEvent {
+    const EVENT_TYPE: EventType = EventType::UBIClaimed;
+    const SCHEMA_VERSION: u32 = 1;
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ConversionRequestedEvent {
+    pub conversion_id: Option<i64>, // None for v1 events
     pub person_id: String,
     pub wallet_address: String,
     pub amount_ue: String,
     pub amount_bu: String,
     pub rate_index: String,
     pub unlock_epoch: i32,
+    pub fee_ue: Option<String>, // None before v3
+    pub bu_reserved: bool, // since v4: amount_bu moved from the treasury to its reserve
+    pub queued: bool, // since v5: waiting for its epoch's rationing batch
+}
+
+impl EventPayload for ConversionRequestedEvent {
+    const EVENT_TYPE: EventType = EventType::ConversionRequested;
+    const SCHEMA_VERSION: u32 = 5;
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -62,6 +426,60 @@ pub struct ConversionClaimedEvent {
     pub amount_bu: String,
 }
 
+/// v2: the treasury debit is emitted separately as TreasuryDebited
+/// (same shape, so no upcaster; replay debits the treasury for v1 events)
+impl EventPayload for ConversionClaimedEvent {
+    const EVENT_TYPE: EventType = EventType::ConversionClaimed;
+    const SCHEMA_VERSION: u32 = 2;
+}
+
+/// The UE burned by a pending conversion, returned to the person's wallet
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversionCancelledEvent {
+    pub conversion_id: i64,
+    pub person_id: String,
+    pub wallet_address: String, // receives the refund
+    pub cap_epoch: i32, // epoch whose conversion cap is released
+    pub amount_ue: String,
+    pub fee_ue: String,
+    pub fee_refunded: bool,
+}
+
+impl EventPayload for ConversionCancelledEvent {
+    const EVENT_TYPE: EventType = EventType::ConversionCancelled;
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+/// A conversion past its unlock epoch, settled without crediting its BU
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversionUnlockedEvent {
+    pub conversion_id: i64,
+    pub person_id: String,
+    pub unlock_epoch: i32,
+}
+
+impl EventPayload for ConversionUnlockedEvent {
+    const EVENT_TYPE: EventType = EventType::ConversionUnlocked;
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+/// A queued conversion's share of its epoch's rationed BU; the unfilled UE is refunded
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConversionRationedEvent {
+    pub conversion_id: i64,
+    pub person_id: String,
+    pub wallet_address: String, // receives the refund
+    pub epoch: i32, // epoch the conversion was requested in
+    pub filled_bu: String, // reserved for the conversion; zero leaves it refunded
+    pub refund_ue: String, // includes fee_refund_ue
+    pub fee_refund_ue: String,
+}
+
+impl EventPayload for ConversionRationedEvent {
+    const EVENT_TYPE: EventType = EventType::ConversionRationed;
+    const SCHEMA_VERSION: u32 = 1;
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct WalletResetEvent {
     pub person_id: String,
@@ -69,6 +487,11 @@ pub struct WalletResetEvent {
     pub new_wallet: String,
 }
 
+impl EventPayload for WalletResetEvent {
+    const EVENT_TYPE: EventType = EventType::WalletReset;
+    const SCHEMA_VERSION: u32 = 1;
+}
+
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RateIndexUpdatedEvent {
     pub region_id: i32,
@@ -77,19 +500,393 @@ pub struct RateIndexUpdatedEvent {
     pub epoch: i32,
 }
 
-/// Emit event to database
-pub async fn emit_event(
-    executor: &mut (impl sqlx::Executor<'_, Database = sqlx::Postgres> + Send),
+impl EventPayload for RateIndexUpdatedEvent {
+    const EVENT_TYPE: EventType = EventType::RateIndexUpdated;
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct DecayRateUpdatedEvent {
+    pub region_id: i32,
+    pub previous_decay_rate: String,
+    pub decay_rate: String,
+    pub inflation_rate: String,
+    pub epoch: i32,
+}
+
+impl EventPayload for DecayRateUpdatedEvent {
+    const EVENT_TYPE: EventType = EventType::DecayRateUpdated;
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct OracleDataSubmittedEvent {
+    pub region_id: i32,
+    pub basket_index_wad: String,
+    pub inflation_rate_wad: String,
+    pub timestamp: i64,
+}
+
+impl EventPayload for OracleDataSubmittedEvent {
+    const EVENT_TYPE: EventType = EventType::OracleDataSubmitted;
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct TreasuryDebitedEvent {
+    pub amount_bu: String,
+    pub balance_bu: String, // after the debit
+    pub conversion_id: i64,
+}
+
+impl EventPayload for TreasuryDebitedEvent {
+    const EVENT_TYPE: EventType = EventType::TreasuryDebited;
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+/// Records a published checkpoint; carries no state of its own
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointCreatedEvent {
+    pub epoch: i32,
+    pub merkle_root: String,
+    pub leaf_count: i32,
+    pub last_event_id: i64, // last event folded into the checkpoint
+    pub last_event_hash: String,
+}
+
+impl EventPayload for CheckpointCreatedEvent {
+    const EVENT_TYPE: EventType = EventType::CheckpointCreated;
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+/// First event a fork writes on top of its parent's imported history
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ForkCreatedEvent {
+    pub parent_head_event_id: i64,
+    pub parent_head_hash: String, // genesis ancestor of the fork
+    pub events_imported: i64,
+}
+
+impl EventPayload for ForkCreatedEvent {
+    const EVENT_TYPE: EventType = EventType::ForkCreated;
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ConstitutionAmendedEvent {
+    pub effective_epoch: i32,
+    pub ue_mint_per_epoch: String,
+    pub conversion_fee_bps: i32,
+    pub conversion_cap_ue: String,
+    pub conversion_delay_epochs: i32,
+    pub cancellation_refunds_fee: bool, // since v2
+    pub rationing_threshold_bu: String, // since v3
+}
+
+impl EventPayload for ConstitutionAmendedEvent {
+    const EVENT_TYPE: EventType = EventType::ConstitutionAmended;
+    const SCHEMA_VERSION: u32 = 3;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct UEPaidEvent {
+    pub payment_id: i64,
+    pub person_id: String, // sender
+    pub from_wallet: String,
+    pub to_wallet: String,
+    pub amount_ue: String,
+    pub memo: Option<String>,
+}
+
+impl EventPayload for UEPaidEvent {
+    const EVENT_TYPE: EventType = EventType::UEPaid;
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct BUTransferredEvent {
+    pub transfer_id: i64,
+    pub from_wallet: String,
+    pub to_wallet: String,
+    pub amount_bu: String,
+    pub memo: Option<String>,
+}
+
+impl EventPayload for BUTransferredEvent {
+    const EVENT_TYPE: EventType = EventType::BUTransferred;
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvoiceCreatedEvent {
+    pub invoice_id: i64,
+    pub merchant_wallet: String,
+    pub amount_ue: String,
+    pub reference: String,
+    pub expiry_epoch: i32,
+}
+
+impl EventPayload for InvoiceCreatedEvent {
+    const EVENT_TYPE: EventType = EventType::InvoiceCreated;
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+/// Emitted with the UEPaid event of the settling payment
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvoicePaidEvent {
+    pub invoice_id: i64,
+    pub payment_id: i64,
+    pub payer_wallet: String,
+}
+
+impl EventPayload for InvoicePaidEvent {
+    const EVENT_TYPE: EventType = EventType::InvoicePaid;
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct InvoiceExpiredEvent {
+    pub invoice_id: i64,
+    pub expiry_epoch: i32,
+}
+
+impl EventPayload for InvoiceExpiredEvent {
+    const EVENT_TYPE: EventType = EventType::InvoiceExpired;
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StandingOrderCreatedEvent {
+    pub order_id: i64,
+    pub person_id: String, // payer
+    pub to_wallet: String,
+    pub amount_ue: String,
+    pub memo: Option<String>,
+    pub offset_seconds: i32,
+    pub first_epoch: i32,
+}
+
+impl EventPayload for StandingOrderCreatedEvent {
+    const EVENT_TYPE: EventType = EventType::StandingOrderCreated;
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+/// Emitted with the UEPaid event of the payment it made
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StandingOrderExecutedEvent {
+    pub order_id: i64,
+    pub execution_id: i64,
+    pub epoch: i32,
+    pub payment_id: i64,
+}
+
+impl EventPayload for StandingOrderExecutedEvent {
+    const EVENT_TYPE: EventType = EventType::StandingOrderExecuted;
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StandingOrderSkippedEvent {
+    pub order_id: i64,
+    pub execution_id: i64,
+    pub epoch: i32,
+    pub reason: String,
+}
+
+impl EventPayload for StandingOrderSkippedEvent {
+    const EVENT_TYPE: EventType = EventType::StandingOrderSkipped;
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct StandingOrderCancelledEvent {
+    pub order_id: i64,
+    pub person_id: String,
+}
+
+impl EventPayload for StandingOrderCancelledEvent {
+    const EVENT_TYPE: EventType = EventType::StandingOrderCancelled;
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+/// Alert raised by the supply checker; changes no state
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct SupplyInvariantViolatedEvent {
+    pub epoch: i32,
+    pub invariant: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+impl EventPayload for SupplyInvariantViolatedEvent {
+    const EVENT_TYPE: EventType = EventType::SupplyInvariantViolated;
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+/// New allowance, or a new amount for an active one
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllowanceApprovedEvent {
+    pub allowance_id: i64,
+    pub person_id: String,
+    pub spender_wallet: String,
+    pub amount_per_epoch: String,
+}
+
+impl EventPayload for AllowanceApprovedEvent {
+    const EVENT_TYPE: EventType = EventType::AllowanceApproved;
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllowanceRevokedEvent {
+    pub allowance_id: i64,
+    pub person_id: String,
+}
+
+impl EventPayload for AllowanceRevokedEvent {
+    const EVENT_TYPE: EventType = EventType::AllowanceRevoked;
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct AllowanceSpentEvent {
+    pub allowance_id: i64,
+    pub spend_id: i64,
+    pub epoch: i32,
+    pub payment_id: i64,
+    pub amount_ue: String,
+}
+
+impl EventPayload for AllowanceSpentEvent {
+    const EVENT_TYPE: EventType = EventType::AllowanceSpent;
+    const SCHEMA_VERSION: u32 = 1;
+}
+
+/// Compute an event's content hash: SHA-256 over the previous hash and the event itself
+///
+/// Version 2 hashes the canonical encoding of
+/// `{"created_at": <unix micros>, "event_data", "event_type", "id", "prev_hash"}`,
+/// which any implementation can reproduce from the stored JSONB
+pub fn compute_event_hash(
+    hash_version: i32,
+    prev_hash: &str,
+    id: i64,
     event_type: &str,
     event_data: &serde_json::Value,
-) -> Result<(), sqlx::Error> {
+    created_at: &DateTime<Utc>,
+) -> anyhow::Result<String> {
+    match hash_version {
+        1 => Ok(compute_event_hash_v1(prev_hash, id, event_type, event_data, created_at)),
+        2 => {
+            let bytes = to_canonical_bytes(&serde_json::json!({
+                "created_at": created_at.timestamp_micros(),
+                "event_data": event_data,
+                "event_type": event_type,
+                "id": id,
+                "prev_hash": prev_hash,
+            }))?;
+            Ok(hex::encode(Sha256::digest(&bytes)))
+        }
+        _ => Err(anyhow::anyhow!("Unknown event hash version {}", hash_version)),
+    }
+}
+
+/// Version 1: relies on serde_json sorting object keys, so it survives the
+/// JSONB round trip only for this implementation
+fn compute_event_hash_v1(
+    prev_hash: &str,
+    id: i64,
+    event_type: &str,
+    event_data: &serde_json::Value,
+    created_at: &DateTime<Utc>,
+) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(prev_hash.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(id.to_string().as_bytes());
+    hasher.update(b"\n");
+    hasher.update(event_type.as_bytes());
+    hasher.update(b"\n");
+    hasher.update(created_at.timestamp_micros().to_string().as_bytes());
+    hasher.update(b"\n");
+    hasher.update(event_data.to_string().as_bytes());
+    hex::encode(hasher.finalize())
+}
+
+/// Take the event chain lock until the transaction ends
+///
+/// A transaction that emits must call this before it locks any row:
+/// emitting takes the lock anyway, and taking it after row locks lets two
+/// writers wait on each other. Taking it again later is a no-op
+pub async fn lock_event_chain(conn: &mut PgConnection) -> Result<(), sqlx::Error> {
+    sqlx::query!("SELECT pg_advisory_xact_lock($1)", EVENT_CHAIN_LOCK)
+        .execute(&mut *conn)
+        .await?;
+    Ok(())
+}
+
+/// Emit event to database, tagged with the payload's schema version, returning its id
+///
+/// Must run inside a transaction: the chain lock is held until commit
+pub async fn emit_event<P: EventPayload>(
+    conn: &mut PgConnection,
+    payload: &P,
+) -> Result<i64, sqlx::Error> {
+    let event_type = P::EVENT_TYPE.as_str();
+    let mut event_data = serde_json::to_value(payload)
+        .map_err(|e| sqlx::Error::Protocol(format!("Event serialization failed: {}", e)))?;
+    if let Some(obj) = event_data.as_object_mut() {
+        obj.insert(SCHEMA_VERSION_FIELD.to_string(), serde_json::json!(P::SCHEMA_VERSION));
+    }
+    
+    lock_event_chain(&mut *conn).await?;
+    
+    let prev_hash = sqlx::query_scalar!(
+        "SELECT hash FROM events WHERE hash IS NOT NULL ORDER BY id DESC LIMIT 1"
+    )
+    .fetch_optional(&mut *conn)
+    .await?
+    .flatten()
+    .unwrap_or_else(|| GENESIS_HASH.to_string());
+    
+    let id = sqlx::query_scalar!("SELECT nextval('events_id_seq') AS \"id!\"")
+        .fetch_one(&mut *conn)
+        .await?;
+    
+    // Postgres stores microseconds; truncate so the hash matches what is read back
+    let now = Utc::now();
+    let created_at = DateTime::from_timestamp_micros(now.timestamp_micros()).unwrap_or(now);
+    let hash = compute_event_hash(EVENT_HASH_VERSION, &prev_hash, id, event_type, &event_data, &created_at)
+        .map_err(|e| sqlx::Error::Protocol(format!("Event hashing failed: {}", e)))?;
+    
     sqlx::query!(
-        "INSERT INTO events (event_type, event_data) VALUES ($1, $2)",
+        r#"
+        INSERT INTO events (id, event_type, event_data, created_at, prev_hash, hash, hash_version)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+        id,
         event_type,
-        event_data
+        event_data,
+        created_at,
+        prev_hash,
+        hash,
+        EVENT_HASH_VERSION
     )
-    .execute(executor)
+    .execute(&mut *conn)
     .await?;
-    Ok(())
+    
+    // Transactional outbox: queued for webhooks if and only if the event commits
+    sqlx::query!(
+        r#"
+        INSERT INTO webhook_outbox (event_id, endpoint_id)
+        SELECT $1, id FROM webhook_endpoints
+        WHERE is_active AND (event_types IS NULL OR $2 = ANY(event_types))
+        "#,
+        id,
+        event_type
+    )
+    .execute(&mut *conn)
+    .await?;
+    Ok(id)
 }
 
@@ -12,7 +12,7 @@ mod events;
 
 use actix_web::{web, App, HttpServer};
 use config::Config;
-use log::info;
+use log::{info, warn};
 use sqlx::PgPool;
 
 #[actix_web::main]
@@ -36,22 +36,108 @@ async fn main() -> std::io::Result<()> {
     info!("Starting TW-UBI System on {}:{}", config.host, config.port);
     // Database URL not logged for security
     
+    match &config.checkpoint_signing_key {
+        Some(seed) => {
+            let signing_key = utils::signing::signing_key_from_hex(seed)
+                .unwrap_or_else(|e| {
+                    eprintln!("Invalid CHECKPOINT_SIGNING_KEY: {}", e);
+                    std::process::exit(1);
+                });
+            info!("Checkpoint public key: {}", utils::signing::public_key_hex(&signing_key));
+            let checkpoints = services::checkpoint::CheckpointService::new(
+                pool.clone(),
+                config.genesis_timestamp,
+                Some(signing_key),
+            );
+            tokio::spawn(checkpoints.run_worker());
+        }
+        None => warn!("CHECKPOINT_SIGNING_KEY not set, epoch checkpoints disabled"),
+    }
+    
+    let webhooks = services::webhook::WebhookService::new(
+        pool.clone(),
+        services::webhook::RetryPolicy::default(),
+    );
+    tokio::spawn(webhooks.run_worker());
+    
+    let standing_orders = services::standing_order::StandingOrderService::new(
+        pool.clone(),
+        services::registry::RegistryService::new(pool.clone()),
+        config.genesis_timestamp,
+    );
+    tokio::spawn(standing_orders.run_worker());
+    
+    let invoices = services::invoice::InvoiceService::new(
+        pool.clone(),
+        services::registry::RegistryService::new(pool.clone()),
+        config.genesis_timestamp,
+    );
+    tokio::spawn(invoices.run_worker());
+    
+    let settlement = services::conversion::ConversionService::new(
+        pool.clone(),
+        services::registry::RegistryService::new(pool.clone()),
+        services::rate_index::RateIndexService::new(pool.clone(), config.genesis_timestamp),
+        config.genesis_timestamp,
+    );
+    tokio::spawn(settlement.run_settlement_worker(config.auto_claim_conversions));
+    
+    let supply = services::supply::SupplyService::new(pool.clone(), config.genesis_timestamp);
+    let supply_status = services::supply::SupplyStatus::default();
+    tokio::spawn(supply.run_worker(supply_status.clone()));
+    
     HttpServer::new(move || {
         App::new()
             .app_data(web::Data::new(pool.clone()))
             .app_data(web::Data::new(config.clone()))
+            .app_data(web::Data::new(supply_status.clone()))
+            .wrap(actix_web::middleware::from_fn(api::idempotency::idempotency))
             .service(api::health::health)
             .service(api::users::register_user)
             .service(api::users::reset_wallet)
+            .service(api::users::issue_token)
+            .service(api::users::issue_wallet_token)
             .service(api::ubi::claim_ubi)
             .service(api::conversion::request_conversion)
             .service(api::conversion::claim_conversion)
+            .service(api::conversion::cancel_conversion)
+            .service(api::conversion::quote_conversion)
+            .service(api::payments::create_payment)
+            .service(api::transfers::transfer_bu)
+            .service(api::invoices::create_invoice)
+            .service(api::invoices::get_invoice)
+            .service(api::invoices::pay_invoice)
+            .service(api::standing_orders::create_standing_order)
+            .service(api::standing_orders::list_standing_orders)
+            .service(api::standing_orders::get_standing_order_executions)
+            .service(api::standing_orders::cancel_standing_order)
+            .service(api::allowances::approve_allowance)
+            .service(api::allowances::list_allowances)
+            .service(api::allowances::list_received_allowances)
+            .service(api::allowances::get_allowance_spends)
+            .service(api::allowances::spend_allowance)
+            .service(api::allowances::revoke_allowance)
+            .service(api::wallets::get_wallet_statement)
+            .service(api::supply::get_supply)
+            .service(api::supply::get_supply_epochs)
             .service(api::oracle::submit_oracle)
             .service(api::admin::export_state)
+            .service(api::admin::replay_events)
+            .service(api::admin::verify_event_chain)
             .service(api::balances::get_ue_balance)
             .service(api::balances::get_bu_balance)
             .service(api::balances::get_rate_index)
             .service(api::pending_conversions::get_pending_conversions)
+            .service(api::events::get_events)
+            .service(api::events::stream_events)
+            .service(api::checkpoints::get_latest_checkpoint)
+            .service(api::checkpoints::get_checkpoint)
+            .service(api::proofs::get_wallet_proofs)
+            .service(api::webhooks::get_dead_letters)
+            .service(api::webhooks::retry_dead_letter)
+            .service(api::webhooks::register_webhook)
+            .service(api::webhooks::list_webhooks)
+            .service(api::webhooks::deactivate_webhook)
     })
     .bind((config.host.clone(), config.port))?
     .run()
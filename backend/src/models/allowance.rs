@@ -0,0 +1,36 @@
+//! UE allowance models
+
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+/// Permission for a wallet to spend a person's UE, up to an amount per epoch
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Allowance {
+    pub id: i64,
+    pub person_id: Vec<u8>, // grantor
+    pub spender_wallet: String,
+    pub amount_per_epoch: Decimal, // WAD
+    pub status: String, // active, revoked
+    pub created_at: DateTime<Utc>,
+}
+
+/// Allowance request (grantor is the requesting wallet's person)
+#[derive(Debug, Deserialize)]
+pub struct AllowanceRequest {
+    pub spender_wallet: String,
+    pub amount_per_epoch: Decimal,
+}
+
+/// A payment made under an allowance
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct AllowanceSpend {
+    pub id: i64,
+    pub allowance_id: i64,
+    pub epoch: i32,
+    pub payment_id: i64,
+    pub amount_ue: Decimal, // WAD
+    pub created_at: DateTime<Utc>,
+}
+
@@ -0,0 +1,61 @@
+//! State checkpoint models
+
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::{DateTime, Utc};
+
+/// Signed commitment to system state at the start of an epoch
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct StateCheckpoint {
+    pub epoch: i32,
+    pub leaf_version: i32,
+    pub merkle_root: String, // hex-encoded
+    pub leaf_count: i32,
+    pub last_event_id: i64,
+    pub last_event_hash: String,
+    pub snapshot_hash: Option<String>, // SHA-256 of the canonical snapshot, from leaf version 3
+    pub signature: String, // Ed25519 over signed_message, hex-encoded
+    pub public_key: String,
+    pub created_at: DateTime<Utc>,
+}
+
+/// One Merkle leaf: a stable lookup key and the exact bytes that were hashed
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct CheckpointLeaf {
+    pub leaf_key: String,
+    pub leaf_data: String,
+}
+
+/// Checkpoint response
+#[derive(Debug, Serialize)]
+pub struct CheckpointResponse {
+    #[serde(flatten)]
+    pub checkpoint: StateCheckpoint,
+    pub signed_message: String,
+}
+
+/// One step of an inclusion proof
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct ProofStepResponse {
+    pub sibling: String, // hex-encoded
+    pub position: String, // side the sibling is on: "left" or "right"
+}
+
+/// A leaf with its path to the checkpoint root
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct LeafProof {
+    pub leaf_index: i32,
+    pub leaf_key: String,
+    pub leaf_data: String,
+    pub leaf_hash: String,
+    pub proof: Vec<ProofStepResponse>,
+}
+
+/// Inclusion proofs for one wallet against a signed checkpoint
+#[derive(Debug, Serialize)]
+pub struct WalletProofResponse {
+    pub wallet_address: String,
+    pub checkpoint: CheckpointResponse,
+    pub leaves: Vec<LeafProof>,
+}
+
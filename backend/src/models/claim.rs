@@ -3,6 +3,7 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
 
 /// UBI claim record
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
@@ -10,7 +11,7 @@ pub struct UBIClaim {
     pub id: i64,
     pub person_id: Vec<u8>,
     pub epoch: i32,
-    pub amount_ue: String,
+    pub amount_ue: Decimal,
     pub claimed_at: DateTime<Utc>,
 }
 
@@ -18,7 +19,7 @@ pub struct UBIClaim {
 #[derive(Debug, Serialize)]
 pub struct ClaimResponse {
     pub epoch: i32,
-    pub amount_ue: String,
+    pub amount_ue: Decimal,
     pub claimed_at: DateTime<Utc>,
 }
 
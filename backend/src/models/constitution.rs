@@ -0,0 +1,55 @@
+//! Constitutional parameter models
+
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use rust_decimal::Decimal;
+use crate::constants::{
+    CANCELLATION_REFUNDS_FEE, CONVERSION_CAP_UE, CONVERSION_DELAY_EPOCHS, CONVERSION_FEE_BPS,
+    RATIONING_THRESHOLD_BU, UE_MINT_PER_EPOCH,
+};
+
+/// Parameters in force from `effective_epoch` until the next amendment
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize, FromRow)]
+pub struct ConstitutionParameters {
+    pub effective_epoch: i32,
+    pub ue_mint_per_epoch: Decimal,
+    pub conversion_fee_bps: i32,
+    pub conversion_cap_ue: Decimal,
+    pub conversion_delay_epochs: i32,
+    #[serde(default)] // snapshots written before it existed; the genesis rule keeps the fee
+    pub cancellation_refunds_fee: bool, // fee returned when a pending conversion is cancelled
+    #[serde(default = "genesis_rationing_threshold")] // snapshots written before it existed
+    pub rationing_threshold_bu: Decimal, // unreserved treasury BU below which conversions are rationed
+}
+
+impl ConstitutionParameters {
+    /// Parameters frozen at deployment (src/constants.rs)
+    pub fn genesis() -> Self {
+        Self {
+            effective_epoch: 0,
+            ue_mint_per_epoch: UE_MINT_PER_EPOCH.parse().expect("UE_MINT_PER_EPOCH is a valid amount"),
+            conversion_fee_bps: CONVERSION_FEE_BPS as i32,
+            conversion_cap_ue: CONVERSION_CAP_UE.parse().expect("CONVERSION_CAP_UE is a valid amount"),
+            conversion_delay_epochs: CONVERSION_DELAY_EPOCHS,
+            cancellation_refunds_fee: CANCELLATION_REFUNDS_FEE,
+            rationing_threshold_bu: genesis_rationing_threshold(),
+        }
+    }
+}
+
+fn genesis_rationing_threshold() -> Decimal {
+    RATIONING_THRESHOLD_BU.parse().expect("RATIONING_THRESHOLD_BU is a valid amount")
+}
+
+/// Requested amendment; omitted parameters keep their current values
+#[derive(Debug, Clone, Deserialize)]
+pub struct ConstitutionAmendment {
+    pub effective_epoch: i32,
+    pub ue_mint_per_epoch: Option<Decimal>,
+    pub conversion_fee_bps: Option<i32>,
+    pub conversion_cap_ue: Option<Decimal>,
+    pub conversion_delay_epochs: Option<i32>,
+    pub cancellation_refunds_fee: Option<bool>,
+    pub rationing_threshold_bu: Option<Decimal>,
+}
+
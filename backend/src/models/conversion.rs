@@ -3,34 +3,49 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
 
 /// Pending conversion
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct PendingConversion {
     pub id: i64,
     pub person_id: Vec<u8>,
-    pub amount_ue: String,
-    pub amount_bu: String,
-    pub rate_index: String,
+    pub amount_ue: Decimal,
+    pub amount_bu: Decimal,
+    pub rate_index: Decimal,
     pub unlock_epoch: i32,
-    pub status: String, // pending, unlocked, claimed
+    pub status: String, // queued, pending, unlocked, claimed, cancelled, refunded
+    pub reserved: bool, // amount_bu held in the treasury's reserve
     pub created_at: DateTime<Utc>,
 }
 
 /// Conversion request
 #[derive(Debug, Deserialize)]
 pub struct ConversionRequest {
-    pub amount_ue: String,
-    pub min_bu_out: String,
+    pub amount_ue: Decimal,
+    pub min_bu_out: Decimal,
 }
 
 /// Conversion response
 #[derive(Debug, Serialize)]
 pub struct ConversionResponse {
     pub conversion_id: i64,
-    pub amount_ue: String,
-    pub amount_bu: String,
+    pub amount_ue: Decimal,
+    pub amount_bu: Decimal,
     pub unlock_epoch: i32,
+    pub status: String, // queued while the treasury is being rationed
+}
+
+/// What a conversion request would give now
+#[derive(Debug, Serialize)]
+pub struct ConversionQuote {
+    pub amount_ue: Decimal,
+    pub fee_ue: Decimal,
+    pub amount_bu: Decimal,
+    pub rate_index: Decimal,
+    pub unlock_epoch: i32,
+    pub cap_remaining_ue: Decimal, // before this conversion
+    pub rationed: bool, // amount_bu is an upper bound, filled pro rata at the epoch's end
 }
 
 impl From<PendingConversion> for ConversionResponse {
@@ -40,6 +55,7 @@ impl From<PendingConversion> for ConversionResponse {
             amount_ue: conv.amount_ue,
             amount_bu: conv.amount_bu,
             unlock_epoch: conv.unlock_epoch,
+            status: conv.status,
         }
     }
 }
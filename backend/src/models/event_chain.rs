@@ -0,0 +1,24 @@
+//! Event chain models
+
+use serde::Serialize;
+
+/// First link where the event chain fails verification
+#[derive(Debug, Clone, Serialize)]
+pub struct BrokenLink {
+    pub event_id: i64,
+    pub reason: String,
+    pub expected: Option<String>,
+    pub found: Option<String>,
+}
+
+/// Result of walking the event chain
+#[derive(Debug, Serialize)]
+pub struct ChainVerification {
+    pub valid: bool,
+    pub events_checked: i64,
+    pub unsealed_events: i64, // legacy events written before hash chaining
+    pub head_event_id: Option<i64>,
+    pub head_hash: Option<String>,
+    pub first_broken_link: Option<BrokenLink>,
+}
+
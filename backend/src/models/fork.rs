@@ -0,0 +1,14 @@
+//! Fork bootstrap models
+
+use serde::Serialize;
+use crate::models::constitution::ConstitutionParameters;
+
+/// Result of bootstrapping a fork from a parent's history
+#[derive(Debug, Serialize)]
+pub struct ForkReport {
+    pub events_imported: i64,
+    pub parent_head_event_id: i64,
+    pub parent_head_hash: String,
+    pub constitution: Option<ConstitutionParameters>, // amendment applied at fork time
+}
+
@@ -0,0 +1,36 @@
+//! Invoice models
+
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+/// Merchant invoice
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Invoice {
+    pub id: i64,
+    pub merchant_wallet: String,
+    pub amount_ue: Decimal, // WAD
+    pub reference: String,
+    pub expiry_epoch: i32,
+    pub status: String, // open, paid, expired
+    pub payment_id: Option<i64>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Invoice request (merchant is the requesting wallet)
+#[derive(Debug, Deserialize)]
+pub struct InvoiceRequest {
+    pub amount_ue: Decimal,
+    pub reference: String,
+    pub expiry_epoch: i32,
+}
+
+/// Invoice with its payment URI (for links and QR codes)
+#[derive(Debug, Serialize)]
+pub struct InvoiceResponse {
+    #[serde(flatten)]
+    pub invoice: Invoice,
+    pub uri: String,
+}
+
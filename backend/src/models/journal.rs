@@ -0,0 +1,102 @@
+//! Double-entry journal models
+//!
+//! CONSTITUTIONAL: Every movement of UE or BU is a balanced journal entry
+
+use serde::{Deserialize, Serialize};
+use rust_decimal::Decimal;
+use std::fmt;
+
+/// Unit of account
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum Unit {
+    UE,
+    BU,
+}
+
+impl Unit {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Unit::UE => "UE",
+            Unit::BU => "BU",
+        }
+    }
+}
+
+/// Journal account
+///
+/// Wallets and the treasury (free and reserved) hold units; the rest are where units come
+/// from and go to, so their balances are negative (sources) or only grow (sinks)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Account {
+    Wallet(String),
+    Treasury,
+    Reserved, // treasury BU set aside for requested conversions
+    Genesis, // BU supply fixed at genesis (source)
+    Mint,    // UE issued by UBI claims (source)
+    Burn,    // UE destroyed by conversions (sink; cancellations return it)
+    Fees,    // UE taken as conversion fees (sink; cancellations may return it)
+}
+
+impl fmt::Display for Account {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Account::Wallet(address) => write!(f, "wallet:{}", address),
+            Account::Treasury => f.write_str("treasury"),
+            Account::Reserved => f.write_str("reserved"),
+            Account::Genesis => f.write_str("genesis"),
+            Account::Mint => f.write_str("mint"),
+            Account::Burn => f.write_str("burn"),
+            Account::Fees => f.write_str("fees"),
+        }
+    }
+}
+
+/// One side of a movement: a signed amount added to an account
+///
+/// Negative amounts are debits (units leave the account), positive
+/// amounts are credits (units arrive)
+#[derive(Debug, Clone, PartialEq)]
+pub struct Posting {
+    pub account: Account,
+    pub unit: Unit,
+    pub amount: Decimal,
+}
+
+/// A set of postings whose amounts sum to zero for each unit
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct JournalEntry {
+    pub postings: Vec<Posting>,
+}
+
+impl JournalEntry {
+    pub fn new() -> Self {
+        Self::default()
+    }
+    
+    /// Move `amount` of `unit` from one account to another (zero amounts are skipped)
+    pub fn transfer(mut self, unit: Unit, from: Account, to: Account, amount: Decimal) -> Self {
+        if !amount.is_zero() {
+            self.postings.push(Posting { account: from, unit, amount: -amount });
+            self.postings.push(Posting { account: to, unit, amount });
+        }
+        self
+    }
+    
+    /// True when every unit's postings sum to zero
+    pub fn is_balanced(&self) -> bool {
+        [Unit::UE, Unit::BU].iter().all(|unit| {
+            self.postings
+                .iter()
+                .filter(|posting| posting.unit == *unit)
+                .map(|posting| posting.amount)
+                .sum::<Decimal>()
+                .is_zero()
+        })
+    }
+}
+
+/// Key of an account balance in replayed and live ledgers
+pub fn ledger_key(unit: Unit, account: &Account) -> String {
+    format!("{}|{}", unit.as_str(), account)
+}
+
@@ -4,6 +4,20 @@ pub mod conversion;
 pub mod rate_index;
 pub mod treasury;
 pub mod oracle;
+pub mod replay;
+pub mod event_chain;
+pub mod checkpoint;
+pub mod constitution;
+pub mod fork;
+pub mod webhook;
+pub mod payment;
+pub mod transfer;
+pub mod journal;
+pub mod invoice;
+pub mod standing_order;
+pub mod statement;
+pub mod supply;
+pub mod allowance;
 
 pub use user::*;
 pub use claim::*;
@@ -11,4 +25,18 @@ pub use conversion::*;
 pub use rate_index::*;
 pub use treasury::*;
 pub use oracle::*;
+pub use replay::*;
+pub use event_chain::*;
+pub use checkpoint::*;
+pub use constitution::*;
+pub use fork::*;
+pub use webhook::*;
+pub use payment::*;
+pub use transfer::*;
+pub use journal::*;
+pub use invoice::*;
+pub use standing_order::*;
+pub use statement::*;
+pub use supply::*;
+pub use allowance::*;
 
@@ -3,13 +3,14 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
 
 /// Region oracle data
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct RegionOracleData {
     pub region_id: i32,
-    pub current_basket_index_wad: String,
-    pub current_inflation_rate_wad: String,
+    pub current_basket_index_wad: Decimal,
+    pub current_inflation_rate_wad: Decimal,
     pub last_update_timestamp: i64,
 }
 
@@ -17,6 +18,6 @@ pub struct RegionOracleData {
 #[derive(Debug, Deserialize)]
 pub struct OracleSubmission {
     pub region_id: i32,
-    pub basket_index_wad: String,
+    pub basket_index_wad: Decimal,
 }
 
@@ -0,0 +1,26 @@
+//! UE payment models
+
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+/// UE payment record
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct Payment {
+    pub id: i64,
+    pub from_wallet: String,
+    pub to_wallet: String,
+    pub amount_ue: Decimal, // WAD
+    pub memo: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Payment request (sender is the requesting wallet)
+#[derive(Debug, Deserialize)]
+pub struct PaymentRequest {
+    pub to_wallet: String,
+    pub amount_ue: Decimal,
+    pub memo: Option<String>,
+}
+
@@ -0,0 +1,183 @@
+//! Replay models
+//!
+//! CONSTITUTIONAL: State must be reconstructible from events
+
+use serde::{Deserialize, Serialize};
+use std::collections::BTreeMap;
+use crate::models::constitution::ConstitutionParameters;
+
+/// Person as rebuilt from events (keyed by hex personId)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayUser {
+    pub wallet_address: String,
+    pub region_id: i32,
+    pub expiry_epoch: i32,
+    pub last_reset_epoch: i32,
+    pub is_active: bool,
+}
+
+/// Pending conversion as rebuilt from events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayConversion {
+    pub person_id: String, // hex-encoded
+    pub amount_ue: String,
+    pub amount_bu: String,
+    pub rate_index: String,
+    pub unlock_epoch: i32,
+    pub status: String,
+    #[serde(default)]
+    pub reserved: bool,
+}
+
+/// Rate index as rebuilt from events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayRateIndex {
+    pub rate_index_wad: String,
+    pub last_epoch: i32,
+    pub current_decay_rate_wad: String,
+    pub last_decay_update_epoch: i32,
+}
+
+/// Region oracle data as rebuilt from events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayOracleData {
+    pub current_basket_index_wad: String,
+    pub current_inflation_rate_wad: String,
+    pub last_update_timestamp: i64,
+}
+
+/// Fork ancestry record as rebuilt from events (keyed by parent head hash)
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayFork {
+    pub parent_head_event_id: i64,
+    pub events_imported: i64,
+}
+
+/// UE payment as rebuilt from events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayPayment {
+    pub from_wallet: String,
+    pub to_wallet: String,
+    pub amount_ue: String,
+    pub memo: Option<String>,
+}
+
+/// BU transfer as rebuilt from events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayTransfer {
+    pub from_wallet: String,
+    pub to_wallet: String,
+    pub amount_bu: String,
+    pub memo: Option<String>,
+}
+
+/// Invoice as rebuilt from events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayInvoice {
+    pub merchant_wallet: String,
+    pub amount_ue: String,
+    pub reference: String,
+    pub expiry_epoch: i32,
+    pub status: String,
+    pub payment_id: Option<i64>,
+}
+
+/// Standing order as rebuilt from events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayStandingOrder {
+    pub person_id: String, // hex-encoded
+    pub to_wallet: String,
+    pub amount_ue: String,
+    pub memo: Option<String>,
+    pub offset_seconds: i32,
+    pub next_epoch: i32,
+    pub status: String,
+}
+
+/// Standing order run as rebuilt from events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayStandingOrderExecution {
+    pub order_id: i64,
+    pub epoch: i32,
+    pub payment_id: Option<i64>,
+    pub skip_reason: Option<String>,
+}
+
+/// UE allowance as rebuilt from events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayAllowance {
+    pub person_id: String, // hex-encoded
+    pub spender_wallet: String,
+    pub amount_per_epoch: String,
+    pub status: String,
+}
+
+/// Allowance spend as rebuilt from events
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+pub struct ReplayAllowanceSpend {
+    pub allowance_id: i64,
+    pub epoch: i32,
+    pub payment_id: i64,
+    pub amount_ue: String,
+}
+
+/// Full system state, either folded from the event log or read from live tables
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct ReplayState {
+    pub users: BTreeMap<String, ReplayUser>,
+    pub ue_balances: BTreeMap<String, String>,
+    pub bu_balances: BTreeMap<String, String>,
+    pub ubi_claims: BTreeMap<String, BTreeMap<i32, String>>, // personId -> epoch -> amount
+    pub last_claimed_epoch: BTreeMap<String, BTreeMap<i32, i32>>, // personId -> region -> epoch
+    pub pending_conversions: BTreeMap<i64, ReplayConversion>,
+    pub converted_this_epoch: BTreeMap<String, BTreeMap<i32, String>>, // personId -> epoch -> amount
+    pub rate_index: BTreeMap<i32, ReplayRateIndex>,
+    pub region_oracle_data: BTreeMap<i32, ReplayOracleData>,
+    pub treasury_bu: String,
+    #[serde(default = "zero")]
+    pub treasury_reserved_bu: String,
+    #[serde(default)]
+    pub constitution: BTreeMap<i32, ConstitutionParameters>, // effective epoch -> parameters
+    #[serde(default)]
+    pub forks: BTreeMap<String, ReplayFork>,
+    #[serde(default)]
+    pub ue_payments: BTreeMap<i64, ReplayPayment>,
+    #[serde(default)]
+    pub bu_transfers: BTreeMap<i64, ReplayTransfer>,
+    #[serde(default)]
+    pub invoices: BTreeMap<i64, ReplayInvoice>,
+    #[serde(default)]
+    pub standing_orders: BTreeMap<i64, ReplayStandingOrder>,
+    #[serde(default)]
+    pub standing_order_executions: BTreeMap<i64, ReplayStandingOrderExecution>,
+    #[serde(default)]
+    pub ue_allowances: BTreeMap<i64, ReplayAllowance>,
+    #[serde(default)]
+    pub allowance_spends: BTreeMap<i64, ReplayAllowanceSpend>,
+    #[serde(default)]
+    pub ledger: BTreeMap<String, String>, // "unit|account" -> signed journal balance
+}
+
+/// Snapshots written before treasury reservations reserved nothing
+fn zero() -> String {
+    "0".to_string()
+}
+
+/// A single row where replayed state and live state disagree
+#[derive(Debug, Clone, Serialize)]
+pub struct Divergence {
+    pub table: String,
+    pub key: String,
+    pub replayed: Option<String>,
+    pub live: Option<String>,
+}
+
+/// Result of replaying the event log against live tables
+#[derive(Debug, Serialize)]
+pub struct ReplayReport {
+    pub events_replayed: usize,
+    pub events_unhandled: usize,
+    pub consistent: bool,
+    pub divergences: Vec<Divergence>,
+}
+
@@ -0,0 +1,41 @@
+//! Standing order models
+
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+/// Recurring UE payment, made once per epoch
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct StandingOrder {
+    pub id: i64,
+    pub person_id: Vec<u8>, // payer
+    pub to_wallet: String,
+    pub amount_ue: Decimal, // WAD
+    pub memo: Option<String>,
+    pub offset_seconds: i32, // seconds into the epoch
+    pub next_epoch: i32,
+    pub status: String, // active, cancelled
+    pub created_at: DateTime<Utc>,
+}
+
+/// Standing order request (payer is the requesting wallet's person)
+#[derive(Debug, Deserialize)]
+pub struct StandingOrderRequest {
+    pub to_wallet: String,
+    pub amount_ue: Decimal,
+    pub memo: Option<String>,
+    pub offset_seconds: i32,
+}
+
+/// One epoch's run of a standing order: the payment made, or why it was skipped
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct StandingOrderExecution {
+    pub id: i64,
+    pub order_id: i64,
+    pub epoch: i32,
+    pub payment_id: Option<i64>,
+    pub skip_reason: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
@@ -0,0 +1,31 @@
+//! Wallet statement models
+
+use serde::Serialize;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+/// One balance change of a wallet, with the balance after it
+#[derive(Debug, Clone, Serialize)]
+pub struct StatementLine {
+    pub event_id: i64,
+    pub event_type: String,
+    pub epoch: i32,
+    pub created_at: DateTime<Utc>,
+    pub unit: String, // UE or BU
+    pub amount: Decimal, // WAD, negative for debits
+    pub balance: Decimal, // WAD, balance in `unit` after this line
+}
+
+/// Balance history of a wallet over a range of epochs
+#[derive(Debug, Clone, Serialize)]
+pub struct Statement {
+    pub wallet_address: String,
+    pub from_epoch: i32,
+    pub to_epoch: i32,
+    pub opening_ue: Decimal,
+    pub opening_bu: Decimal,
+    pub closing_ue: Decimal,
+    pub closing_bu: Decimal,
+    pub lines: Vec<StatementLine>,
+}
+
@@ -0,0 +1,46 @@
+//! Supply accounting models
+
+use serde::Serialize;
+
+/// UE and BU supply for one epoch
+///
+/// Minted, burned and fees are the UE that moved during the epoch; the
+/// rest are balances at the end of it. Amounts are decimal strings: sums
+/// can pass what a Decimal holds
+#[derive(Debug, Clone, Serialize)]
+pub struct EpochSupply {
+    pub epoch: i32,
+    pub ue_minted: String, // WAD
+    pub ue_burned: String, // WAD
+    pub ue_fees: String, // WAD
+    pub ue_circulating: String, // WAD, held by wallets
+    pub bu_treasury: String, // WAD, unreserved
+    pub bu_reserved: String, // WAD, set aside for requested conversions
+    pub bu_pending: String, // WAD, owed to conversions not yet claimed
+    pub bu_wallets: String, // WAD
+}
+
+/// A supply invariant that does not hold
+#[derive(Debug, Clone, Serialize)]
+pub struct SupplyViolation {
+    pub invariant: String,
+    pub expected: String,
+    pub actual: String,
+}
+
+/// Live supply totals and the invariants they break, as decimal strings
+#[derive(Debug, Clone, Serialize)]
+pub struct SupplyReport {
+    pub epoch: i32,
+    pub ue_minted: String, // WAD, since genesis
+    pub ue_burned: String, // WAD, since genesis
+    pub ue_fees: String, // WAD, since genesis
+    pub ue_circulating: String,
+    pub bu_treasury: String,
+    pub bu_reserved: String,
+    pub bu_pending: String,
+    pub bu_wallets: String,
+    pub healthy: bool,
+    pub violations: Vec<SupplyViolation>,
+}
+
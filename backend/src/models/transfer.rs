@@ -0,0 +1,26 @@
+//! BU transfer models
+
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
+
+/// BU transfer record
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct BUTransfer {
+    pub id: i64,
+    pub from_wallet: String,
+    pub to_wallet: String,
+    pub amount_bu: Decimal, // WAD
+    pub memo: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
+/// BU transfer request (sender is the requesting wallet)
+#[derive(Debug, Deserialize)]
+pub struct BUTransferRequest {
+    pub to_wallet: String,
+    pub amount_bu: Decimal,
+    pub memo: Option<String>,
+}
+
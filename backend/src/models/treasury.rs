@@ -3,12 +3,13 @@
 use serde::{Deserialize, Serialize};
 use sqlx::FromRow;
 use chrono::{DateTime, Utc};
+use rust_decimal::Decimal;
 
 /// Treasury state
 #[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
 pub struct Treasury {
     pub id: i64,
-    pub balance_bu: String, // WAD format
+    pub balance_bu: Decimal, // WAD
     pub created_at: DateTime<Utc>,
 }
 
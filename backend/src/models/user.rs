@@ -40,6 +40,30 @@ pub struct ResetWalletRequest {
     pub mfa_code: String,
 }
 
+/// Token request (requires MFA)
+#[derive(Debug, Deserialize)]
+pub struct TokenRequest {
+    pub person_id: String,
+    pub mfa_code: String,
+    pub wallet_address: Option<String>, // a wallet reset away from; default the current one
+}
+
+/// Token request for a wallet derived from an Ed25519 key
+#[derive(Debug, Deserialize)]
+pub struct WalletTokenRequest {
+    pub public_key: String, // hex
+    pub timestamp: i64, // unix timestamp, signed with the wallet address
+    pub signature: String, // hex
+}
+
+/// Bearer token and the wallet it acts for
+#[derive(Debug, Serialize)]
+pub struct TokenResponse {
+    pub token: String,
+    pub wallet_address: String,
+    pub expires_at: i64, // unix timestamp
+}
+
 /// User response
 #[derive(Debug, Serialize)]
 pub struct UserResponse {
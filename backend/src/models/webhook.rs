@@ -0,0 +1,43 @@
+//! Webhook models
+
+use serde::{Deserialize, Serialize};
+use sqlx::FromRow;
+use chrono::{DateTime, Utc};
+
+/// Registered webhook endpoint (secret omitted)
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct WebhookEndpoint {
+    pub id: i64,
+    pub url: String,
+    pub event_types: Option<Vec<String>>, // None: all event types
+    pub is_active: bool,
+    pub created_at: DateTime<Utc>,
+}
+
+/// Webhook registration request
+#[derive(Debug, Deserialize)]
+pub struct WebhookRegistration {
+    pub url: String,
+    pub event_types: Option<Vec<String>>,
+}
+
+/// Webhook registration response; the secret is only shown once
+#[derive(Debug, Serialize)]
+pub struct WebhookRegistered {
+    pub id: i64,
+    pub url: String,
+    pub secret: String,
+}
+
+/// Delivery that exhausted its retries
+#[derive(Debug, Clone, Serialize, Deserialize, FromRow)]
+pub struct DeadLetter {
+    pub id: i64,
+    pub event_id: i64,
+    pub endpoint_id: i64,
+    pub url: String,
+    pub attempts: i32,
+    pub last_error: Option<String>,
+    pub created_at: DateTime<Utc>,
+}
+
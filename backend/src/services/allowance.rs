@@ -0,0 +1,310 @@
+//! Allowance service
+//!
+//! A person lets another wallet (a co-op store, a guardian) spend their
+//! UE, up to a fixed amount per epoch. The allowance belongs to the
+//! grantor's personId, so it follows them through wallet resets: spends
+//! always come from the grantor's current wallet. Approving, revoking and
+//! every spend emit an event.
+
+use crate::models::allowance::{Allowance, AllowanceRequest, AllowanceSpend};
+use crate::models::payment::{Payment, PaymentRequest};
+use crate::services::registry::RegistryService;
+use crate::services::payment::{check_transfer_amount, send_payment, PAYMENT_MEMO_MAX_CHARS};
+use crate::utils::{epoch::current_epoch, errors::UBIError};
+use crate::events::{emit_event, lock_event_chain, AllowanceApprovedEvent, AllowanceRevokedEvent, AllowanceSpentEvent};
+use sqlx::PgPool;
+use rust_decimal::Decimal;
+use log::info;
+use hex;
+
+pub struct AllowanceService {
+    pool: PgPool,
+    registry: RegistryService,
+    genesis_timestamp: i64,
+}
+
+impl AllowanceService {
+    pub fn new(pool: PgPool, registry: RegistryService, genesis_timestamp: i64) -> Self {
+        Self {
+            pool,
+            registry,
+            genesis_timestamp,
+        }
+    }
+    
+    /// Let a wallet spend `wallet`'s person's UE
+    ///
+    /// Approving a spender that already has an active allowance changes its amount
+    pub async fn approve(&self, wallet: &str, req: AllowanceRequest) -> Result<Allowance, UBIError> {
+        let spender_wallet = req.spender_wallet.trim();
+        if spender_wallet.is_empty() {
+            return Err(UBIError::InvalidTransfer("Spender wallet is required".to_string()));
+        }
+        if spender_wallet == wallet {
+            return Err(UBIError::InvalidTransfer("Cannot approve your own wallet".to_string()));
+        }
+        let amount = check_transfer_amount(req.amount_per_epoch)?;
+        
+        let grantor = self.registry
+            .get_user_by_wallet(wallet)
+            .await?
+            .filter(|user| user.is_active)
+            .ok_or(UBIError::WalletNotActive(wallet.to_string()))?;
+        
+        // Start transaction
+        let mut tx = self.pool.begin().await?;
+        lock_event_chain(&mut *tx).await?;
+        
+        let allowance = sqlx::query_as!(
+            Allowance,
+            r#"
+            INSERT INTO ue_allowances (person_id, spender_wallet, amount_per_epoch)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (person_id, spender_wallet) WHERE status = 'active'
+            DO UPDATE SET amount_per_epoch = EXCLUDED.amount_per_epoch
+            RETURNING id, person_id, spender_wallet, amount_per_epoch, status, created_at
+            "#,
+            grantor.person_id.as_slice(),
+            spender_wallet,
+            amount
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        
+        // Emit event
+        emit_event(&mut *tx, &AllowanceApprovedEvent {
+            allowance_id: allowance.id,
+            person_id: hex::encode(&allowance.person_id),
+            spender_wallet: allowance.spender_wallet.clone(),
+            amount_per_epoch: allowance.amount_per_epoch.to_string(),
+        }).await?;
+        
+        // Commit transaction
+        tx.commit().await?;
+        
+        info!("Allowance {}: {} may spend {} UE per epoch from {}",
+              allowance.id, allowance.spender_wallet, allowance.amount_per_epoch, wallet);
+        
+        Ok(allowance)
+    }
+    
+    /// Allowances granted by `wallet`'s person, newest first
+    pub async fn granted(&self, wallet: &str) -> Result<Vec<Allowance>, UBIError> {
+        let user = self.registry
+            .get_user_by_wallet(wallet)
+            .await?
+            .ok_or(UBIError::WalletNotActive(wallet.to_string()))?;
+        
+        let allowances = sqlx::query_as!(
+            Allowance,
+            r#"
+            SELECT id, person_id, spender_wallet, amount_per_epoch, status, created_at
+            FROM ue_allowances
+            WHERE person_id = $1
+            ORDER BY id DESC
+            "#,
+            user.person_id.as_slice()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(allowances)
+    }
+    
+    /// Active allowances `wallet` may spend from, newest first
+    pub async fn received(&self, wallet: &str) -> Result<Vec<Allowance>, UBIError> {
+        let allowances = sqlx::query_as!(
+            Allowance,
+            r#"
+            SELECT id, person_id, spender_wallet, amount_per_epoch, status, created_at
+            FROM ue_allowances
+            WHERE spender_wallet = $1 AND status = 'active'
+            ORDER BY id DESC
+            "#,
+            wallet
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(allowances)
+    }
+    
+    /// Spends under an allowance, visible to its grantor and its spender
+    pub async fn spends(&self, wallet: &str, allowance_id: i64) -> Result<Vec<AllowanceSpend>, UBIError> {
+        let person_id = self.registry
+            .get_user_by_wallet(wallet)
+            .await?
+            .map(|user| user.person_id);
+        
+        let spends = sqlx::query_as!(
+            AllowanceSpend,
+            r#"
+            SELECT s.id, s.allowance_id, s.epoch, s.payment_id, s.amount_ue, s.created_at
+            FROM allowance_spends s
+            JOIN ue_allowances a ON a.id = s.allowance_id
+            WHERE s.allowance_id = $1 AND (a.spender_wallet = $2 OR a.person_id = $3)
+            ORDER BY s.id
+            "#,
+            allowance_id,
+            wallet,
+            person_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(spends)
+    }
+    
+    /// Revoke one of `wallet`'s person's allowances
+    pub async fn revoke(&self, wallet: &str, allowance_id: i64) -> Result<(), UBIError> {
+        let user = self.registry
+            .get_user_by_wallet(wallet)
+            .await?
+            .ok_or(UBIError::WalletNotActive(wallet.to_string()))?;
+        
+        // Start transaction
+        let mut tx = self.pool.begin().await?;
+        lock_event_chain(&mut *tx).await?;
+        
+        let revoked = sqlx::query_scalar!(
+            r#"
+            UPDATE ue_allowances SET status = 'revoked'
+            WHERE id = $1 AND person_id = $2 AND status = 'active'
+            RETURNING id
+            "#,
+            allowance_id,
+            user.person_id.as_slice()
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        
+        if revoked.is_none() {
+            return Err(UBIError::Other("Allowance not found".to_string()));
+        }
+        
+        // Emit event
+        emit_event(&mut *tx, &AllowanceRevokedEvent {
+            allowance_id,
+            person_id: hex::encode(&user.person_id),
+        }).await?;
+        
+        // Commit transaction
+        tx.commit().await?;
+        
+        info!("Allowance {} revoked by {}", allowance_id, wallet);
+        
+        Ok(())
+    }
+    
+    /// Pay from the grantor's current wallet as the allowance's spender
+    ///
+    /// The payment, the spend and its event commit together
+    pub async fn spend(&self, wallet: &str, allowance_id: i64, req: PaymentRequest) -> Result<Payment, UBIError> {
+        let to_wallet = req.to_wallet.trim();
+        if to_wallet.is_empty() {
+            return Err(UBIError::InvalidTransfer("Recipient wallet is required".to_string()));
+        }
+        let amount = check_transfer_amount(req.amount_ue)?;
+        let memo = req.memo
+            .map(|memo| memo.trim().to_string())
+            .filter(|memo| !memo.is_empty());
+        if memo.as_ref().is_some_and(|memo| memo.chars().count() > PAYMENT_MEMO_MAX_CHARS) {
+            return Err(UBIError::InvalidTransfer(
+                format!("Memo longer than {} characters", PAYMENT_MEMO_MAX_CHARS)
+            ));
+        }
+        let epoch = current_epoch(self.genesis_timestamp);
+        
+        // Start transaction
+        let mut tx = self.pool.begin().await?;
+        lock_event_chain(&mut *tx).await?;
+        
+        // Locked so concurrent spends cannot both fit under the limit
+        let allowance = sqlx::query_as!(
+            Allowance,
+            r#"
+            SELECT id, person_id, spender_wallet, amount_per_epoch, status, created_at
+            FROM ue_allowances
+            WHERE id = $1 AND spender_wallet = $2 AND status = 'active'
+            FOR UPDATE
+            "#,
+            allowance_id,
+            wallet
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(UBIError::Other("Allowance not found".to_string()))?;
+        
+        // Held until commit, so a wallet reset cannot move the balance mid-spend
+        let grantor = sqlx::query!(
+            "SELECT wallet_address, is_active FROM users WHERE person_id = $1 FOR SHARE",
+            allowance.person_id.as_slice()
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .filter(|grantor| grantor.is_active)
+        .ok_or(UBIError::InvalidTransfer("Grantor is not active".to_string()))?;
+        if to_wallet == grantor.wallet_address {
+            return Err(UBIError::InvalidTransfer("Cannot pay the grantor's own wallet".to_string()));
+        }
+        
+        let spent = sqlx::query_scalar!(
+            r#"
+            SELECT COALESCE(SUM(amount_ue), 0) AS "spent!"
+            FROM allowance_spends
+            WHERE allowance_id = $1 AND epoch = $2
+            "#,
+            allowance.id,
+            epoch
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        let remaining = (allowance.amount_per_epoch - spent).max(Decimal::ZERO);
+        if amount > remaining {
+            return Err(UBIError::InvalidTransfer(
+                format!("Allowance exceeded: {} UE left this epoch", remaining)
+            ));
+        }
+        
+        let payment = send_payment(
+            &mut tx,
+            &allowance.person_id,
+            &grantor.wallet_address,
+            to_wallet,
+            amount,
+            memo,
+        ).await?;
+        
+        let spend_id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO allowance_spends (allowance_id, epoch, payment_id, amount_ue)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id
+            "#,
+            allowance.id,
+            epoch,
+            payment.id,
+            amount
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        
+        // Emit event
+        emit_event(&mut *tx, &AllowanceSpentEvent {
+            allowance_id: allowance.id,
+            spend_id,
+            epoch,
+            payment_id: payment.id,
+            amount_ue: payment.amount_ue.to_string(),
+        }).await?;
+        
+        // Commit transaction
+        tx.commit().await?;
+        
+        info!("Allowance {} spent by {}: {} UE from {} to {} (payment {})",
+              allowance.id, wallet, payment.amount_ue, payment.from_wallet, payment.to_wallet, payment.id);
+        
+        Ok(payment)
+    }
+}
+
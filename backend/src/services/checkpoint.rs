@@ -0,0 +1,496 @@
+//! Checkpoint service
+//!
+//! As each epoch closes, commits to its final balances, open
+//! conversions and treasury with a signed Merkle root, and to the full
+//! snapshot with a signed hash. Replays can start from a verified
+//! checkpoint instead of event zero, and wallets can prove a single
+//! balance against the published root.
+
+use crate::models::checkpoint::{
+    CheckpointLeaf, CheckpointResponse, LeafProof, ProofStepResponse, StateCheckpoint,
+    WalletProofResponse,
+};
+use crate::models::replay::ReplayState;
+use crate::events::{emit_event, lock_event_chain, CheckpointCreatedEvent, GENESIS_HASH};
+use crate::services::replay::read_live_state;
+use crate::utils::{epoch::{current_epoch, epoch_end_timestamp}, errors::UBIError};
+use crate::utils::canonical::to_canonical_bytes;
+use crate::utils::merkle::{leaf_hash, merkle_proof, merkle_root};
+use crate::utils::signing::{public_key_hex, sign_hex, verify_hex};
+use ed25519_dalek::SigningKey;
+use rust_decimal::Decimal;
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
+use std::str::FromStr;
+use std::time::Duration;
+use log::{error, info, warn};
+use hex;
+
+/// Leaf set written by this build (1: balances, pending conversions, treasury;
+/// 2: adds UBI claims; 3: adds queued and unlocked conversions and reserved BU,
+/// and signs the snapshot hash)
+pub const CHECKPOINT_LEAF_VERSION: i32 = 3;
+
+/// Conversion statuses still owed BU or UE, committed to from leaf version 3
+const OPEN_CONVERSION_STATUSES: [&str; 3] = ["pending", "queued", "unlocked"];
+
+/// Longest the worker sleeps before checking for a new epoch
+const CHECKPOINT_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+pub struct CheckpointService {
+    pool: PgPool,
+    genesis_timestamp: i64,
+    signing_key: Option<SigningKey>, // None: read and verify only
+}
+
+impl CheckpointService {
+    pub fn new(pool: PgPool, genesis_timestamp: i64, signing_key: Option<SigningKey>) -> Self {
+        Self {
+            pool,
+            genesis_timestamp,
+            signing_key,
+        }
+    }
+    
+    /// Checkpoint each epoch as it closes, until the process exits
+    ///
+    /// The worker wakes at the boundary and snapshots the closed epoch
+    /// straight away. It never backfills: an epoch that closed before it
+    /// started, or while it was not running, is left without a checkpoint
+    /// rather than signed with state from after its boundary.
+    pub async fn run_worker(self) {
+        let mut last_epoch = current_epoch(self.genesis_timestamp);
+        if last_epoch > 0 {
+            match self.get_checkpoint(last_epoch - 1).await {
+                Ok(Some(_)) => {}
+                Ok(None) => warn!("Epoch {} closed before the checkpoint worker started; not backfilling it", last_epoch - 1),
+                Err(e) => error!("Checkpoint lookup for epoch {} failed: {}", last_epoch - 1, e),
+            }
+        }
+        
+        loop {
+            let until_boundary = epoch_end_timestamp(last_epoch, self.genesis_timestamp) - chrono::Utc::now().timestamp();
+            let sleep = CHECKPOINT_POLL_INTERVAL.min(Duration::from_secs(until_boundary.max(0) as u64));
+            tokio::time::sleep(sleep).await;
+            
+            let epoch = current_epoch(self.genesis_timestamp);
+            if epoch == last_epoch + 1 {
+                if let Err(e) = self.ensure_checkpoint(last_epoch).await {
+                    error!("Checkpoint for epoch {} failed: {}", last_epoch, e);
+                }
+            } else if epoch > last_epoch + 1 {
+                warn!("Epochs {} to {} closed while the checkpoint worker was not polling; not backfilling them",
+                      last_epoch, epoch - 1);
+            }
+            last_epoch = epoch;
+        }
+    }
+    
+    /// Checkpoint for a closed epoch, creating it from current state if missing
+    pub async fn ensure_checkpoint(&self, epoch: i32) -> Result<StateCheckpoint, UBIError> {
+        if let Some(checkpoint) = self.get_checkpoint(epoch).await? {
+            return Ok(checkpoint);
+        }
+        self.create_checkpoint(epoch).await
+    }
+    
+    /// Snapshot live state, sign its Merkle root and store it for `epoch`
+    ///
+    /// If another writer got there first, its checkpoint is returned
+    pub async fn create_checkpoint(&self, epoch: i32) -> Result<StateCheckpoint, UBIError> {
+        let signing_key = self.signing_key.as_ref()
+            .ok_or_else(|| UBIError::Other("Checkpoint signing key not configured".to_string()))?;
+        
+        // State and the last event must come from the same snapshot
+        let mut snapshot_tx = self.pool.begin().await?;
+        sqlx::query!("SET TRANSACTION ISOLATION LEVEL REPEATABLE READ READ ONLY")
+            .execute(&mut *snapshot_tx)
+            .await?;
+        let last_event = sqlx::query!(
+            "SELECT id, hash FROM events ORDER BY id DESC LIMIT 1"
+        )
+        .fetch_optional(&mut *snapshot_tx)
+        .await?;
+        let state = read_live_state(&mut snapshot_tx).await?;
+        snapshot_tx.commit().await?;
+        
+        let (last_event_id, last_event_hash) = match last_event {
+            Some(row) => (row.id, row.hash.unwrap_or_else(|| GENESIS_HASH.to_string())),
+            None => (0, GENESIS_HASH.to_string()),
+        };
+        
+        let leaf_version = CHECKPOINT_LEAF_VERSION;
+        let leaves = checkpoint_leaves(&state, leaf_version)?;
+        let merkle_root = root_of(&leaves);
+        let leaf_count = leaves.len() as i32;
+        let snapshot = serde_json::to_value(&state)
+            .map_err(|e| UBIError::Other(format!("Snapshot serialization failed: {}", e)))?;
+        let snapshot_hash = snapshot_hash(&snapshot)?;
+        let message = checkpoint_message(
+            leaf_version, epoch, &merkle_root, leaf_count, last_event_id, &last_event_hash,
+            Some(&snapshot_hash),
+        );
+        let signature = sign_hex(signing_key, message.as_bytes());
+        let public_key = public_key_hex(signing_key);
+        
+        // Written outside the snapshot so the event links to the chain head
+        let mut tx = self.pool.begin().await?;
+        lock_event_chain(&mut *tx).await?;
+        let inserted = sqlx::query!(
+            r#"
+            INSERT INTO state_checkpoints
+                (epoch, leaf_version, merkle_root, leaf_count, last_event_id, last_event_hash,
+                 snapshot, snapshot_hash, signature, public_key)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10)
+            ON CONFLICT (epoch) DO NOTHING
+            "#,
+            epoch,
+            leaf_version,
+            merkle_root,
+            leaf_count,
+            last_event_id,
+            last_event_hash,
+            snapshot,
+            snapshot_hash,
+            signature,
+            public_key
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        
+        if inserted == 0 {
+            tx.rollback().await?;
+            return self.get_checkpoint(epoch).await?
+                .ok_or(UBIError::CheckpointNotFound(epoch));
+        }
+        
+        let indexes: Vec<i32> = (0..leaf_count).collect();
+        let keys: Vec<String> = leaves.iter().map(|l| l.leaf_key.clone()).collect();
+        let data: Vec<String> = leaves.iter().map(|l| l.leaf_data.clone()).collect();
+        sqlx::query!(
+            r#"
+            INSERT INTO checkpoint_leaves (epoch, leaf_index, leaf_key, leaf_data)
+            SELECT $1, * FROM UNNEST($2::INTEGER[], $3::TEXT[], $4::TEXT[])
+            "#,
+            epoch,
+            &indexes,
+            &keys,
+            &data
+        )
+        .execute(&mut *tx)
+        .await?;
+        
+        emit_event(&mut *tx, &CheckpointCreatedEvent {
+            epoch,
+            merkle_root: merkle_root.clone(),
+            leaf_count,
+            last_event_id,
+            last_event_hash: last_event_hash.clone(),
+        }).await?;
+        
+        tx.commit().await?;
+        
+        info!("Checkpoint for epoch {}: root {} over {} leaves (through event {})",
+              epoch, merkle_root, leaf_count, last_event_id);
+        
+        self.get_checkpoint(epoch).await?
+            .ok_or(UBIError::CheckpointNotFound(epoch))
+    }
+    
+    /// Stored checkpoint for an epoch
+    pub async fn get_checkpoint(&self, epoch: i32) -> Result<Option<StateCheckpoint>, UBIError> {
+        let checkpoint = sqlx::query_as!(
+            StateCheckpoint,
+            r#"
+            SELECT epoch, leaf_version, merkle_root, leaf_count, last_event_id, last_event_hash,
+                   snapshot_hash, signature, public_key, created_at
+            FROM state_checkpoints
+            WHERE epoch = $1
+            "#,
+            epoch
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(checkpoint)
+    }
+    
+    /// Most recent stored checkpoint
+    pub async fn latest_checkpoint(&self) -> Result<Option<StateCheckpoint>, UBIError> {
+        let checkpoint = sqlx::query_as!(
+            StateCheckpoint,
+            r#"
+            SELECT epoch, leaf_version, merkle_root, leaf_count, last_event_id, last_event_hash,
+                   snapshot_hash, signature, public_key, created_at
+            FROM state_checkpoints
+            ORDER BY epoch DESC
+            LIMIT 1
+            "#
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        Ok(checkpoint)
+    }
+    
+    /// Load a checkpoint's snapshot after checking it against the signed root
+    /// and the signed snapshot hash
+    ///
+    /// Checkpoints before leaf version 3 sign only their leaves, so the rest of
+    /// their snapshot cannot be trusted and they are refused
+    pub async fn load_verified_snapshot(
+        &self,
+        epoch: i32,
+    ) -> Result<(StateCheckpoint, ReplayState), UBIError> {
+        let checkpoint = self.get_checkpoint(epoch).await?
+            .ok_or(UBIError::CheckpointNotFound(epoch))?;
+        
+        // Only trust checkpoints signed by our own key, when we have one
+        if let Some(signing_key) = &self.signing_key {
+            if public_key_hex(signing_key) != checkpoint.public_key {
+                return Err(UBIError::CheckpointInvalid("signed by an unknown key".to_string()));
+            }
+        }
+        if !verify_hex(&checkpoint.public_key, signed_message(&checkpoint).as_bytes(), &checkpoint.signature) {
+            return Err(UBIError::CheckpointInvalid("bad signature".to_string()));
+        }
+        let Some(signed_hash) = checkpoint.snapshot_hash.as_deref().filter(|_| checkpoint.leaf_version >= 3) else {
+            return Err(UBIError::CheckpointInvalid(format!(
+                "leaf version {} signs only its leaves; replay from genesis instead", checkpoint.leaf_version
+            )));
+        };
+        
+        let snapshot = sqlx::query_scalar!(
+            "SELECT snapshot FROM state_checkpoints WHERE epoch = $1",
+            epoch
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        let hash = snapshot_hash(&snapshot)?;
+        if hash != signed_hash {
+            return Err(UBIError::CheckpointInvalid(format!(
+                "snapshot hash {} does not match signed hash {}", hash, signed_hash
+            )));
+        }
+        let state: ReplayState = serde_json::from_value(snapshot)
+            .map_err(|e| UBIError::CheckpointInvalid(format!("unreadable snapshot: {}", e)))?;
+        
+        let leaves = checkpoint_leaves(&state, checkpoint.leaf_version)?;
+        let root = root_of(&leaves);
+        if root != checkpoint.merkle_root || leaves.len() as i32 != checkpoint.leaf_count {
+            return Err(UBIError::CheckpointInvalid(format!(
+                "snapshot root {} does not match signed root {}", root, checkpoint.merkle_root
+            )));
+        }
+        
+        Ok((checkpoint, state))
+    }
+    
+    /// Inclusion proofs for a wallet's balances and UBI claims
+    ///
+    /// Uses the given epoch's checkpoint, or the latest one
+    pub async fn wallet_proofs(
+        &self,
+        wallet_address: &str,
+        epoch: Option<i32>,
+    ) -> Result<WalletProofResponse, UBIError> {
+        let checkpoint = match epoch {
+            Some(epoch) => self.get_checkpoint(epoch).await?.ok_or(UBIError::CheckpointNotFound(epoch))?,
+            None => self.latest_checkpoint().await?
+                .ok_or_else(|| UBIError::Other("No checkpoints yet".to_string()))?,
+        };
+        
+        let leaves = sqlx::query!(
+            r#"
+            SELECT leaf_index, leaf_key, leaf_data
+            FROM checkpoint_leaves
+            WHERE epoch = $1
+            ORDER BY leaf_index
+            "#,
+            checkpoint.epoch
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        
+        // Claims are keyed by person; resolve the wallet as of the checkpoint
+        let person_id = sqlx::query_scalar!(
+            r#"
+            SELECT u.key AS "person_id!"
+            FROM state_checkpoints c, jsonb_each(c.snapshot->'users') u
+            WHERE c.epoch = $1 AND u.value->>'wallet_address' = $2
+            "#,
+            checkpoint.epoch,
+            wallet_address
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        let ue_key = format!("ue_balance|{}", wallet_address);
+        let bu_key = format!("bu_balance|{}", wallet_address);
+        let claim_prefix = person_id.map(|p| format!("ubi_claim|{}|", p));
+        let is_wallet_leaf = |key: &str| {
+            key == ue_key
+                || key == bu_key
+                || claim_prefix.as_deref().map_or(false, |prefix| key.starts_with(prefix))
+        };
+        
+        let hashes: Vec<[u8; 32]> = leaves.iter().map(|l| leaf_hash(l.leaf_data.as_bytes())).collect();
+        let mut proofs = Vec::new();
+        for leaf in leaves.iter().filter(|l| is_wallet_leaf(&l.leaf_key)) {
+            let proof = merkle_proof(&hashes, leaf.leaf_index as usize)
+                .ok_or_else(|| UBIError::Other(format!("Leaf {} out of range", leaf.leaf_index)))?;
+            proofs.push(LeafProof {
+                leaf_index: leaf.leaf_index,
+                leaf_key: leaf.leaf_key.clone(),
+                leaf_data: leaf.leaf_data.clone(),
+                leaf_hash: hex::encode(hashes[leaf.leaf_index as usize]),
+                proof: proof.iter().map(|step| ProofStepResponse {
+                    sibling: hex::encode(step.sibling),
+                    position: if step.sibling_is_left { "left" } else { "right" }.to_string(),
+                }).collect(),
+            });
+        }
+        
+        Ok(WalletProofResponse {
+            wallet_address: wallet_address.to_string(),
+            checkpoint: checkpoint.into(),
+            leaves: proofs,
+        })
+    }
+}
+
+impl From<StateCheckpoint> for CheckpointResponse {
+    fn from(checkpoint: StateCheckpoint) -> Self {
+        let signed_message = signed_message(&checkpoint);
+        Self {
+            checkpoint,
+            signed_message,
+        }
+    }
+}
+
+/// Message covered by a checkpoint's signature
+///
+/// The snapshot hash is appended from leaf version 3 on
+pub fn checkpoint_message(
+    leaf_version: i32,
+    epoch: i32,
+    merkle_root: &str,
+    leaf_count: i32,
+    last_event_id: i64,
+    last_event_hash: &str,
+    snapshot_hash: Option<&str>,
+) -> String {
+    let mut message = format!(
+        "tw-ubi-checkpoint|v{}|{}|{}|{}|{}|{}",
+        leaf_version, epoch, merkle_root, leaf_count, last_event_id, last_event_hash
+    );
+    if let Some(snapshot_hash) = snapshot_hash.filter(|_| leaf_version >= 3) {
+        message.push('|');
+        message.push_str(snapshot_hash);
+    }
+    message
+}
+
+/// Message a stored checkpoint was signed over
+pub fn signed_message(checkpoint: &StateCheckpoint) -> String {
+    checkpoint_message(
+        checkpoint.leaf_version,
+        checkpoint.epoch,
+        &checkpoint.merkle_root,
+        checkpoint.leaf_count,
+        checkpoint.last_event_id,
+        &checkpoint.last_event_hash,
+        checkpoint.snapshot_hash.as_deref(),
+    )
+}
+
+/// Hex SHA-256 over a snapshot's canonical encoding, which JSONB round trips keep
+pub fn snapshot_hash(snapshot: &serde_json::Value) -> Result<String, UBIError> {
+    let bytes = to_canonical_bytes(snapshot)
+        .map_err(|e| UBIError::Other(format!("Snapshot has no canonical form: {}", e)))?;
+    Ok(hex::encode(Sha256::digest(&bytes)))
+}
+
+/// Merkle leaves for a state, sorted by key
+///
+/// Leaf data is `kind|key|fields...` with amounts in normalized decimal form:
+/// `ue_balance|wallet|balance`, `bu_balance|wallet|balance`,
+/// `conversion|id|personId|amount_ue|amount_bu|rate_index|unlock_epoch`
+/// (pending only) and `treasury|balance_bu`; version 2 adds
+/// `ubi_claim|personId|epoch|amount_ue`. Version 3 covers every open
+/// conversion as `conversion|id|personId|status|amount_ue|amount_bu|rate_index|unlock_epoch`
+/// (pending, queued or unlocked) and the treasury as
+/// `treasury|balance_bu|reserved_bu`
+pub fn checkpoint_leaves(state: &ReplayState, leaf_version: i32) -> Result<Vec<CheckpointLeaf>, UBIError> {
+    let mut leaves = Vec::new();
+    
+    for (wallet, balance) in &state.ue_balances {
+        leaves.push(leaf(format!("ue_balance|{}", wallet), &[&canonical_amount(balance)?]));
+    }
+    for (wallet, balance) in &state.bu_balances {
+        leaves.push(leaf(format!("bu_balance|{}", wallet), &[&canonical_amount(balance)?]));
+    }
+    for (id, conversion) in &state.pending_conversions {
+        let amounts = [
+            canonical_amount(&conversion.amount_ue)?,
+            canonical_amount(&conversion.amount_bu)?,
+            canonical_amount(&conversion.rate_index)?,
+        ];
+        let unlock_epoch = conversion.unlock_epoch.to_string();
+        if leaf_version >= 3 {
+            if !OPEN_CONVERSION_STATUSES.contains(&conversion.status.as_str()) {
+                continue;
+            }
+            leaves.push(leaf(format!("conversion|{}", id), &[
+                &conversion.person_id, &conversion.status, &amounts[0], &amounts[1], &amounts[2], &unlock_epoch,
+            ]));
+        } else if conversion.status == "pending" {
+            leaves.push(leaf(format!("conversion|{}", id), &[
+                &conversion.person_id, &amounts[0], &amounts[1], &amounts[2], &unlock_epoch,
+            ]));
+        }
+    }
+    if leaf_version >= 3 {
+        leaves.push(leaf("treasury".to_string(), &[
+            &canonical_amount(&state.treasury_bu)?,
+            &canonical_amount(&state.treasury_reserved_bu)?,
+        ]));
+    } else {
+        leaves.push(leaf("treasury".to_string(), &[&canonical_amount(&state.treasury_bu)?]));
+    }
+    if leaf_version >= 2 {
+        for (person_id, claims) in &state.ubi_claims {
+            for (epoch, amount) in claims {
+                leaves.push(leaf(format!("ubi_claim|{}|{}", person_id, epoch), &[&canonical_amount(amount)?]));
+            }
+        }
+    }
+    
+    leaves.sort_by(|a, b| a.leaf_key.cmp(&b.leaf_key));
+    Ok(leaves)
+}
+
+/// Hex-encoded Merkle root over leaves, in order
+pub fn root_of(leaves: &[CheckpointLeaf]) -> String {
+    let hashes: Vec<[u8; 32]> = leaves.iter().map(|l| leaf_hash(l.leaf_data.as_bytes())).collect();
+    hex::encode(merkle_root(&hashes))
+}
+
+fn leaf(leaf_key: String, fields: &[&str]) -> CheckpointLeaf {
+    let mut leaf_data = leaf_key.clone();
+    for field in fields {
+        leaf_data.push('|');
+        leaf_data.push_str(field);
+    }
+    CheckpointLeaf { leaf_key, leaf_data }
+}
+
+/// Amounts are stored as text; "100" and "100.0" must hash the same
+fn canonical_amount(amount: &str) -> Result<String, UBIError> {
+    Decimal::from_str(amount)
+        .map(|d| d.normalize().to_string())
+        .map_err(|e| UBIError::Other(format!("Invalid amount {}: {}", amount, e)))
+}
+
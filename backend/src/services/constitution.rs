@@ -0,0 +1,135 @@
+//! Constitution service
+//! 
+//! CONSTITUTIONAL: Parameters are frozen at deployment
+//! A fork may amend them from a given epoch onward; history before that
+//! epoch keeps the parameters it was written under
+
+use crate::models::constitution::{ConstitutionAmendment, ConstitutionParameters};
+use crate::events::{emit_event, lock_event_chain, ConstitutionAmendedEvent};
+use crate::utils::errors::UBIError;
+use sqlx::{PgConnection, PgPool};
+use log::info;
+
+pub struct ConstitutionService {
+    pool: PgPool,
+}
+
+impl ConstitutionService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+    
+    /// Parameters in force at an epoch
+    pub async fn parameters_at(&self, epoch: i32) -> Result<ConstitutionParameters, UBIError> {
+        let mut conn = self.pool.acquire().await?;
+        parameters_at(&mut conn, epoch).await
+    }
+    
+    /// Amend parameters from an epoch onward
+    pub async fn amend(&self, amendment: ConstitutionAmendment) -> Result<ConstitutionParameters, UBIError> {
+        let mut tx = self.pool.begin().await?;
+        lock_event_chain(&mut *tx).await?;
+        let parameters = amend(&mut tx, amendment).await?;
+        tx.commit().await?;
+        Ok(parameters)
+    }
+}
+
+/// Parameters in force at an epoch, read on the caller's connection
+pub async fn parameters_at(conn: &mut PgConnection, epoch: i32) -> Result<ConstitutionParameters, UBIError> {
+    let parameters = sqlx::query_as!(
+        ConstitutionParameters,
+        r#"
+        SELECT effective_epoch, ue_mint_per_epoch, conversion_fee_bps, conversion_cap_ue, conversion_delay_epochs,
+               cancellation_refunds_fee, rationing_threshold_bu
+        FROM constitution_parameters
+        WHERE effective_epoch <= $1
+        ORDER BY effective_epoch DESC
+        LIMIT 1
+        "#,
+        epoch
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+    
+    Ok(parameters.unwrap_or_else(ConstitutionParameters::genesis))
+}
+
+/// Record an amendment and emit ConstitutionAmended
+///
+/// Amendments may only be appended after the latest one
+pub async fn amend(
+    conn: &mut PgConnection,
+    amendment: ConstitutionAmendment,
+) -> Result<ConstitutionParameters, UBIError> {
+    let latest = sqlx::query_scalar!("SELECT MAX(effective_epoch) FROM constitution_parameters")
+        .fetch_one(&mut *conn)
+        .await?;
+    if let Some(latest) = latest {
+        if amendment.effective_epoch <= latest {
+            return Err(UBIError::Other(format!(
+                "Amendments must take effect after epoch {}", latest
+            )));
+        }
+    }
+    
+    let current = parameters_at(conn, amendment.effective_epoch).await?;
+    let parameters = ConstitutionParameters {
+        effective_epoch: amendment.effective_epoch,
+        ue_mint_per_epoch: amendment.ue_mint_per_epoch.unwrap_or(current.ue_mint_per_epoch),
+        conversion_fee_bps: amendment.conversion_fee_bps.unwrap_or(current.conversion_fee_bps),
+        conversion_cap_ue: amendment.conversion_cap_ue.unwrap_or(current.conversion_cap_ue),
+        conversion_delay_epochs: amendment.conversion_delay_epochs.unwrap_or(current.conversion_delay_epochs),
+        cancellation_refunds_fee: amendment.cancellation_refunds_fee.unwrap_or(current.cancellation_refunds_fee),
+        rationing_threshold_bu: amendment.rationing_threshold_bu.unwrap_or(current.rationing_threshold_bu),
+    };
+    
+    for amount in [parameters.ue_mint_per_epoch, parameters.conversion_cap_ue, parameters.rationing_threshold_bu] {
+        if amount.is_sign_negative() || !amount.fract().is_zero() {
+            return Err(UBIError::InvalidInput(format!("Invalid amount: {}", amount)));
+        }
+    }
+    if !(0..=10_000).contains(&parameters.conversion_fee_bps) {
+        return Err(UBIError::InvalidInput(format!(
+            "Conversion fee must be 0 to 10000 bps, got {}", parameters.conversion_fee_bps
+        )));
+    }
+    if parameters.conversion_delay_epochs < 0 {
+        return Err(UBIError::InvalidInput(format!(
+            "Conversion delay cannot be negative, got {}", parameters.conversion_delay_epochs
+        )));
+    }
+    
+    sqlx::query!(
+        r#"
+        INSERT INTO constitution_parameters
+            (effective_epoch, ue_mint_per_epoch, conversion_fee_bps, conversion_cap_ue, conversion_delay_epochs,
+             cancellation_refunds_fee, rationing_threshold_bu)
+        VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+        parameters.effective_epoch,
+        parameters.ue_mint_per_epoch,
+        parameters.conversion_fee_bps,
+        parameters.conversion_cap_ue,
+        parameters.conversion_delay_epochs,
+        parameters.cancellation_refunds_fee,
+        parameters.rationing_threshold_bu
+    )
+    .execute(&mut *conn)
+    .await?;
+    
+    emit_event(&mut *conn, &ConstitutionAmendedEvent {
+        effective_epoch: parameters.effective_epoch,
+        ue_mint_per_epoch: parameters.ue_mint_per_epoch.to_string(),
+        conversion_fee_bps: parameters.conversion_fee_bps,
+        conversion_cap_ue: parameters.conversion_cap_ue.to_string(),
+        conversion_delay_epochs: parameters.conversion_delay_epochs,
+        cancellation_refunds_fee: parameters.cancellation_refunds_fee,
+        rationing_threshold_bu: parameters.rationing_threshold_bu.to_string(),
+    }).await?;
+    
+    info!("Constitution amended from epoch {}: {:?}", parameters.effective_epoch, parameters);
+    
+    Ok(parameters)
+}
+
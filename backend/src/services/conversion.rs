@@ -3,17 +3,30 @@
 //! CONSTITUTIONAL: UE balances do NOT decay
 //! Conversion power decays via rateIndex
 
-use crate::models::conversion::{PendingConversion, ConversionRequest, ConversionResponse};
+use crate::models::conversion::{PendingConversion, ConversionQuote, ConversionRequest, ConversionResponse};
 use crate::services::registry::RegistryService;
 use crate::services::rate_index::RateIndexService;
-use crate::utils::{epoch::current_epoch, errors::UBIError, wad};
-use crate::constants::{CONVERSION_CAP_UE, CONVERSION_DELAY_EPOCHS, CONVERSION_FEE_BPS};
-use crate::events::{emit_event, ConversionRequestedEvent};
-use sqlx::PgPool;
+use crate::services::constitution::parameters_at;
+use crate::services::journal::post_entry;
+use crate::models::journal::{Account, JournalEntry, Unit};
+use crate::utils::{epoch::{current_epoch, epoch_at, epoch_start_timestamp}, errors::UBIError, wad};
+use crate::events::{
+    emit_event, lock_event_chain, ConversionCancelledEvent, ConversionClaimedEvent, ConversionRationedEvent,
+    ConversionRequestedEvent, ConversionUnlockedEvent, TreasuryDebitedEvent,
+};
+use sqlx::{Connection, PgConnection, PgPool};
 use rust_decimal::Decimal;
-use log::info;
+use std::collections::BTreeMap;
+use std::time::Duration;
+use log::{error, info, warn};
 use hex;
 
+/// Conversions settled per worker transaction
+const SETTLEMENT_BATCH_SIZE: i64 = 100;
+
+/// How often the worker looks for conversions past their unlock epoch
+const SETTLEMENT_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
 pub struct ConversionService {
     pool: PgPool,
     registry: RegistryService,
@@ -44,9 +57,6 @@ impl ConversionService {
     ) -> Result<ConversionResponse, UBIError> {
         info!("Conversion request: {} UE from wallet {}", req.amount_ue, wallet);
         
-        // Start transaction
-        let mut tx = self.pool.begin().await?;
-        
         // Get user
         let user = self.registry
             .get_user_by_wallet(wallet)
@@ -55,139 +65,161 @@ impl ConversionService {
         
         let epoch = current_epoch(self.genesis_timestamp);
         
-        // Check per-epoch cap
-        let converted_this_epoch = self.get_converted_this_epoch(&user.person_id, epoch).await?;
-        let converted_decimal: Decimal = converted_this_epoch.parse()
-            .map_err(|_| UBIError::Other("Invalid converted amount".to_string()))?;
-        let amount_decimal: Decimal = req.amount_ue.parse()
-            .map_err(|_| UBIError::Other("Invalid amount".to_string()))?;
-        let total_converted = converted_decimal + amount_decimal;
-        let cap_wad: Decimal = CONVERSION_CAP_UE.parse()
-            .map_err(|_| UBIError::Other("Invalid cap".to_string()))?;
-        
-        if total_converted > cap_wad {
-            return Err(UBIError::ConversionCapExceeded(
-                format!("Exceeds per-epoch cap of {}", CONVERSION_CAP_UE)
-            ));
-        }
-        
-        // Roll rate index to current epoch
-        self.rate_index.roll_rate_index(user.region_id).await?;
-        
-        // Get current rate index
+        // Roll rate index to current epoch and read it; the roll commits on its own
+        // connection, so it has to run before this transaction takes the event chain lock
         let rate_index_value = self.rate_index.get_rate_index(user.region_id).await?;
-        if rate_index_value.is_empty() || rate_index_value == "0" {
+        if rate_index_value.is_zero() {
             return Err(UBIError::RateIndexNotInitialized(user.region_id));
         }
         
-        // Calculate BU amount (with fee)
-        let fee_amount = wad::mul_wad(&req.amount_ue, &CONVERSION_FEE_BPS.to_string())?;
-        let fee_decimal: Decimal = fee_amount.parse()
-            .map_err(|_| UBIError::Other("Invalid fee".to_string()))?;
-        let amount_ue_decimal: Decimal = req.amount_ue.parse()
-            .map_err(|_| UBIError::Other("Invalid amount".to_string()))?;
+        // Start transaction
+        let mut tx = self.pool.begin().await?;
+        lock_event_chain(&mut *tx).await?;
         
-        let amount_ue_after_fee = (amount_ue_decimal - (fee_decimal / Decimal::from(10000))).to_string();
-        let amount_bu = wad::mul_wad(&amount_ue_after_fee, &rate_index_value)?;
+        let parameters = parameters_at(&mut *tx, epoch).await?;
         
-        // Slippage protection
-        let min_bu_decimal: Decimal = req.min_bu_out.parse()
-            .map_err(|_| UBIError::Other("Invalid min_bu_out".to_string()))?;
-        let amount_bu_decimal: Decimal = amount_bu.parse()
-            .map_err(|_| UBIError::Other("Invalid amount_bu".to_string()))?;
+        // Check per-epoch cap against the locked row, so concurrent requests queue behind
+        // each other; the row is created first because a missing row cannot be locked
+        sqlx::query!(
+            r#"
+            INSERT INTO converted_this_epoch (person_id, epoch, amount_ue)
+            VALUES ($1, $2, 0)
+            ON CONFLICT (person_id, epoch) DO NOTHING
+            "#,
+            user.person_id.as_slice(),
+            epoch
+        )
+        .execute(&mut *tx)
+        .await?;
+        let converted_this_epoch = sqlx::query_scalar!(
+            "SELECT amount_ue FROM converted_this_epoch WHERE person_id = $1 AND epoch = $2 FOR UPDATE",
+            user.person_id.as_slice(),
+            epoch
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        check_cap(req.amount_ue, converted_this_epoch, parameters.conversion_cap_ue)?;
+        
+        // Calculate BU amount (with fee)
+        let (fee_ue, amount_bu) = price(req.amount_ue, parameters.conversion_fee_bps, rate_index_value)?;
         
-        if amount_bu_decimal < min_bu_decimal {
-            return Err(UBIError::SlippageTooHigh(req.min_bu_out, amount_bu));
+        // Slippage protection
+        if amount_bu < req.min_bu_out {
+            return Err(UBIError::SlippageTooHigh(req.min_bu_out.to_string(), amount_bu.to_string()));
         }
         
         // Check UE balance
         let ue_balance = sqlx::query_scalar!(
-            "SELECT balance FROM ue_balances WHERE wallet_address = $1",
+            "SELECT balance FROM ue_balances WHERE wallet_address = $1 FOR UPDATE",
             wallet
         )
         .fetch_optional(&mut *tx)
         .await?
-        .unwrap_or_else(|| "0".to_string());
-        
-        let balance_decimal: Decimal = ue_balance.parse()
-            .map_err(|_| UBIError::Other("Invalid balance".to_string()))?;
+        .unwrap_or(Decimal::ZERO);
         
-        if balance_decimal < amount_ue_decimal {
+        if ue_balance < req.amount_ue {
             return Err(UBIError::InsufficientBalance);
         }
         
-        // Burn UE (deduct from balance)
-        let new_balance = (balance_decimal - amount_ue_decimal).to_string();
-        sqlx::query!(
-            "UPDATE ue_balances SET balance = $1 WHERE wallet_address = $2",
-            &new_balance,
-            wallet
+        // Reserve the BU now, so the claim cannot find the treasury short
+        let treasury_balance = sqlx::query_scalar!(
+            "SELECT balance_bu FROM treasury ORDER BY id DESC LIMIT 1 FOR UPDATE"
         )
-        .execute(&mut *tx)
-        .await?;
+        .fetch_optional(&mut *tx)
+        .await?
+        .unwrap_or(Decimal::ZERO);
+        
+        // Below the threshold the request waits for its epoch's rationing batch instead
+        let queued = treasury_balance < parameters.rationing_threshold_bu;
+        if !queued {
+            check_treasury(treasury_balance, amount_bu)?;
+        }
         
         // Record conversion
-        let unlock_epoch = epoch + CONVERSION_DELAY_EPOCHS;
-        sqlx::query!(
+        let unlock_epoch = epoch + parameters.conversion_delay_epochs;
+        let conversion_id = sqlx::query_scalar!(
             r#"
-            INSERT INTO pending_conversions (person_id, amount_ue, amount_bu, rate_index, unlock_epoch, status)
-            VALUES ($1, $2, $3, $4, $5, 'pending')
+            INSERT INTO pending_conversions (person_id, amount_ue, amount_bu, rate_index, unlock_epoch, status, reserved)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            RETURNING id
             "#,
             user.person_id.as_slice(),
-            &req.amount_ue,
-            &amount_bu,
-            &rate_index_value,
-            unlock_epoch
+            req.amount_ue,
+            amount_bu,
+            rate_index_value,
+            unlock_epoch,
+            if queued { "queued" } else { "pending" },
+            !queued
         )
-        .execute(&mut *tx)
+        .fetch_one(&mut *tx)
         .await?;
         
         // Update converted this epoch
-        let new_converted = total_converted.to_string();
         sqlx::query!(
             r#"
             INSERT INTO converted_this_epoch (person_id, epoch, amount_ue)
             VALUES ($1, $2, $3)
             ON CONFLICT (person_id, epoch)
-            DO UPDATE SET amount_ue = $3
+            DO UPDATE SET amount_ue = converted_this_epoch.amount_ue + EXCLUDED.amount_ue
             "#,
             user.person_id.as_slice(),
             epoch,
-            &new_converted
+            req.amount_ue
         )
         .execute(&mut *tx)
         .await?;
         
+        if !queued {
+            emit_event(&mut *tx, &TreasuryDebitedEvent {
+                amount_bu: amount_bu.to_string(),
+                balance_bu: (treasury_balance - amount_bu).to_string(),
+                conversion_id,
+            }).await?;
+        }
+        
         // Emit event
-        let event_data = serde_json::to_value(crate::events::ConversionRequestedEvent {
+        let event_id = emit_event(&mut *tx, &ConversionRequestedEvent {
+            conversion_id: Some(conversion_id),
             person_id: hex::encode(&user.person_id),
             wallet_address: wallet.to_string(),
-            amount_ue: req.amount_ue.clone(),
-            amount_bu: amount_bu.clone(),
-            rate_index: rate_index_value.clone(),
+            amount_ue: req.amount_ue.to_string(),
+            amount_bu: amount_bu.to_string(),
+            rate_index: rate_index_value.to_string(),
             unlock_epoch,
-        }).unwrap();
-        
-        emit_event(&mut *tx, "ConversionRequested", &event_data).await?;
+            fee_ue: Some(fee_ue.to_string()),
+            bu_reserved: !queued,
+            queued,
+        }).await?;
+        
+        // Burn UE (the fee is burned separately so it stays visible) and reserve BU
+        let mut entry = JournalEntry::new()
+            .transfer(Unit::UE, Account::Wallet(wallet.to_string()), Account::Burn, req.amount_ue - fee_ue)
+            .transfer(Unit::UE, Account::Wallet(wallet.to_string()), Account::Fees, fee_ue);
+        if !queued {
+            entry = entry.transfer(Unit::BU, Account::Treasury, Account::Reserved, amount_bu);
+        }
+        post_entry(&mut *tx, event_id, "Conversion request", &entry).await?;
         
         // Commit transaction
         tx.commit().await?;
         
-        info!("Conversion successful: {} UE -> {} BU, unlocks at epoch {}", 
-              req.amount_ue, amount_bu, unlock_epoch);
+        if queued {
+            info!("Conversion queued for rationing: {} UE -> up to {} BU, unlocks at epoch {}",
+                  req.amount_ue, amount_bu, unlock_epoch);
+        } else {
+            info!("Conversion successful: {} UE -> {} BU, unlocks at epoch {}", 
+                  req.amount_ue, amount_bu, unlock_epoch);
+        }
         
         // Get created conversion
         let conversion = sqlx::query_as!(
             PendingConversion,
             r#"
-            SELECT id, person_id, amount_ue, amount_bu, rate_index, unlock_epoch, status, created_at
+            SELECT id, person_id, amount_ue, amount_bu, rate_index, unlock_epoch, status, reserved, created_at
             FROM pending_conversions
-            WHERE person_id = $1 AND unlock_epoch = $2
-            ORDER BY created_at DESC
-            LIMIT 1
+            WHERE id = $1
             "#,
-            user.person_id.as_slice(),
-            unlock_epoch
+            conversion_id
         )
         .fetch_one(&self.pool)
         .await?;
@@ -195,12 +227,61 @@ impl ConversionService {
         Ok(conversion.into())
     }
     
+    /// What `request_conversion` would give for `amount_ue` now, without writing anything
+    ///
+    /// Runs the same cap, fee, rate index and treasury logic, with the rate
+    /// index rolled forward in memory only. While the treasury is rationed
+    /// the quote is flagged and `amount_bu` is the most the request can get
+    pub async fn quote(&self, wallet: &str, amount_ue: Decimal) -> Result<ConversionQuote, UBIError> {
+        // Get user
+        let user = self.registry
+            .get_user_by_wallet(wallet)
+            .await?
+            .ok_or(UBIError::WalletNotActive(wallet.to_string()))?;
+        
+        let epoch = current_epoch(self.genesis_timestamp);
+        let mut conn = self.pool.acquire().await?;
+        let parameters = parameters_at(&mut conn, epoch).await?;
+        
+        // Check per-epoch cap
+        let converted_this_epoch = self.get_converted_this_epoch(&user.person_id, epoch).await?;
+        check_cap(amount_ue, converted_this_epoch, parameters.conversion_cap_ue)?;
+        
+        let rate_index_value = self.rate_index.preview_rate_index(user.region_id).await?;
+        if rate_index_value.is_zero() {
+            return Err(UBIError::RateIndexNotInitialized(user.region_id));
+        }
+        
+        let (fee_ue, amount_bu) = price(amount_ue, parameters.conversion_fee_bps, rate_index_value)?;
+        
+        let treasury_balance = sqlx::query_scalar!(
+            "SELECT balance_bu FROM treasury ORDER BY id DESC LIMIT 1"
+        )
+        .fetch_optional(&mut *conn)
+        .await?
+        .unwrap_or(Decimal::ZERO);
+        let rationed = treasury_balance < parameters.rationing_threshold_bu;
+        if !rationed {
+            check_treasury(treasury_balance, amount_bu)?;
+        }
+        
+        Ok(ConversionQuote {
+            amount_ue,
+            fee_ue,
+            amount_bu,
+            rate_index: rate_index_value,
+            unlock_epoch: epoch + parameters.conversion_delay_epochs,
+            cap_remaining_ue: parameters.conversion_cap_ue - converted_this_epoch,
+            rationed,
+        })
+    }
+    
     /// Claim unlocked BU from conversion
     pub async fn claim_converted_bu(
         &self,
         wallet: &str,
         conversion_id: i64,
-    ) -> Result<String, UBIError> {
+    ) -> Result<Decimal, UBIError> {
         // Get user
         let user = self.registry
             .get_user_by_wallet(wallet)
@@ -209,18 +290,23 @@ impl ConversionService {
         
         let epoch = current_epoch(self.genesis_timestamp);
         
-        // Get conversion
+        // Start transaction
+        let mut tx = self.pool.begin().await?;
+        lock_event_chain(&mut *tx).await?;
+        
+        // Locked so the settlement worker or a second claim cannot credit it twice
         let conversion = sqlx::query_as!(
             PendingConversion,
             r#"
-            SELECT id, person_id, amount_ue, amount_bu, rate_index, unlock_epoch, status, created_at
+            SELECT id, person_id, amount_ue, amount_bu, rate_index, unlock_epoch, status, reserved, created_at
             FROM pending_conversions
             WHERE id = $1 AND person_id = $2
+            FOR UPDATE
             "#,
             conversion_id,
             user.person_id.as_slice()
         )
-        .fetch_optional(&self.pool)
+        .fetch_optional(&mut *tx)
         .await?
         .ok_or(UBIError::Other("Conversion not found".to_string()))?;
         
@@ -234,73 +320,391 @@ impl ConversionService {
             return Err(UBIError::Other("Conversion already claimed".to_string()));
         }
         
+        credit_conversion(&mut tx, &conversion, wallet).await?;
+        
+        // Commit transaction
+        tx.commit().await?;
+        
+        info!("BU claimed: {} BU to wallet {}", conversion.amount_bu, wallet);
+        
+        Ok(conversion.amount_bu)
+    }
+    
+    /// Ration queued conversions and settle due ones until the process exits
+    pub async fn run_settlement_worker(self, auto_claim: bool) {
+        loop {
+            if let Err(e) = self.ration_queued().await {
+                error!("Conversion rationing failed: {}", e);
+            }
+            match self.settle_due(auto_claim, SETTLEMENT_BATCH_SIZE).await {
+                // A full batch means more are probably waiting
+                Ok(settled) if settled == SETTLEMENT_BATCH_SIZE => continue,
+                Ok(_) => {}
+                Err(e) => error!("Conversion settlement failed: {}", e),
+            }
+            tokio::time::sleep(SETTLEMENT_POLL_INTERVAL).await;
+        }
+    }
+    
+    /// Settle up to `limit` pending conversions whose unlock epoch has arrived
+    ///
+    /// Each is marked unlocked (ConversionUnlocked), or with `auto_claim`
+    /// its BU is credited to the person's current wallet as if they had
+    /// claimed it (ConversionClaimed). One that cannot be credited, because
+    /// the person is inactive or the treasury is short for one requested
+    /// before reservations, is only unlocked.
+    /// The batch commits together, and rows are claimed with SKIP LOCKED,
+    /// so several nodes can settle side by side.
+    pub async fn settle_due(&self, auto_claim: bool, limit: i64) -> Result<i64, UBIError> {
+        let epoch = current_epoch(self.genesis_timestamp);
+        
         // Start transaction
         let mut tx = self.pool.begin().await?;
+        lock_event_chain(&mut *tx).await?;
+        
+        let due = sqlx::query_as!(
+            PendingConversion,
+            r#"
+            SELECT id, person_id, amount_ue, amount_bu, rate_index, unlock_epoch, status, reserved, created_at
+            FROM pending_conversions
+            WHERE status = 'pending' AND unlock_epoch <= $1
+            ORDER BY unlock_epoch, id
+            LIMIT $2
+            FOR UPDATE SKIP LOCKED
+            "#,
+            epoch,
+            limit
+        )
+        .fetch_all(&mut *tx)
+        .await?;
         
-        // Update status to unlocked if needed
-        if conversion.status == "pending" {
+        for conversion in &due {
+            if auto_claim {
+                let owner = sqlx::query!(
+                    "SELECT wallet_address, is_active FROM users WHERE person_id = $1",
+                    conversion.person_id.as_slice()
+                )
+                .fetch_optional(&mut *tx)
+                .await?
+                .filter(|owner| owner.is_active);
+                
+                if let Some(owner) = owner {
+                    // A failed credit must not leave partial writes behind the unlock
+                    let mut savepoint = tx.begin().await?;
+                    match credit_conversion(&mut savepoint, conversion, &owner.wallet_address).await {
+                        Ok(()) => {
+                            savepoint.commit().await?;
+                            info!("Conversion {} settled: {} BU to wallet {}",
+                                  conversion.id, conversion.amount_bu, owner.wallet_address);
+                            continue;
+                        }
+                        Err(e) => {
+                            savepoint.rollback().await?;
+                            warn!("Conversion {} not credited, unlocking only: {}", conversion.id, e);
+                        }
+                    }
+                }
+            }
+            
             sqlx::query!(
                 "UPDATE pending_conversions SET status = 'unlocked' WHERE id = $1",
-                conversion_id
+                conversion.id
             )
             .execute(&mut *tx)
             .await?;
+            
+            // Emit event
+            emit_event(&mut *tx, &ConversionUnlockedEvent {
+                conversion_id: conversion.id,
+                person_id: hex::encode(&conversion.person_id),
+                unlock_epoch: conversion.unlock_epoch,
+            }).await?;
+            
+            info!("Conversion {} unlocked", conversion.id);
         }
         
-        // Transfer BU from treasury
-        sqlx::query!(
+        // Commit transaction
+        tx.commit().await?;
+        
+        Ok(due.len() as i64)
+    }
+    
+    /// Fill the queued conversions of every ended epoch from the treasury's unreserved BU
+    ///
+    /// Each epoch's batch, oldest first, gets what is left. When it asks for
+    /// more, the BU is split between the people in it in proportion to what
+    /// each asked for, and a person's share fills their conversions oldest
+    /// first. A conversion keeps the UE that paid for its fill; the rest,
+    /// with its part of the fee, is refunded to the person's current wallet
+    /// and frees conversion cap (ConversionRationed). One filled with
+    /// nothing ends refunded.
+    /// The treasury row is locked first, so nodes ration one at a time.
+    /// Returns the number of conversions rationed.
+    pub async fn ration_queued(&self) -> Result<usize, UBIError> {
+        let epoch = current_epoch(self.genesis_timestamp);
+        
+        // Start transaction
+        let mut tx = self.pool.begin().await?;
+        lock_event_chain(&mut *tx).await?;
+        
+        let mut available_bu = sqlx::query_scalar!(
+            "SELECT balance_bu FROM treasury ORDER BY id DESC LIMIT 1 FOR UPDATE"
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .unwrap_or(Decimal::ZERO);
+        
+        // Only ended epochs, so no batch can grow after it is filled
+        let queued = sqlx::query_as!(
+            PendingConversion,
             r#"
-            UPDATE treasury
-            SET balance_bu = balance_bu - $1
-            WHERE balance_bu >= $1
+            SELECT id, person_id, amount_ue, amount_bu, rate_index, unlock_epoch, status, reserved, created_at
+            FROM pending_conversions
+            WHERE status = 'queued' AND created_at < TO_TIMESTAMP($1::BIGINT)
+            ORDER BY id
+            FOR UPDATE
             "#,
-            &conversion.amount_bu
+            epoch_start_timestamp(epoch, self.genesis_timestamp)
         )
-        .execute(&mut *tx)
+        .fetch_all(&mut *tx)
         .await?;
         
-        // Update user BU balance
-        sqlx::query!(
+        // Batch by request epoch, and within a batch by person in order of first request
+        let mut batches: BTreeMap<i32, Vec<(Vec<u8>, Vec<&PendingConversion>)>> = BTreeMap::new();
+        for conversion in &queued {
+            let batch = batches
+                .entry(epoch_at(conversion.created_at.timestamp(), self.genesis_timestamp))
+                .or_default();
+            match batch.iter_mut().find(|(person_id, _)| *person_id == conversion.person_id) {
+                Some((_, conversions)) => conversions.push(conversion),
+                None => batch.push((conversion.person_id.clone(), vec![conversion])),
+            }
+        }
+        
+        for (request_epoch, batch) in batches {
+            let fee_bps = parameters_at(&mut *tx, request_epoch).await?.conversion_fee_bps;
+            let demand_bu: Decimal = batch.iter()
+                .flat_map(|(_, conversions)| conversions.iter().map(|c| c.amount_bu))
+                .sum();
+            // None fills everyone in full
+            let fill_ratio = if demand_bu > available_bu {
+                Some(wad::div_wad(available_bu, demand_bu).map_err(|e| UBIError::Other(e.to_string()))?)
+            } else {
+                None
+            };
+            
+            for (person_id, conversions) in batch {
+                let person_demand_bu: Decimal = conversions.iter().map(|c| c.amount_bu).sum();
+                let mut person_fill_bu = match fill_ratio {
+                    Some(ratio) => wad::mul_wad(person_demand_bu, ratio)
+                        .map_err(|e| UBIError::Other(e.to_string()))?
+                        .min(person_demand_bu),
+                    None => person_demand_bu,
+                };
+                
+                let wallet = sqlx::query_scalar!(
+                    "SELECT wallet_address FROM users WHERE person_id = $1",
+                    person_id.as_slice()
+                )
+                .fetch_one(&mut *tx)
+                .await?;
+                
+                for conversion in conversions {
+                    let filled_bu = conversion.amount_bu.min(person_fill_bu);
+                    person_fill_bu -= filled_bu;
+                    
+                    let kept_ue = if filled_bu == conversion.amount_bu {
+                        conversion.amount_ue
+                    } else {
+                        wad::div_wad(filled_bu, conversion.amount_bu)
+                            .and_then(|share| wad::mul_wad(conversion.amount_ue, share))
+                            .map_err(|e| UBIError::Other(e.to_string()))?
+                    };
+                    let refund_ue = conversion.amount_ue - kept_ue;
+                    let (fee_ue, _) = price(conversion.amount_ue, fee_bps, conversion.rate_index)?;
+                    let (kept_fee_ue, _) = price(kept_ue, fee_bps, conversion.rate_index)?;
+                    let fee_refund_ue = fee_ue - kept_fee_ue;
+                    
+                    // An empty fill keeps the requested amounts on record
+                    if kept_ue.is_zero() {
+                        sqlx::query!(
+                            "UPDATE pending_conversions SET status = 'refunded' WHERE id = $1",
+                            conversion.id
+                        )
+                        .execute(&mut *tx)
+                        .await?;
+                    } else {
+                        sqlx::query!(
+                            r#"
+                            UPDATE pending_conversions
+                            SET status = 'pending', amount_ue = $2, amount_bu = $3, reserved = true
+                            WHERE id = $1
+                            "#,
+                            conversion.id,
+                            kept_ue,
+                            filled_bu
+                        )
+                        .execute(&mut *tx)
+                        .await?;
+                    }
+                    
+                    // Release the cap usage of the refunded UE
+                    sqlx::query!(
+                        r#"
+                        UPDATE converted_this_epoch SET amount_ue = amount_ue - $3
+                        WHERE person_id = $1 AND epoch = $2
+                        "#,
+                        person_id.as_slice(),
+                        request_epoch,
+                        refund_ue
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                    
+                    if !filled_bu.is_zero() {
+                        available_bu -= filled_bu;
+                        emit_event(&mut *tx, &TreasuryDebitedEvent {
+                            amount_bu: filled_bu.to_string(),
+                            balance_bu: available_bu.to_string(),
+                            conversion_id: conversion.id,
+                        }).await?;
+                    }
+                    
+                    // Emit event
+                    let event_id = emit_event(&mut *tx, &ConversionRationedEvent {
+                        conversion_id: conversion.id,
+                        person_id: hex::encode(&person_id),
+                        wallet_address: wallet.clone(),
+                        epoch: request_epoch,
+                        filled_bu: filled_bu.to_string(),
+                        refund_ue: refund_ue.to_string(),
+                        fee_refund_ue: fee_refund_ue.to_string(),
+                    }).await?;
+                    
+                    // Return the unfilled UE with its part of the fee, and reserve the fill
+                    let entry = JournalEntry::new()
+                        .transfer(Unit::UE, Account::Burn, Account::Wallet(wallet.clone()), refund_ue - fee_refund_ue)
+                        .transfer(Unit::UE, Account::Fees, Account::Wallet(wallet.clone()), fee_refund_ue)
+                        .transfer(Unit::BU, Account::Treasury, Account::Reserved, filled_bu);
+                    post_entry(&mut *tx, event_id, "Conversion rationing", &entry).await?;
+                    
+                    info!("Conversion {} rationed: {} BU filled, {} UE refunded to wallet {}",
+                          conversion.id, filled_bu, refund_ue, wallet);
+                }
+            }
+        }
+        
+        // Commit transaction
+        tx.commit().await?;
+        
+        Ok(queued.len())
+    }
+    
+    /// Cancel a conversion still in its delay window
+    ///
+    /// Returns the burned UE to the person's current wallet, frees the
+    /// conversion cap it used and releases its reserved BU to the treasury.
+    /// A conversion still queued for rationing can be cancelled the same way.
+    /// The fee comes back only if the constitution in force says so.
+    /// Returns the UE refunded.
+    pub async fn cancel_conversion(
+        &self,
+        wallet: &str,
+        conversion_id: i64,
+    ) -> Result<Decimal, UBIError> {
+        // Get user
+        let user = self.registry
+            .get_user_by_wallet(wallet)
+            .await?
+            .filter(|user| user.is_active)
+            .ok_or(UBIError::WalletNotActive(wallet.to_string()))?;
+        
+        let epoch = current_epoch(self.genesis_timestamp);
+        
+        // Start transaction
+        let mut tx = self.pool.begin().await?;
+        lock_event_chain(&mut *tx).await?;
+        
+        // Locked so a concurrent claim or cancel sees the new status
+        let conversion = sqlx::query_as!(
+            PendingConversion,
             r#"
-            INSERT INTO bu_balances (wallet_address, balance)
-            VALUES ($1, $2)
-            ON CONFLICT (wallet_address)
-            DO UPDATE SET balance = bu_balances.balance + $2
+            SELECT id, person_id, amount_ue, amount_bu, rate_index, unlock_epoch, status, reserved, created_at
+            FROM pending_conversions
+            WHERE id = $1 AND person_id = $2
+            FOR UPDATE
             "#,
-            wallet,
-            &conversion.amount_bu
+            conversion_id,
+            user.person_id.as_slice()
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(UBIError::Other("Conversion not found".to_string()))?;
+        
+        // Check status
+        if !matches!(conversion.status.as_str(), "pending" | "queued") || epoch >= conversion.unlock_epoch {
+            return Err(UBIError::Other("Only conversions still in their delay window can be cancelled".to_string()));
+        }
+        
+        // The fee charged at request time, by the parameters then in force
+        let cap_epoch = epoch_at(conversion.created_at.timestamp(), self.genesis_timestamp);
+        let requested_with = parameters_at(&mut *tx, cap_epoch).await?;
+        let (fee_ue, _) = price(conversion.amount_ue, requested_with.conversion_fee_bps, conversion.rate_index)?;
+        let fee_refunded = parameters_at(&mut *tx, epoch).await?.cancellation_refunds_fee;
+        let refund_ue = if fee_refunded { conversion.amount_ue } else { conversion.amount_ue - fee_ue };
+        
+        sqlx::query!(
+            "UPDATE pending_conversions SET status = 'cancelled' WHERE id = $1",
+            conversion_id
         )
         .execute(&mut *tx)
         .await?;
         
-        // Mark as claimed
+        // Release the cap usage
         sqlx::query!(
-            "UPDATE pending_conversions SET status = 'claimed' WHERE id = $1",
-            conversion_id
+            r#"
+            UPDATE converted_this_epoch SET amount_ue = amount_ue - $3
+            WHERE person_id = $1 AND epoch = $2
+            "#,
+            user.person_id.as_slice(),
+            cap_epoch,
+            conversion.amount_ue
         )
         .execute(&mut *tx)
         .await?;
         
         // Emit event
-        let event_data = serde_json::to_value(crate::events::ConversionClaimedEvent {
+        let event_id = emit_event(&mut *tx, &ConversionCancelledEvent {
+            conversion_id,
             person_id: hex::encode(&user.person_id),
             wallet_address: wallet.to_string(),
-            conversion_id,
-            amount_bu: conversion.amount_bu.clone(),
-        }).unwrap();
-        
-        emit_event(&mut *tx, "ConversionClaimed", &event_data).await?;
+            cap_epoch,
+            amount_ue: conversion.amount_ue.to_string(),
+            fee_ue: fee_ue.to_string(),
+            fee_refunded,
+        }).await?;
+        
+        // Return the burned UE, and the fee if the constitution refunds it
+        let mut entry = JournalEntry::new()
+            .transfer(Unit::UE, Account::Burn, Account::Wallet(wallet.to_string()), conversion.amount_ue - fee_ue);
+        if fee_refunded {
+            entry = entry.transfer(Unit::UE, Account::Fees, Account::Wallet(wallet.to_string()), fee_ue);
+        }
+        if conversion.reserved {
+            entry = entry.transfer(Unit::BU, Account::Reserved, Account::Treasury, conversion.amount_bu);
+        }
+        post_entry(&mut *tx, event_id, "Conversion cancellation", &entry).await?;
         
         // Commit transaction
         tx.commit().await?;
         
-        info!("BU claimed: {} BU to wallet {}", conversion.amount_bu, wallet);
+        info!("Conversion {} cancelled: {} UE refunded to wallet {}", conversion_id, refund_ue, wallet);
         
-        Ok(conversion.amount_bu)
+        Ok(refund_ue)
     }
     
     /// Get converted amount this epoch
-    async fn get_converted_this_epoch(&self, person_id: &[u8], epoch: i32) -> Result<String, UBIError> {
+    async fn get_converted_this_epoch(&self, person_id: &[u8], epoch: i32) -> Result<Decimal, UBIError> {
         let amount = sqlx::query_scalar!(
             "SELECT amount_ue FROM converted_this_epoch WHERE person_id = $1 AND epoch = $2",
             person_id,
@@ -308,9 +712,92 @@ impl ConversionService {
         )
         .fetch_optional(&self.pool)
         .await?
-        .unwrap_or_else(|| "0".to_string());
+        .unwrap_or(Decimal::ZERO);
         
         Ok(amount)
     }
 }
 
+/// Reject an amount that is not a positive whole number of base units, or would pass the cap
+fn check_cap(amount_ue: Decimal, converted_this_epoch: Decimal, cap_ue: Decimal) -> Result<(), UBIError> {
+    if amount_ue <= Decimal::ZERO || !amount_ue.fract().is_zero() {
+        return Err(UBIError::Other("Invalid amount".to_string()));
+    }
+    if converted_this_epoch + amount_ue > cap_ue {
+        return Err(UBIError::ConversionCapExceeded(
+            format!("Exceeds per-epoch cap of {}", cap_ue)
+        ));
+    }
+    Ok(())
+}
+
+/// Reject a conversion the treasury's unreserved BU cannot cover
+fn check_treasury(treasury_bu: Decimal, amount_bu: Decimal) -> Result<(), UBIError> {
+    if treasury_bu < amount_bu {
+        return Err(UBIError::TreasuryDepleted(
+            format!("{} BU needed, {} BU available", amount_bu, treasury_bu)
+        ));
+    }
+    Ok(())
+}
+
+/// Fee and BU out for `amount_ue`, rounded down to whole base units
+fn price(amount_ue: Decimal, fee_bps: i32, rate_index: Decimal) -> Result<(Decimal, Decimal), UBIError> {
+    let fee_ue = (amount_ue * Decimal::from(fee_bps) / Decimal::from(10000)).floor();
+    let amount_bu = wad::mul_wad(amount_ue - fee_ue, rate_index)
+        .map_err(|e| UBIError::Other(e.to_string()))?
+        .floor();
+    Ok((fee_ue, amount_bu))
+}
+
+/// Move a conversion's BU from the treasury's reserve to `wallet` and mark it claimed
+///
+/// The caller holds the conversion's row lock and has checked it is due
+async fn credit_conversion(
+    conn: &mut PgConnection,
+    conversion: &PendingConversion,
+    wallet: &str,
+) -> Result<(), UBIError> {
+    // Conversions requested before reservations still draw on the treasury
+    let source = if conversion.reserved {
+        Account::Reserved
+    } else {
+        let treasury_balance = sqlx::query_scalar!(
+            "SELECT balance_bu FROM treasury ORDER BY id DESC LIMIT 1 FOR UPDATE"
+        )
+        .fetch_optional(&mut *conn)
+        .await?
+        .unwrap_or(Decimal::ZERO);
+        check_treasury(treasury_balance, conversion.amount_bu)?;
+        
+        emit_event(&mut *conn, &TreasuryDebitedEvent {
+            amount_bu: conversion.amount_bu.to_string(),
+            balance_bu: (treasury_balance - conversion.amount_bu).to_string(),
+            conversion_id: conversion.id,
+        }).await?;
+        Account::Treasury
+    };
+    
+    // Mark as claimed
+    sqlx::query!(
+        "UPDATE pending_conversions SET status = 'claimed' WHERE id = $1",
+        conversion.id
+    )
+    .execute(&mut *conn)
+    .await?;
+    
+    // Emit event
+    let event_id = emit_event(&mut *conn, &ConversionClaimedEvent {
+        person_id: hex::encode(&conversion.person_id),
+        wallet_address: wallet.to_string(),
+        conversion_id: conversion.id,
+        amount_bu: conversion.amount_bu.to_string(),
+    }).await?;
+    
+    let entry = JournalEntry::new()
+        .transfer(Unit::BU, source, Account::Wallet(wallet.to_string()), conversion.amount_bu);
+    post_entry(&mut *conn, event_id, "Conversion claim", &entry).await?;
+    
+    Ok(())
+}
+
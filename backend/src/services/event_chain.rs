@@ -0,0 +1,175 @@
+//! Event chain verification
+//! 
+//! CONSTITUTIONAL: Events are append-only
+//! Any edited, deleted or reordered event breaks the hash chain
+
+use crate::models::event_chain::{BrokenLink, ChainVerification};
+use crate::events::{compute_event_hash, Event, GENESIS_HASH};
+use crate::utils::errors::UBIError;
+use sqlx::PgPool;
+use log::{info, warn};
+
+/// Events read per page while walking the chain
+const VERIFY_PAGE_SIZE: i64 = 1000;
+
+pub struct EventChainService {
+    pool: PgPool,
+}
+
+impl EventChainService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+    
+    /// Walk the whole chain from genesis and stop at the first broken link
+    pub async fn verify(&self) -> Result<ChainVerification, UBIError> {
+        let mut walk = ChainWalk::new();
+        let mut last_id = 0i64;
+        
+        loop {
+            let page = sqlx::query_as!(
+                Event,
+                r#"
+                SELECT id, event_type, event_data, created_at, prev_hash, hash, hash_version
+                FROM events
+                WHERE id > $1
+                ORDER BY id
+                LIMIT $2
+                "#,
+                last_id,
+                VERIFY_PAGE_SIZE
+            )
+            .fetch_all(&self.pool)
+            .await?;
+            
+            if page.is_empty() {
+                break;
+            }
+            
+            for event in &page {
+                last_id = event.id;
+                if !walk.step(event) {
+                    return Ok(walk.report);
+                }
+            }
+        }
+        
+        info!("Event chain verified: {} events, head {:?}", walk.report.events_checked, walk.report.head_hash);
+        
+        Ok(walk.report)
+    }
+}
+
+/// Verify events held in memory (e.g. an export), in id order
+pub fn verify_events(events: &[Event]) -> ChainVerification {
+    let mut walk = ChainWalk::new();
+    for event in events {
+        if !walk.step(event) {
+            break;
+        }
+    }
+    walk.report
+}
+
+/// Running state while walking the chain in id order
+struct ChainWalk {
+    report: ChainVerification,
+    expected_prev: String,
+    chain_started: bool,
+}
+
+impl ChainWalk {
+    fn new() -> Self {
+        Self {
+            report: ChainVerification {
+                valid: true,
+                events_checked: 0,
+                unsealed_events: 0,
+                head_event_id: None,
+                head_hash: None,
+                first_broken_link: None,
+            },
+            expected_prev: GENESIS_HASH.to_string(),
+            chain_started: false,
+        }
+    }
+    
+    /// Check the next event; false once the chain is broken
+    fn step(&mut self, event: &Event) -> bool {
+        if let Some(broken) = check_link(event, &self.expected_prev, self.chain_started) {
+            warn!("Event chain broken at event {}: {}", broken.event_id, broken.reason);
+            self.report.valid = false;
+            self.report.first_broken_link = Some(broken);
+            return false;
+        }
+        
+        match &event.hash {
+            Some(hash) => {
+                self.chain_started = true;
+                self.report.events_checked += 1;
+                self.report.head_event_id = Some(event.id);
+                self.report.head_hash = Some(hash.clone());
+                self.expected_prev = hash.clone();
+            }
+            None => self.report.unsealed_events += 1,
+        }
+        true
+    }
+}
+
+/// Check one event against the hash of the event before it
+fn check_link(event: &Event, expected_prev: &str, chain_started: bool) -> Option<BrokenLink> {
+    let hash = match &event.hash {
+        Some(hash) => hash,
+        // Legacy events may only appear before the first sealed event
+        None if !chain_started => return None,
+        None => {
+            return Some(BrokenLink {
+                event_id: event.id,
+                reason: "Event has no hash inside the sealed chain".to_string(),
+                expected: None,
+                found: None,
+            });
+        }
+    };
+    
+    if event.prev_hash.as_deref() != Some(expected_prev) {
+        return Some(BrokenLink {
+            event_id: event.id,
+            reason: "prev_hash does not match the previous event (event deleted or reordered)".to_string(),
+            expected: Some(expected_prev.to_string()),
+            found: event.prev_hash.clone(),
+        });
+    }
+    
+    let recomputed = match compute_event_hash(
+        event.hash_version,
+        expected_prev,
+        event.id,
+        &event.event_type,
+        &event.event_data,
+        &event.created_at,
+    ) {
+        Ok(recomputed) => recomputed,
+        Err(e) => {
+            return Some(BrokenLink {
+                event_id: event.id,
+                reason: format!("Event cannot be hashed: {}", e),
+                expected: None,
+                found: Some(hash.clone()),
+            });
+        }
+    };
+    
+    if &recomputed != hash {
+        return Some(BrokenLink {
+            event_id: event.id,
+            reason: "Content hash mismatch (event modified)".to_string(),
+            expected: Some(recomputed),
+            found: Some(hash.clone()),
+        });
+    }
+    
+    None
+}
+
@@ -0,0 +1,39 @@
+//! Event feed service
+//! 
+//! Cursor-based reads of the append-only event log, so mirrors and
+//! analytics consumers can follow it incrementally
+
+use crate::events::Event;
+use crate::utils::errors::UBIError;
+use sqlx::PgPool;
+
+pub struct EventFeedService {
+    pool: PgPool,
+}
+
+impl EventFeedService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+    
+    /// Events with id greater than `since_id`, oldest first
+    pub async fn fetch_page(&self, since_id: i64, limit: i64) -> Result<Vec<Event>, UBIError> {
+        let events = sqlx::query_as!(
+            Event,
+            r#"
+            SELECT id, event_type, event_data, created_at, prev_hash, hash, hash_version
+            FROM events
+            WHERE id > $1
+            ORDER BY id
+            LIMIT $2
+            "#,
+            since_id,
+            limit
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(events)
+    }
+}
+
@@ -0,0 +1,165 @@
+//! Fork service
+//! 
+//! CONSTITUTIONAL: Forking is a feature
+//! Bootstraps an empty database from a parent deployment's event history.
+//! Imported events keep their ids and hashes, so the fork's chain extends
+//! the parent's and its genesis ancestor is the parent's head.
+
+use crate::constants::BU_TOTAL_SUPPLY;
+use crate::models::constitution::ConstitutionAmendment;
+use crate::models::fork::ForkReport;
+use crate::events::{emit_event, lock_event_chain, Event, ForkCreatedEvent};
+use crate::services::constitution;
+use crate::services::event_chain::verify_events;
+use crate::services::replay::{write_state, ReplayService};
+use crate::utils::errors::UBIError;
+use sqlx::PgPool;
+use log::info;
+
+pub struct ForkService {
+    pool: PgPool,
+    genesis_timestamp: i64,
+}
+
+impl ForkService {
+    pub fn new(pool: PgPool, genesis_timestamp: i64) -> Self {
+        Self {
+            pool,
+            genesis_timestamp,
+        }
+    }
+    
+    /// Import a parent's events, rebuild its state and record the fork
+    ///
+    /// Optionally amends constitutional parameters from a given epoch onward
+    pub async fn bootstrap(
+        &self,
+        mut events: Vec<Event>,
+        amendment: Option<ConstitutionAmendment>,
+    ) -> Result<ForkReport, UBIError> {
+        events.sort_by_key(|e| e.id);
+        
+        let chain = verify_events(&events);
+        if let Some(broken) = chain.first_broken_link {
+            return Err(UBIError::Other(format!(
+                "Parent history is broken at event {}: {}", broken.event_id, broken.reason
+            )));
+        }
+        let (parent_head_event_id, parent_head_hash) = match (chain.head_event_id, chain.head_hash) {
+            (Some(id), Some(hash)) if events.last().map(|e| e.id) == Some(id) => (id, hash),
+            _ => return Err(UBIError::Other("Parent history has no sealed head event".to_string())),
+        };
+        
+        let replay = ReplayService::new(self.pool.clone(), self.genesis_timestamp);
+        let (state, unhandled) = replay.fold(&events)?;
+        if unhandled > 0 {
+            return Err(UBIError::Other(format!(
+                "{} events have types unknown to this build", unhandled
+            )));
+        }
+        
+        let mut tx = self.pool.begin().await?;
+        lock_event_chain(&mut *tx).await?;
+        
+        // Migrations seed the treasury and open the journal with it; anything
+        // beyond that seed means the database already has a history
+        let occupied = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (SELECT 1 FROM events)
+                OR EXISTS (SELECT 1 FROM users)
+                OR (SELECT COUNT(*) FROM journal_entries) > 1
+                OR EXISTS (SELECT 1 FROM journal_postings WHERE account NOT IN ('treasury', 'genesis'))
+                OR (SELECT COUNT(*) FROM treasury) > 1
+                OR EXISTS (SELECT 1 FROM treasury WHERE balance_bu <> $1::TEXT::NUMERIC OR reserved_bu <> 0)
+                AS "occupied!"
+            "#,
+            BU_TOTAL_SUPPLY
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        if occupied {
+            return Err(UBIError::Other("Target database is not empty".to_string()));
+        }
+        
+        // Verbatim, so every imported hash still verifies
+        for event in &events {
+            sqlx::query!(
+                r#"
+                INSERT INTO events (id, event_type, event_data, created_at, prev_hash, hash, hash_version)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                "#,
+                event.id,
+                &event.event_type,
+                &event.event_data,
+                event.created_at,
+                event.prev_hash.as_deref(),
+                event.hash.as_deref(),
+                event.hash_version
+            )
+            .execute(&mut *tx)
+            .await?;
+        }
+        sqlx::query!("SELECT setval('events_id_seq', $1)", parent_head_event_id)
+            .fetch_one(&mut *tx)
+            .await?;
+        
+        write_state(&mut tx, &state).await?;
+        
+        let events_imported = events.len() as i64;
+        sqlx::query!(
+            "INSERT INTO fork_genesis (parent_head_hash, parent_head_event_id, events_imported) VALUES ($1, $2, $3)",
+            &parent_head_hash,
+            parent_head_event_id,
+            events_imported
+        )
+        .execute(&mut *tx)
+        .await?;
+        
+        emit_event(&mut *tx, &ForkCreatedEvent {
+            parent_head_event_id,
+            parent_head_hash: parent_head_hash.clone(),
+            events_imported,
+        }).await?;
+        
+        let constitution = match amendment {
+            Some(amendment) => Some(constitution::amend(&mut tx, amendment).await?),
+            None => None,
+        };
+        
+        tx.commit().await?;
+        
+        info!("Fork created from parent head {} (event {}), {} events imported",
+              parent_head_hash, parent_head_event_id, events_imported);
+        
+        Ok(ForkReport {
+            events_imported,
+            parent_head_event_id,
+            parent_head_hash,
+            constitution,
+        })
+    }
+}
+
+/// Parse a parent's history: a `SystemState` export (GET /api/admin/export-state)
+/// or an NDJSON event stream (GET /api/events)
+///
+/// Only the events are used; table contents are rebuilt by replay
+pub fn parse_history(input: &str) -> Result<Vec<Event>, UBIError> {
+    if let Ok(export) = serde_json::from_str::<serde_json::Value>(input) {
+        if let Some(events) = export.get("events") {
+            return serde_json::from_value(events.clone())
+                .map_err(|e| UBIError::Other(format!("Invalid events in export: {}", e)));
+        }
+    }
+    
+    input
+        .lines()
+        .enumerate()
+        .filter(|(_, line)| !line.trim().is_empty())
+        .map(|(n, line)| {
+            serde_json::from_str(line)
+                .map_err(|e| UBIError::Other(format!("Invalid event on line {}: {}", n + 1, e)))
+        })
+        .collect()
+}
+
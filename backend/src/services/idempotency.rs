@@ -0,0 +1,180 @@
+//! Idempotency key service
+//!
+//! Stores the response to a POST under the client's Idempotency-Key so a
+//! retry gets the same response instead of running the request again.
+//! Keys are scoped to the caller (see `api::idempotency`) and the
+//! endpoint, and are forgotten after the retention window.
+
+use crate::utils::errors::UBIError;
+use sqlx::PgPool;
+use std::time::Duration;
+
+/// How long a stored response is replayed for
+pub const IDEMPOTENCY_RETENTION: Duration = Duration::from_secs(24 * 60 * 60);
+
+/// A first request still in flight after this long is assumed lost, and a retry may take over its key
+const IDEMPOTENCY_LOCK_TIMEOUT: Duration = Duration::from_secs(5 * 60);
+
+/// Longest Idempotency-Key accepted, in bytes
+pub const IDEMPOTENCY_KEY_MAX_LEN: usize = 255;
+
+/// What to do with a request carrying an idempotency key
+#[derive(Debug)]
+pub enum IdempotencyOutcome {
+    /// First use of the key: run the request, then `complete` or `release` it
+    Started,
+    /// The key already has a response: send it again
+    Replay {
+        status_code: u16,
+        content_type: Option<String>,
+        body: Vec<u8>,
+    },
+    /// The first request with this key has not finished yet
+    InProgress,
+    /// The key was used for a request with a different body
+    Mismatch,
+}
+
+pub struct IdempotencyService {
+    pool: PgPool,
+}
+
+impl IdempotencyService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+    
+    /// Claim `key` for a request, or find the response already stored under it
+    pub async fn begin(
+        &self,
+        wallet: &str,
+        endpoint: &str,
+        key: &str,
+        request_hash: &str,
+    ) -> Result<IdempotencyOutcome, UBIError> {
+        let retention = IDEMPOTENCY_RETENTION.as_secs() as f64;
+        let lock_timeout = IDEMPOTENCY_LOCK_TIMEOUT.as_secs() as f64;
+        
+        // Start transaction
+        let mut tx = self.pool.begin().await?;
+        
+        sqlx::query!(
+            "DELETE FROM idempotency_keys WHERE created_at < NOW() - MAKE_INTERVAL(secs => $1)",
+            retention
+        )
+        .execute(&mut *tx)
+        .await?;
+        
+        let inserted = sqlx::query!(
+            r#"
+            INSERT INTO idempotency_keys (wallet_address, endpoint, idempotency_key, request_hash)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (wallet_address, endpoint, idempotency_key) DO NOTHING
+            "#,
+            wallet,
+            endpoint,
+            key,
+            request_hash
+        )
+        .execute(&mut *tx)
+        .await?
+        .rows_affected();
+        
+        if inserted == 1 {
+            tx.commit().await?;
+            return Ok(IdempotencyOutcome::Started);
+        }
+        
+        let stored = sqlx::query!(
+            r#"
+            SELECT request_hash, status_code, content_type, response_body,
+                   created_at < NOW() - MAKE_INTERVAL(secs => $4) AS "abandoned!"
+            FROM idempotency_keys
+            WHERE wallet_address = $1 AND endpoint = $2 AND idempotency_key = $3
+            FOR UPDATE
+            "#,
+            wallet,
+            endpoint,
+            key,
+            lock_timeout
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        
+        let outcome = if stored.request_hash != request_hash {
+            IdempotencyOutcome::Mismatch
+        } else if let (Some(status_code), Some(body)) = (stored.status_code, stored.response_body) {
+            IdempotencyOutcome::Replay {
+                status_code: status_code as u16,
+                content_type: stored.content_type,
+                body,
+            }
+        } else if stored.abandoned {
+            sqlx::query!(
+                r#"
+                UPDATE idempotency_keys SET created_at = NOW()
+                WHERE wallet_address = $1 AND endpoint = $2 AND idempotency_key = $3
+                "#,
+                wallet,
+                endpoint,
+                key
+            )
+            .execute(&mut *tx)
+            .await?;
+            IdempotencyOutcome::Started
+        } else {
+            IdempotencyOutcome::InProgress
+        };
+        
+        // Commit transaction
+        tx.commit().await?;
+        
+        Ok(outcome)
+    }
+    
+    /// Store the response for a key claimed by `begin`
+    pub async fn complete(
+        &self,
+        wallet: &str,
+        endpoint: &str,
+        key: &str,
+        status_code: u16,
+        content_type: Option<&str>,
+        body: &[u8],
+    ) -> Result<(), UBIError> {
+        sqlx::query!(
+            r#"
+            UPDATE idempotency_keys SET status_code = $4, content_type = $5, response_body = $6
+            WHERE wallet_address = $1 AND endpoint = $2 AND idempotency_key = $3
+            "#,
+            wallet,
+            endpoint,
+            key,
+            status_code as i32,
+            content_type,
+            body
+        )
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+    
+    /// Forget a key claimed by `begin` without storing a response, so a retry runs again
+    pub async fn release(&self, wallet: &str, endpoint: &str, key: &str) -> Result<(), UBIError> {
+        sqlx::query!(
+            r#"
+            DELETE FROM idempotency_keys
+            WHERE wallet_address = $1 AND endpoint = $2 AND idempotency_key = $3 AND status_code IS NULL
+            "#,
+            wallet,
+            endpoint,
+            key
+        )
+        .execute(&self.pool)
+        .await?;
+        
+        Ok(())
+    }
+}
+
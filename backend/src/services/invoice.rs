@@ -0,0 +1,261 @@
+//! Invoice service
+//!
+//! Merchants issue invoices for an amount of UE; a payer settles one with
+//! a single UE payment. Invoices move from open to paid or expired, and
+//! every transition emits an event. A worker records expiries; reads
+//! report an overdue invoice as expired without writing.
+
+use crate::models::invoice::{Invoice, InvoiceRequest, InvoiceResponse};
+use crate::services::registry::RegistryService;
+use crate::services::payment::{check_transfer_amount, send_payment};
+use crate::utils::{epoch::current_epoch, errors::UBIError};
+use crate::events::{emit_event, lock_event_chain, InvoiceCreatedEvent, InvoiceExpiredEvent, InvoicePaidEvent};
+use sqlx::PgPool;
+use std::time::Duration;
+use log::{error, info};
+
+/// Longest invoice reference accepted, in characters
+pub const INVOICE_REFERENCE_MAX_CHARS: usize = 140;
+
+/// How often the worker expires overdue invoices
+const INVOICE_EXPIRY_INTERVAL: Duration = Duration::from_secs(60);
+
+pub struct InvoiceService {
+    pool: PgPool,
+    registry: RegistryService,
+    genesis_timestamp: i64,
+}
+
+impl InvoiceService {
+    pub fn new(pool: PgPool, registry: RegistryService, genesis_timestamp: i64) -> Self {
+        Self {
+            pool,
+            registry,
+            genesis_timestamp,
+        }
+    }
+    
+    /// Issue an invoice payable to `wallet`
+    pub async fn create(&self, wallet: &str, req: InvoiceRequest) -> Result<InvoiceResponse, UBIError> {
+        let amount = check_transfer_amount(req.amount_ue)?;
+        let reference = req.reference.trim();
+        if reference.is_empty() {
+            return Err(UBIError::InvalidTransfer("Invoice reference is required".to_string()));
+        }
+        if reference.chars().count() > INVOICE_REFERENCE_MAX_CHARS {
+            return Err(UBIError::InvalidTransfer(
+                format!("Reference longer than {} characters", INVOICE_REFERENCE_MAX_CHARS)
+            ));
+        }
+        let epoch = current_epoch(self.genesis_timestamp);
+        if req.expiry_epoch < epoch {
+            return Err(UBIError::InvalidTransfer(format!("Expiry epoch is before epoch {}", epoch)));
+        }
+        
+        // Start transaction
+        let mut tx = self.pool.begin().await?;
+        lock_event_chain(&mut *tx).await?;
+        
+        let invoice = sqlx::query_as!(
+            Invoice,
+            r#"
+            INSERT INTO invoices (merchant_wallet, amount_ue, reference, expiry_epoch)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id, merchant_wallet, amount_ue, reference, expiry_epoch, status, payment_id, created_at
+            "#,
+            wallet,
+            amount,
+            reference,
+            req.expiry_epoch
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        
+        // Emit event
+        emit_event(&mut *tx, &InvoiceCreatedEvent {
+            invoice_id: invoice.id,
+            merchant_wallet: invoice.merchant_wallet.clone(),
+            amount_ue: invoice.amount_ue.to_string(),
+            reference: invoice.reference.clone(),
+            expiry_epoch: invoice.expiry_epoch,
+        }).await?;
+        
+        // Commit transaction
+        tx.commit().await?;
+        
+        info!("Invoice {} issued by {} for {} UE", invoice.id, wallet, invoice.amount_ue);
+        
+        Ok(invoice.into())
+    }
+    
+    /// Get an invoice by id
+    ///
+    /// One past its expiry epoch reads as expired even before the worker
+    /// has recorded it
+    pub async fn get(&self, invoice_id: i64) -> Result<InvoiceResponse, UBIError> {
+        let mut invoice = sqlx::query_as!(
+            Invoice,
+            r#"
+            SELECT id, merchant_wallet, amount_ue, reference, expiry_epoch, status, payment_id, created_at
+            FROM invoices
+            WHERE id = $1
+            "#,
+            invoice_id
+        )
+        .fetch_optional(&self.pool)
+        .await?
+        .ok_or(UBIError::Other("Invoice not found".to_string()))?;
+        
+        if invoice.status == "open" && current_epoch(self.genesis_timestamp) > invoice.expiry_epoch {
+            invoice.status = "expired".to_string();
+        }
+        
+        Ok(invoice.into())
+    }
+    
+    /// Settle an open invoice from `wallet`
+    ///
+    /// The payment, its journal entry and the status change commit together
+    pub async fn pay(&self, wallet: &str, invoice_id: i64) -> Result<InvoiceResponse, UBIError> {
+        let payer = self.registry
+            .get_user_by_wallet(wallet)
+            .await?
+            .filter(|user| user.is_active)
+            .ok_or(UBIError::WalletNotActive(wallet.to_string()))?;
+        
+        // Start transaction
+        let mut tx = self.pool.begin().await?;
+        lock_event_chain(&mut *tx).await?;
+        
+        let invoice = sqlx::query_as!(
+            Invoice,
+            r#"
+            SELECT id, merchant_wallet, amount_ue, reference, expiry_epoch, status, payment_id, created_at
+            FROM invoices
+            WHERE id = $1
+            FOR UPDATE
+            "#,
+            invoice_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .ok_or(UBIError::Other("Invoice not found".to_string()))?;
+        
+        match invoice.status.as_str() {
+            "open" => {}
+            "paid" => return Err(UBIError::InvalidTransfer("Invoice already paid".to_string())),
+            _ => return Err(UBIError::InvalidTransfer("Invoice expired".to_string())),
+        }
+        if current_epoch(self.genesis_timestamp) > invoice.expiry_epoch {
+            return Err(UBIError::InvalidTransfer("Invoice expired".to_string()));
+        }
+        if invoice.merchant_wallet == wallet {
+            return Err(UBIError::InvalidTransfer("Cannot pay your own invoice".to_string()));
+        }
+        
+        let payment = send_payment(
+            &mut tx,
+            &payer.person_id,
+            wallet,
+            &invoice.merchant_wallet,
+            invoice.amount_ue,
+            Some(invoice.reference.clone()),
+        ).await?;
+        
+        let invoice = sqlx::query_as!(
+            Invoice,
+            r#"
+            UPDATE invoices SET status = 'paid', payment_id = $2
+            WHERE id = $1
+            RETURNING id, merchant_wallet, amount_ue, reference, expiry_epoch, status, payment_id, created_at
+            "#,
+            invoice_id,
+            payment.id
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        
+        // Emit event
+        emit_event(&mut *tx, &InvoicePaidEvent {
+            invoice_id,
+            payment_id: payment.id,
+            payer_wallet: wallet.to_string(),
+        }).await?;
+        
+        // Commit transaction
+        tx.commit().await?;
+        
+        info!("Invoice {} paid by {} (payment {})", invoice_id, wallet, payment.id);
+        
+        Ok(invoice.into())
+    }
+    
+    /// Expire overdue invoices until the process exits
+    pub async fn run_worker(self) {
+        loop {
+            match self.expire_overdue().await {
+                Ok(0) => {}
+                Ok(expired) => info!("{} invoices expired", expired),
+                Err(e) => error!("Invoice expiry failed: {}", e),
+            }
+            tokio::time::sleep(INVOICE_EXPIRY_INTERVAL).await;
+        }
+    }
+    
+    /// Mark open invoices past their expiry epoch as expired
+    pub async fn expire_overdue(&self) -> Result<usize, UBIError> {
+        let epoch = current_epoch(self.genesis_timestamp);
+        
+        // Start transaction
+        let mut tx = self.pool.begin().await?;
+        lock_event_chain(&mut *tx).await?;
+        
+        let expired = sqlx::query!(
+            r#"
+            UPDATE invoices SET status = 'expired'
+            WHERE status = 'open' AND expiry_epoch < $1
+            RETURNING id, expiry_epoch
+            "#,
+            epoch
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        
+        for row in &expired {
+            emit_event(&mut *tx, &InvoiceExpiredEvent {
+                invoice_id: row.id,
+                expiry_epoch: row.expiry_epoch,
+            }).await?;
+        }
+        
+        // Commit transaction
+        tx.commit().await?;
+        
+        Ok(expired.len())
+    }
+}
+
+impl From<Invoice> for InvoiceResponse {
+    fn from(invoice: Invoice) -> Self {
+        InvoiceResponse {
+            uri: generate_invoice_uri(&invoice),
+            invoice,
+        }
+    }
+}
+
+/// Payment URI for an invoice, for links and QR codes
+///
+/// `ubi:invoice/{id}?to={merchant}&amount={amount_ue}&ref={reference}&exp={expiry_epoch}`,
+/// with form-encoded query values
+pub fn generate_invoice_uri(invoice: &Invoice) -> String {
+    let mut uri = reqwest::Url::parse(&format!("ubi:invoice/{}", invoice.id))
+        .expect("invoice URI base is a valid URL");
+    uri.query_pairs_mut()
+        .append_pair("to", &invoice.merchant_wallet)
+        .append_pair("amount", &invoice.amount_ue.to_string())
+        .append_pair("ref", &invoice.reference)
+        .append_pair("exp", &invoice.expiry_epoch.to_string());
+    uri.to_string()
+}
+
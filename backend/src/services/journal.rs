@@ -0,0 +1,120 @@
+//! Journal service
+//!
+//! CONSTITUTIONAL: Balances are projections of the journal
+//! post_entry is the only writer of ue_balances, bu_balances and the
+//! treasury balances: it appends the entry and applies each posting to
+//! its projection, inside the caller's transaction.
+
+use crate::models::journal::{Account, JournalEntry, Unit};
+use crate::utils::errors::UBIError;
+use rust_decimal::Decimal;
+use sqlx::PgConnection;
+
+/// Append an entry for `event_id` and update the balance projections
+pub async fn post_entry(
+    conn: &mut PgConnection,
+    event_id: i64,
+    description: &str,
+    entry: &JournalEntry,
+) -> Result<i64, UBIError> {
+    if !entry.is_balanced() {
+        return Err(UBIError::Other(format!("Unbalanced journal entry: {}", description)));
+    }
+    
+    let entry_id = sqlx::query_scalar!(
+        "INSERT INTO journal_entries (event_id, description) VALUES ($1, $2) RETURNING id",
+        event_id,
+        description
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    
+    let accounts: Vec<String> = entry.postings.iter().map(|p| p.account.to_string()).collect();
+    let units: Vec<String> = entry.postings.iter().map(|p| p.unit.as_str().to_string()).collect();
+    let amounts: Vec<Decimal> = entry.postings.iter().map(|p| p.amount).collect();
+    sqlx::query!(
+        r#"
+        INSERT INTO journal_postings (entry_id, account, unit, amount)
+        SELECT $1, account, unit, amount
+        FROM UNNEST($2::TEXT[], $3::TEXT[], $4::NUMERIC[]) AS p(account, unit, amount)
+        "#,
+        entry_id,
+        &accounts,
+        &units,
+        &amounts
+    )
+    .execute(&mut *conn)
+    .await?;
+    
+    // Missing wallet rows are created empty first: Postgres checks the proposed
+    // row of an upsert against the non-negativity CHECK before resolving the
+    // conflict, so a debit cannot go through INSERT ... ON CONFLICT
+    for posting in &entry.postings {
+        let amount = posting.amount;
+        match (&posting.account, posting.unit) {
+            (Account::Wallet(wallet), Unit::UE) => {
+                sqlx::query!(
+                    "INSERT INTO ue_balances (wallet_address, balance) VALUES ($1, 0) ON CONFLICT DO NOTHING",
+                    wallet
+                )
+                .execute(&mut *conn)
+                .await?;
+                sqlx::query!(
+                    "UPDATE ue_balances SET balance = balance + $2 WHERE wallet_address = $1",
+                    wallet,
+                    amount
+                )
+                .execute(&mut *conn)
+                .await?;
+            }
+            (Account::Wallet(wallet), Unit::BU) => {
+                sqlx::query!(
+                    "INSERT INTO bu_balances (wallet_address, balance) VALUES ($1, 0) ON CONFLICT DO NOTHING",
+                    wallet
+                )
+                .execute(&mut *conn)
+                .await?;
+                sqlx::query!(
+                    "UPDATE bu_balances SET balance = balance + $2 WHERE wallet_address = $1",
+                    wallet,
+                    amount
+                )
+                .execute(&mut *conn)
+                .await?;
+            }
+            (Account::Treasury, Unit::BU) => {
+                // The latest treasury row is the live balance
+                sqlx::query!(
+                    r#"
+                    UPDATE treasury
+                    SET balance_bu = balance_bu + $1
+                    WHERE id = (SELECT MAX(id) FROM treasury)
+                    "#,
+                    amount
+                )
+                .execute(&mut *conn)
+                .await?;
+            }
+            (Account::Reserved, Unit::BU) => {
+                sqlx::query!(
+                    r#"
+                    UPDATE treasury
+                    SET reserved_bu = reserved_bu + $1
+                    WHERE id = (SELECT MAX(id) FROM treasury)
+                    "#,
+                    amount
+                )
+                .execute(&mut *conn)
+                .await?;
+            }
+            (Account::Treasury | Account::Reserved, Unit::UE) => {
+                return Err(UBIError::Other("The treasury holds no UE".to_string()));
+            }
+            // Sources and sinks have no projection
+            (Account::Genesis | Account::Mint | Account::Burn | Account::Fees, _) => {}
+        }
+    }
+    
+    Ok(entry_id)
+}
+
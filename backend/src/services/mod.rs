@@ -4,6 +4,22 @@ pub mod conversion;
 pub mod rate_index;
 pub mod treasury;
 pub mod oracle;
+pub mod replay;
+pub mod event_chain;
+pub mod event_feed;
+pub mod checkpoint;
+pub mod constitution;
+pub mod fork;
+pub mod webhook;
+pub mod payment;
+pub mod transfer;
+pub mod journal;
+pub mod invoice;
+pub mod standing_order;
+pub mod statement;
+pub mod supply;
+pub mod allowance;
+pub mod idempotency;
 
 pub use registry::*;
 pub use ubi::*;
@@ -11,4 +27,20 @@ pub use conversion::*;
 pub use rate_index::*;
 pub use treasury::*;
 pub use oracle::*;
+pub use replay::*;
+pub use event_chain::*;
+pub use event_feed::*;
+pub use checkpoint::*;
+pub use constitution::*;
+pub use fork::*;
+pub use webhook::*;
+pub use payment::*;
+pub use transfer::*;
+pub use journal::*;
+pub use invoice::*;
+pub use standing_order::*;
+pub use statement::*;
+pub use supply::*;
+pub use allowance::*;
+pub use idempotency::*;
 
@@ -2,6 +2,8 @@
 
 use crate::models::oracle::{RegionOracleData, OracleSubmission};
 use crate::utils::errors::UBIError;
+use crate::events::{emit_event, lock_event_chain, OracleDataSubmittedEvent};
+use rust_decimal::Decimal;
 use sqlx::PgPool;
 use log::info;
 
@@ -19,7 +21,12 @@ impl OracleService {
         info!("Oracle submission for region {}: {}", submission.region_id, submission.basket_index_wad);
         
         // Calculate inflation rate (simplified: assume 0% if no previous data)
-        let inflation_rate = "0".to_string();
+        let inflation_rate = Decimal::ZERO;
+        let timestamp = chrono::Utc::now().timestamp();
+        
+        // Start transaction
+        let mut tx = self.pool.begin().await?;
+        lock_event_chain(&mut *tx).await?;
         
         // Update or insert oracle data
         sqlx::query!(
@@ -35,11 +42,22 @@ impl OracleService {
             submission.region_id,
             submission.basket_index_wad,
             inflation_rate,
-            chrono::Utc::now().timestamp()
+            timestamp
         )
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
         
+        // Emit event
+        emit_event(&mut *tx, &OracleDataSubmittedEvent {
+            region_id: submission.region_id,
+            basket_index_wad: submission.basket_index_wad.to_string(),
+            inflation_rate_wad: inflation_rate.to_string(),
+            timestamp,
+        }).await?;
+        
+        // Commit transaction
+        tx.commit().await?;
+        
         Ok(())
     }
     
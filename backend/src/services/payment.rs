@@ -0,0 +1,162 @@
+//! Payment service
+//!
+//! Peer-to-peer UE transfers between wallets. The sender must be an
+//! active person; anyone can receive. Both balances change in one
+//! transaction together with the UEPaid event.
+
+use crate::models::payment::{Payment, PaymentRequest};
+use crate::models::journal::{Account, JournalEntry, Unit};
+use crate::services::registry::RegistryService;
+use crate::services::journal::post_entry;
+use crate::utils::errors::UBIError;
+use crate::events::{emit_event, lock_event_chain, UEPaidEvent};
+use sqlx::{PgConnection, PgPool};
+use rust_decimal::Decimal;
+use log::info;
+use hex;
+
+/// Longest memo accepted on a payment, in characters
+pub const PAYMENT_MEMO_MAX_CHARS: usize = 280;
+
+pub struct PaymentService {
+    pool: PgPool,
+    registry: RegistryService,
+}
+
+impl PaymentService {
+    pub fn new(pool: PgPool, registry: RegistryService) -> Self {
+        Self { pool, registry }
+    }
+    
+    /// Pay UE from `wallet` to another wallet
+    pub async fn pay(&self, wallet: &str, req: PaymentRequest) -> Result<Payment, UBIError> {
+        info!("Payment request: {} UE from {} to {}", req.amount_ue, wallet, req.to_wallet);
+        
+        let to_wallet = req.to_wallet.trim();
+        if to_wallet.is_empty() {
+            return Err(UBIError::InvalidTransfer("Recipient wallet is required".to_string()));
+        }
+        if to_wallet == wallet {
+            return Err(UBIError::InvalidTransfer("Cannot pay your own wallet".to_string()));
+        }
+        let amount = check_transfer_amount(req.amount_ue)?;
+        let memo = req.memo
+            .map(|memo| memo.trim().to_string())
+            .filter(|memo| !memo.is_empty());
+        if memo.as_ref().is_some_and(|memo| memo.chars().count() > PAYMENT_MEMO_MAX_CHARS) {
+            return Err(UBIError::InvalidTransfer(
+                format!("Memo longer than {} characters", PAYMENT_MEMO_MAX_CHARS)
+            ));
+        }
+        
+        let sender = self.registry
+            .get_user_by_wallet(wallet)
+            .await?
+            .filter(|user| user.is_active)
+            .ok_or(UBIError::WalletNotActive(wallet.to_string()))?;
+        
+        // Start transaction
+        let mut tx = self.pool.begin().await?;
+        lock_event_chain(&mut *tx).await?;
+        
+        let payment = send_payment(&mut tx, &sender.person_id, wallet, to_wallet, amount, memo).await?;
+        
+        // Commit transaction
+        tx.commit().await?;
+        
+        info!("Payment {} successful: {} UE from {} to {}",
+              payment.id, payment.amount_ue, payment.from_wallet, payment.to_wallet);
+        
+        Ok(payment)
+    }
+}
+
+/// Move UE between wallets and record the payment, inside the caller's transaction
+///
+/// The caller has already validated the request and the sender, and
+/// taken the event chain lock before any row lock
+pub async fn send_payment(
+    conn: &mut PgConnection,
+    person_id: &[u8],
+    from_wallet: &str,
+    to_wallet: &str,
+    amount: Decimal,
+    memo: Option<String>,
+) -> Result<Payment, UBIError> {
+    // Make sure both rows exist, then lock them in a fixed order so
+    // opposite payments between the same wallets cannot deadlock
+    sqlx::query!(
+        "INSERT INTO ue_balances (wallet_address, balance) VALUES ($1, 0) ON CONFLICT (wallet_address) DO NOTHING",
+        to_wallet
+    )
+    .execute(&mut *conn)
+    .await?;
+    
+    let wallets = vec![from_wallet.to_string(), to_wallet.to_string()];
+    let rows = sqlx::query!(
+        r#"
+        SELECT wallet_address, balance FROM ue_balances
+        WHERE wallet_address = ANY($1)
+        ORDER BY wallet_address
+        FOR UPDATE
+        "#,
+        &wallets
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    
+    let sender_balance = rows.iter()
+        .find(|row| row.wallet_address == from_wallet)
+        .map_or(Decimal::ZERO, |row| row.balance);
+    
+    if sender_balance < amount {
+        return Err(UBIError::InsufficientBalance);
+    }
+    
+    // Record payment
+    let payment = sqlx::query_as!(
+        Payment,
+        r#"
+        INSERT INTO ue_payments (from_wallet, to_wallet, amount_ue, memo)
+        VALUES ($1, $2, $3, $4)
+        RETURNING id, from_wallet, to_wallet, amount_ue, memo, created_at
+        "#,
+        from_wallet,
+        to_wallet,
+        amount,
+        memo
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    
+    // Emit event
+    let event_id = emit_event(&mut *conn, &UEPaidEvent {
+        payment_id: payment.id,
+        person_id: hex::encode(person_id),
+        from_wallet: payment.from_wallet.clone(),
+        to_wallet: payment.to_wallet.clone(),
+        amount_ue: payment.amount_ue.to_string(),
+        memo: payment.memo.clone(),
+    }).await?;
+    
+    let entry = JournalEntry::new().transfer(
+        Unit::UE,
+        Account::Wallet(payment.from_wallet.clone()),
+        Account::Wallet(payment.to_wallet.clone()),
+        amount,
+    );
+    post_entry(&mut *conn, event_id, "UE payment", &entry).await?;
+    
+    Ok(payment)
+}
+
+/// Check a transfer amount: a positive whole number of base units
+pub fn check_transfer_amount(amount: Decimal) -> Result<Decimal, UBIError> {
+    if amount <= Decimal::ZERO || !amount.fract().is_zero() {
+        return Err(UBIError::InvalidTransfer(
+            "Amount must be a positive whole number of base units".to_string()
+        ));
+    }
+    Ok(amount.normalize())
+}
+
@@ -7,7 +7,8 @@ use crate::utils::{epoch::current_epoch, errors::UBIError, wad};
 use crate::constants::{
     RATE_INDEX_START, BASE_DECAY, MIN_DECAY, MAX_DECAY, MAX_DECAY_CHANGE
 };
-use sqlx::PgPool;
+use crate::events::{emit_event, lock_event_chain, DecayRateUpdatedEvent, RateIndexUpdatedEvent};
+use sqlx::{PgConnection, PgPool};
 use rust_decimal::Decimal;
 use log::info;
 
@@ -28,12 +29,16 @@ impl RateIndexService {
     pub async fn roll_rate_index(&self, region_id: i32) -> Result<(), UBIError> {
         let epoch = current_epoch(self.genesis_timestamp);
         
-        // Get current rate index
+        // Start transaction
+        let mut tx = self.pool.begin().await?;
+        lock_event_chain(&mut *tx).await?;
+        
+        // Get current rate index (locked, so concurrent rolls apply decay once)
         let rate_data = sqlx::query!(
-            "SELECT rate_index_wad, last_epoch, current_decay_rate_wad, last_decay_update_epoch FROM rate_index WHERE region_id = $1",
+            "SELECT rate_index_wad, last_epoch, current_decay_rate_wad, last_decay_update_epoch FROM rate_index WHERE region_id = $1 FOR UPDATE",
             region_id
         )
-        .fetch_optional(&self.pool)
+        .fetch_optional(&mut *tx)
         .await?;
         
         if let Some(data) = rate_data {
@@ -42,20 +47,20 @@ impl RateIndexService {
             }
             
             // Update decay rate first
-            self.update_decay_rate(region_id, epoch).await?;
+            self.update_decay_rate(&mut *tx, region_id, epoch).await?;
             
             // Get updated decay rate
             let decay_rate = sqlx::query_scalar!(
                 "SELECT current_decay_rate_wad FROM rate_index WHERE region_id = $1",
                 region_id
             )
-            .fetch_one(&self.pool)
-            .await?
-            .unwrap_or_else(|| BASE_DECAY.to_string());
+            .fetch_one(&mut *tx)
+            .await?;
             
             // Apply decay for epochs since last update
             let epochs_to_apply = epoch - data.last_epoch;
-            let new_rate_index = wad::apply_decay(&data.rate_index_wad, &decay_rate, epochs_to_apply)?;
+            let new_rate_index = wad::apply_decay(data.rate_index_wad, decay_rate, epochs_to_apply)
+                .map_err(|e| UBIError::Other(e.to_string()))?;
             
             // Update rate index
             sqlx::query!(
@@ -64,39 +69,67 @@ impl RateIndexService {
                 SET rate_index_wad = $1, last_epoch = $2
                 WHERE region_id = $3
                 "#,
-                &new_rate_index,
+                new_rate_index,
                 epoch,
                 region_id
             )
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
+            
+            // Emit event
+            emit_event(&mut *tx, &RateIndexUpdatedEvent {
+                region_id,
+                rate_index: new_rate_index.to_string(),
+                decay_rate: decay_rate.to_string(),
+                epoch,
+            }).await?;
         } else {
             // Initialize rate index (starts at 1.0)
+            let rate_index_start: Decimal = RATE_INDEX_START.parse()
+                .map_err(|_| UBIError::Other("Invalid rate index start".to_string()))?;
+            let base_decay: Decimal = BASE_DECAY.parse()
+                .map_err(|_| UBIError::Other("Invalid base decay".to_string()))?;
             sqlx::query!(
                 r#"
                 INSERT INTO rate_index (region_id, rate_index_wad, last_epoch, current_decay_rate_wad, last_decay_update_epoch)
                 VALUES ($1, $2, $3, $4, $3)
                 "#,
                 region_id,
-                RATE_INDEX_START,
+                rate_index_start,
                 epoch,
-                BASE_DECAY
+                base_decay
             )
-            .execute(&self.pool)
+            .execute(&mut *tx)
             .await?;
+            
+            // Emit event
+            emit_event(&mut *tx, &RateIndexUpdatedEvent {
+                region_id,
+                rate_index: RATE_INDEX_START.to_string(),
+                decay_rate: BASE_DECAY.to_string(),
+                epoch,
+            }).await?;
         }
         
+        // Commit transaction
+        tx.commit().await?;
+        
         Ok(())
     }
     
     /// Update decay rate based on oracle inflation signal
-    async fn update_decay_rate(&self, region_id: i32, epoch: i32) -> Result<(), UBIError> {
+    async fn update_decay_rate(
+        &self,
+        conn: &mut PgConnection,
+        region_id: i32,
+        epoch: i32,
+    ) -> Result<(), UBIError> {
         // Get last decay update epoch
         let last_update = sqlx::query_scalar!(
             "SELECT last_decay_update_epoch FROM rate_index WHERE region_id = $1",
             region_id
         )
-        .fetch_optional(&self.pool)
+        .fetch_optional(&mut *conn)
         .await?;
         
         if let Some(last_epoch) = last_update {
@@ -108,54 +141,17 @@ impl RateIndexService {
         // Get inflation rate from oracle
         let inflation_rate = self.get_inflation_rate(region_id).await?;
         
-        // Calculate target decay rate: baseDecay - (k * inflation)
-        // k = 0.5 (hardcoded for simplicity)
-        let k_wad = "500000000000000000"; // 0.5e18
-        let k_inflation = wad::mul_wad(k_wad, &inflation_rate)?;
         let base_decay: Decimal = BASE_DECAY.parse()
             .map_err(|_| UBIError::Other("Invalid base decay".to_string()))?;
-        let k_inf_decimal: Decimal = k_inflation.parse()
-            .map_err(|_| UBIError::Other("Invalid k inflation".to_string()))?;
-        
-        let target_decimal = base_decay - (k_inf_decimal / Decimal::from(1_000_000_000_000_000_000u64));
-        
-        // Clamp to bounds
-        let min_decay: Decimal = MIN_DECAY.parse()
-            .map_err(|_| UBIError::Other("Invalid min decay".to_string()))?;
-        let max_decay: Decimal = MAX_DECAY.parse()
-            .map_err(|_| UBIError::Other("Invalid max decay".to_string()))?;
-        
-        let target_clamped = if target_decimal < min_decay {
-            min_decay
-        } else if target_decimal > max_decay {
-            max_decay
-        } else {
-            target_decimal
-        };
-        
-        // Apply max change per epoch
-        let current_decay = sqlx::query_scalar!(
+        let current = sqlx::query_scalar!(
             "SELECT current_decay_rate_wad FROM rate_index WHERE region_id = $1",
             region_id
         )
-        .fetch_optional(&self.pool)
+        .fetch_optional(&mut *conn)
         .await?
-        .unwrap_or_else(|| BASE_DECAY.to_string());
-        
-        let current: Decimal = current_decay.parse()
-            .map_err(|_| UBIError::Other("Invalid current decay".to_string()))?;
-        let max_change: Decimal = MAX_DECAY_CHANGE.parse()
-            .map_err(|_| UBIError::Other("Invalid max change".to_string()))?;
-        let epochs_since_update = epoch - last_update.unwrap_or(epoch);
-        let max_total_change = max_change * Decimal::from(epochs_since_update);
-        
-        let final_target = if target_clamped < current - max_total_change {
-            current - max_total_change
-        } else if target_clamped > current + max_total_change {
-            current + max_total_change
-        } else {
-            target_clamped
-        };
+        .unwrap_or(base_decay);
+        
+        let final_target = next_decay_rate(current, inflation_rate, epoch - last_update.unwrap_or(epoch))?;
         
         // Update decay rate
         sqlx::query!(
@@ -164,18 +160,27 @@ impl RateIndexService {
             SET current_decay_rate_wad = $1, last_decay_update_epoch = $2
             WHERE region_id = $3
             "#,
-            &final_target.to_string(),
+            final_target,
             epoch,
             region_id
         )
-        .execute(&self.pool)
+        .execute(&mut *conn)
         .await?;
         
+        // Emit event
+        emit_event(&mut *conn, &DecayRateUpdatedEvent {
+            region_id,
+            previous_decay_rate: current.to_string(),
+            decay_rate: final_target.to_string(),
+            inflation_rate: inflation_rate.to_string(),
+            epoch,
+        }).await?;
+        
         Ok(())
     }
     
     /// Get rate index for region
-    pub async fn get_rate_index(&self, region_id: i32) -> Result<String, UBIError> {
+    pub async fn get_rate_index(&self, region_id: i32) -> Result<Decimal, UBIError> {
         // Ensure rate index is up to date
         self.roll_rate_index(region_id).await?;
         
@@ -186,11 +191,42 @@ impl RateIndexService {
         .fetch_optional(&self.pool)
         .await?;
         
-        Ok(rate.unwrap_or_else(|| "0".to_string()))
+        Ok(rate.unwrap_or(Decimal::ZERO))
+    }
+    
+    /// Rate index a roll to the current epoch would produce, without writing it
+    pub async fn preview_rate_index(&self, region_id: i32) -> Result<Decimal, UBIError> {
+        let epoch = current_epoch(self.genesis_timestamp);
+        
+        let rate_data = sqlx::query!(
+            "SELECT rate_index_wad, last_epoch, current_decay_rate_wad, last_decay_update_epoch FROM rate_index WHERE region_id = $1",
+            region_id
+        )
+        .fetch_optional(&self.pool)
+        .await?;
+        
+        let Some(data) = rate_data else {
+            // A roll would initialize it
+            return RATE_INDEX_START.parse()
+                .map_err(|_| UBIError::Other("Invalid rate index start".to_string()));
+        };
+        if epoch <= data.last_epoch {
+            return Ok(data.rate_index_wad);
+        }
+        
+        // Same order as roll_rate_index: decay rate first, then decay
+        let decay_rate = if epoch > data.last_decay_update_epoch {
+            let inflation_rate = self.get_inflation_rate(region_id).await?;
+            next_decay_rate(data.current_decay_rate_wad, inflation_rate, epoch - data.last_decay_update_epoch)?
+        } else {
+            data.current_decay_rate_wad
+        };
+        wad::apply_decay(data.rate_index_wad, decay_rate, epoch - data.last_epoch)
+            .map_err(|e| UBIError::Other(e.to_string()))
     }
     
     /// Get inflation rate from oracle
-    async fn get_inflation_rate(&self, region_id: i32) -> Result<String, UBIError> {
+    async fn get_inflation_rate(&self, region_id: i32) -> Result<Decimal, UBIError> {
         let inflation = sqlx::query_scalar!(
             "SELECT current_inflation_rate_wad FROM region_oracle_data WHERE region_id = $1",
             region_id
@@ -198,7 +234,49 @@ impl RateIndexService {
         .fetch_optional(&self.pool)
         .await?;
         
-        Ok(inflation.unwrap_or_else(|| "0".to_string()))
+        Ok(inflation.unwrap_or(Decimal::ZERO))
     }
 }
 
+/// Decay rate after `epochs_since_update` epochs of the oracle's inflation signal
+fn next_decay_rate(current: Decimal, inflation_rate: Decimal, epochs_since_update: i32) -> Result<Decimal, UBIError> {
+    // Calculate target decay rate: baseDecay - (k * inflation)
+    // k = 0.5 (hardcoded for simplicity)
+    let k_wad = Decimal::from(500_000_000_000_000_000u64); // 0.5e18
+    let k_inflation = wad::mul_wad(k_wad, inflation_rate)
+        .map_err(|e| UBIError::Other(e.to_string()))?;
+    let base_decay: Decimal = BASE_DECAY.parse()
+        .map_err(|_| UBIError::Other("Invalid base decay".to_string()))?;
+    
+    let target_decimal = base_decay - (k_inflation / Decimal::from(1_000_000_000_000_000_000u64));
+    
+    // Clamp to bounds
+    let min_decay: Decimal = MIN_DECAY.parse()
+        .map_err(|_| UBIError::Other("Invalid min decay".to_string()))?;
+    let max_decay: Decimal = MAX_DECAY.parse()
+        .map_err(|_| UBIError::Other("Invalid max decay".to_string()))?;
+    
+    let target_clamped = if target_decimal < min_decay {
+        min_decay
+    } else if target_decimal > max_decay {
+        max_decay
+    } else {
+        target_decimal
+    };
+    
+    // Apply max change per epoch
+    let max_change: Decimal = MAX_DECAY_CHANGE.parse()
+        .map_err(|_| UBIError::Other("Invalid max change".to_string()))?;
+    let max_total_change = max_change * Decimal::from(epochs_since_update);
+    
+    let final_target = if target_clamped < current - max_total_change {
+        current - max_total_change
+    } else if target_clamped > current + max_total_change {
+        current + max_total_change
+    } else {
+        target_clamped
+    }.trunc();
+    
+    Ok(final_target)
+}
+
@@ -3,8 +3,13 @@
 //! CONSTITUTIONAL: Identity = personId, NOT wallet
 
 use crate::models::user::{User, PersonId};
+use crate::models::journal::{Account, JournalEntry, Unit};
+use crate::services::journal::post_entry;
+use crate::utils::auth::{key_wallet_address, Claims, TokenHolder};
 use crate::utils::{errors::UBIError, mfa};
+use crate::events::{emit_event, lock_event_chain, PersonRegisteredEvent, WalletResetEvent};
 use sqlx::PgPool;
+use rust_decimal::Decimal;
 use hex;
 use log::info;
 
@@ -49,6 +54,10 @@ impl RegistryService {
         // Generate MFA secret
         let mfa_secret = mfa::generate_mfa_secret();
         
+        // Start transaction
+        let mut tx = self.pool.begin().await?;
+        lock_event_chain(&mut *tx).await?;
+        
         // Insert user
         sqlx::query!(
             r#"
@@ -61,35 +70,29 @@ impl RegistryService {
             expiry_epoch,
             mfa_secret
         )
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
         
-        // Initialize UE balance
+        // Initialize UE balance (the wallet may already hold payments)
         sqlx::query!(
-            "INSERT INTO ue_balances (wallet_address, balance) VALUES ($1, '0')",
+            "INSERT INTO ue_balances (wallet_address, balance) VALUES ($1, 0) ON CONFLICT (wallet_address) DO NOTHING",
             wallet_address
         )
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
         
-        info!("Person registered: {} (region {})", person_id_hex, region_id);
-        
         // Emit event
-        let event_data = serde_json::to_value(crate::events::PersonRegisteredEvent {
+        emit_event(&mut *tx, &PersonRegisteredEvent {
             person_id: person_id_hex.to_string(),
             wallet_address: wallet_address.to_string(),
             region_id,
             expiry_epoch,
-        }).unwrap();
+        }).await?;
         
-        // Emit event
-        sqlx::query!(
-            "INSERT INTO events (event_type, event_data) VALUES ($1, $2)",
-            "PersonRegistered",
-            event_data
-        )
-        .execute(&self.pool)
-        .await?;
+        // Commit transaction
+        tx.commit().await?;
+        
+        info!("Person registered: {} (region {})", person_id_hex, region_id);
         
         // Get created user
         let user = sqlx::query_as!(
@@ -129,34 +132,24 @@ impl RegistryService {
             return Err(UBIError::MFAVerificationFailed);
         }
         
-        // Transfer UE balance
+        // Start transaction
+        let mut tx = self.pool.begin().await?;
+        lock_event_chain(&mut *tx).await?;
+        
+        // UE balance moves to the new wallet
         let old_balance = sqlx::query_scalar!(
-            "SELECT balance FROM ue_balances WHERE wallet_address = $1",
+            "SELECT balance FROM ue_balances WHERE wallet_address = $1 FOR UPDATE",
             user.wallet_address
         )
-        .fetch_optional(&self.pool)
+        .fetch_optional(&mut *tx)
         .await?
-        .unwrap_or_else(|| "0".to_string());
-        
-        // Update old wallet balance to 0
-        sqlx::query!(
-            "UPDATE ue_balances SET balance = '0' WHERE wallet_address = $1",
-            user.wallet_address
-        )
-        .execute(&self.pool)
-        .await?;
+        .unwrap_or(Decimal::ZERO);
         
-        // Set new wallet balance
         sqlx::query!(
-            r#"
-            INSERT INTO ue_balances (wallet_address, balance)
-            VALUES ($1, $2)
-            ON CONFLICT (wallet_address) DO UPDATE SET balance = $2
-            "#,
-            new_wallet,
-            old_balance
+            "INSERT INTO ue_balances (wallet_address, balance) VALUES ($1, 0) ON CONFLICT (wallet_address) DO NOTHING",
+            new_wallet
         )
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
         
         // Update user wallet
@@ -175,27 +168,123 @@ impl RegistryService {
             epoch,
             person_id.as_slice()
         )
-        .execute(&self.pool)
+        .execute(&mut *tx)
         .await?;
         
-        info!("Wallet reset: {} -> {}", user.wallet_address, new_wallet);
-        
         // Emit event
-        let event_data = serde_json::to_value(crate::events::WalletResetEvent {
+        let event_id = emit_event(&mut *tx, &WalletResetEvent {
             person_id: person_id_hex.to_string(),
-            old_wallet: user.wallet_address,
+            old_wallet: user.wallet_address.clone(),
             new_wallet: new_wallet.to_string(),
-        }).unwrap();
+        }).await?;
         
-        sqlx::query!(
-            "INSERT INTO events (event_type, event_data) VALUES ($1, $2)",
-            "WalletReset",
-            event_data
+        let entry = JournalEntry::new().transfer(
+            Unit::UE,
+            Account::Wallet(user.wallet_address.clone()),
+            Account::Wallet(new_wallet.to_string()),
+            old_balance,
+        );
+        post_entry(&mut *tx, event_id, "Wallet reset", &entry).await?;
+        
+        // Commit transaction
+        tx.commit().await?;
+        
+        info!("Wallet reset: {} -> {}", user.wallet_address, new_wallet);
+        
+        Ok(())
+    }
+    
+    /// Active person whose MFA code checks out, for issuing a token
+    pub async fn authenticate(
+        &self,
+        person_id_hex: &str,
+        mfa_code: &str,
+    ) -> Result<User, UBIError> {
+        let person_id = hex::decode(person_id_hex)
+            .map_err(|_| UBIError::InvalidPersonId)?;
+        
+        // Get user
+        let user = sqlx::query_as!(
+            User,
+            "SELECT person_id, wallet_address, region_id, expiry_epoch, last_reset_epoch, is_active, mfa_secret, created_at FROM users WHERE person_id = $1",
+            person_id.as_slice()
         )
-        .execute(&self.pool)
+        .fetch_optional(&self.pool)
+        .await?
+        .filter(|user| user.is_active)
+        .ok_or(UBIError::UserNotFound)?;
+        
+        // Verify MFA
+        let mfa_secret = user.mfa_secret.as_deref().ok_or(UBIError::MFAVerificationFailed)?;
+        if !mfa::verify_mfa_code(mfa_secret, mfa_code) {
+            return Err(UBIError::MFAVerificationFailed);
+        }
+        
+        Ok(user)
+    }
+    
+    /// Wallet a token for `user` may act for: their current wallet by default
+    ///
+    /// A wallet they have reset away from is allowed too, so BU left there
+    /// can still be moved, unless another person now holds it
+    pub async fn token_wallet(&self, user: &User, requested: Option<&str>) -> Result<String, UBIError> {
+        let Some(wallet) = requested.map(str::trim).filter(|wallet| *wallet != user.wallet_address) else {
+            return Ok(user.wallet_address.clone());
+        };
+        
+        let held_before = sqlx::query_scalar!(
+            r#"
+            SELECT EXISTS (
+                SELECT 1 FROM events
+                WHERE event_type = 'WalletReset'
+                  AND event_data->>'person_id' = $1
+                  AND event_data->>'old_wallet' = $2
+            ) AS "held!"
+            "#,
+            hex::encode(&user.person_id),
+            wallet
+        )
+        .fetch_one(&self.pool)
         .await?;
+        if !held_before || self.get_user_by_wallet(wallet).await?.is_some() {
+            return Err(UBIError::Forbidden(format!("{} is not a wallet this person can act for", wallet)));
+        }
         
-        Ok(())
+        Ok(wallet.to_string())
+    }
+    
+    /// Whether the registry still honours a token's claims
+    ///
+    /// A person's token lapses once they reset away from the wallet it was
+    /// issued under, or if they are deactivated
+    pub async fn check_token(&self, claims: &Claims) -> Result<(), UBIError> {
+        match &claims.issued_to {
+            TokenHolder::Person { registry_wallet } => {
+                let person_id = hex::decode(&claims.user_id).map_err(|_| UBIError::Unauthorized)?;
+                let current = sqlx::query_scalar!(
+                    "SELECT wallet_address FROM users WHERE person_id = $1 AND is_active",
+                    person_id.as_slice()
+                )
+                .fetch_optional(&self.pool)
+                .await?;
+                if current.as_ref() != Some(registry_wallet) {
+                    return Err(UBIError::Unauthorized);
+                }
+                // A former wallet another person has since registered is theirs now
+                if claims.wallet_address != *registry_wallet
+                    && self.get_user_by_wallet(&claims.wallet_address).await?.is_some()
+                {
+                    return Err(UBIError::Unauthorized);
+                }
+                Ok(())
+            }
+            TokenHolder::WalletKey => {
+                if key_wallet_address(&claims.user_id).as_ref() != Some(&claims.wallet_address) {
+                    return Err(UBIError::Unauthorized);
+                }
+                Ok(())
+            }
+        }
     }
     
     /// Get user by wallet
@@ -0,0 +1,1325 @@
+//! Replay service
+//!
+//! CONSTITUTIONAL: State must be reconstructible from events
+//! Folds the append-only event log back into every table and reports
+//! where the result disagrees with live state
+
+use crate::models::replay::{
+    Divergence, ReplayAllowance, ReplayAllowanceSpend, ReplayConversion, ReplayFork, ReplayInvoice, ReplayOracleData, ReplayPayment,
+    ReplayRateIndex, ReplayReport, ReplayStandingOrder, ReplayStandingOrderExecution, ReplayState,
+    ReplayTransfer, ReplayUser,
+};
+use crate::models::constitution::ConstitutionParameters;
+use crate::models::journal::{ledger_key, Account, JournalEntry, Unit};
+use crate::events::{
+    Event, EventPayload, EventType, AllowanceApprovedEvent, AllowanceRevokedEvent, AllowanceSpentEvent,
+    BUTransferredEvent, ConstitutionAmendedEvent, ConversionCancelledEvent, ConversionClaimedEvent,
+    ConversionRationedEvent, ConversionRequestedEvent, ConversionUnlockedEvent, DecayRateUpdatedEvent, ForkCreatedEvent, InvoiceCreatedEvent,
+    InvoiceExpiredEvent, InvoicePaidEvent, OracleDataSubmittedEvent, PersonRegisteredEvent,
+    StandingOrderCancelledEvent, StandingOrderCreatedEvent, StandingOrderExecutedEvent,
+    StandingOrderSkippedEvent, RateIndexUpdatedEvent, TreasuryDebitedEvent, UBIClaimedEvent,
+    UEPaidEvent, WalletResetEvent,
+};
+use crate::services::checkpoint::CheckpointService;
+use crate::utils::{epoch::epoch_at, errors::UBIError};
+use crate::constants::{BU_TOTAL_SUPPLY, CONVERSION_DELAY_EPOCHS};
+use serde::Serialize;
+use sqlx::{PgConnection, PgPool};
+use rust_decimal::Decimal;
+use std::collections::BTreeMap;
+use log::info;
+use hex;
+
+pub struct ReplayService {
+    pool: PgPool,
+    genesis_timestamp: i64,
+}
+
+impl ReplayService {
+    pub fn new(pool: PgPool, genesis_timestamp: i64) -> Self {
+        Self {
+            pool,
+            genesis_timestamp,
+        }
+    }
+    
+    /// Replay the full event log and compare the result with live tables
+    pub async fn verify(&self) -> Result<ReplayReport, UBIError> {
+        let events = self.load_events().await?;
+        let (replayed, unhandled) = self.fold(&events)?;
+        self.report(&events, replayed, unhandled).await
+    }
+    
+    /// Replay from a verified checkpoint instead of genesis
+    ///
+    /// Only events after the checkpoint's last event are folded
+    pub async fn verify_from_checkpoint(&self, epoch: i32) -> Result<ReplayReport, UBIError> {
+        let checkpoints = CheckpointService::new(self.pool.clone(), self.genesis_timestamp, None);
+        let (checkpoint, snapshot) = checkpoints.load_verified_snapshot(epoch).await?;
+        
+        let events = self.load_events_after(checkpoint.last_event_id).await?;
+        let (replayed, unhandled) = self.fold_from(snapshot, &events)?;
+        self.report(&events, replayed, unhandled).await
+    }
+    
+    async fn report(
+        &self,
+        events: &[Event],
+        replayed: ReplayState,
+        unhandled: usize,
+    ) -> Result<ReplayReport, UBIError> {
+        let live = self.load_live_state().await?;
+        
+        let divergences = diff_states(&replayed, &live)?;
+        
+        info!("Replayed {} events ({} of unknown type), {} divergences",
+              events.len(), unhandled, divergences.len());
+        
+        Ok(ReplayReport {
+            events_replayed: events.len(),
+            events_unhandled: unhandled,
+            consistent: divergences.is_empty(),
+            divergences,
+        })
+    }
+    
+    /// Load the full event log in append order
+    pub async fn load_events(&self) -> Result<Vec<Event>, UBIError> {
+        self.load_events_after(0).await
+    }
+    
+    /// Load events with id greater than `after_id`, in append order
+    pub async fn load_events_after(&self, after_id: i64) -> Result<Vec<Event>, UBIError> {
+        let events = sqlx::query_as!(
+            Event,
+            "SELECT id, event_type, event_data, created_at, prev_hash, hash, hash_version FROM events WHERE id > $1 ORDER BY id",
+            after_id
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(events)
+    }
+    
+    /// Fold events into state, starting from genesis
+    ///
+    /// Returns the state and the number of events of unknown type
+    pub fn fold(&self, events: &[Event]) -> Result<(ReplayState, usize), UBIError> {
+        let mut genesis = ReplayState {
+            treasury_bu: "0".to_string(),
+            treasury_reserved_bu: "0".to_string(),
+            ..Default::default()
+        };
+        let supply = JournalEntry::new()
+            .transfer(Unit::BU, Account::Genesis, Account::Treasury, parse(BU_TOTAL_SUPPLY)?);
+        post(&mut genesis, &supply)?;
+        self.fold_from(genesis, events)
+    }
+    
+    /// Fold events into an existing state
+    pub fn fold_from(
+        &self,
+        mut state: ReplayState,
+        events: &[Event],
+    ) -> Result<(ReplayState, usize), UBIError> {
+        let mut unhandled = 0;
+        for event in events {
+            if !self.apply(&mut state, event)? {
+                unhandled += 1;
+            }
+        }
+        
+        Ok((state, unhandled))
+    }
+    
+    /// Apply a single event to state
+    ///
+    /// Returns false if the event type is unknown to this build
+    pub fn apply(&self, state: &mut ReplayState, event: &Event) -> Result<bool, UBIError> {
+        let event_type = match event.event_type() {
+            Some(event_type) => event_type,
+            None => return Ok(false),
+        };
+        
+        match event_type {
+            EventType::PersonRegistered => {
+                let e: PersonRegisteredEvent = decode(event)?;
+                
+                state.users.insert(e.person_id.clone(), ReplayUser {
+                    wallet_address: e.wallet_address.clone(),
+                    region_id: e.region_id,
+                    expiry_epoch: e.expiry_epoch,
+                    last_reset_epoch: 0,
+                    is_active: true,
+                });
+                state.ue_balances.entry(e.wallet_address).or_insert_with(|| "0".to_string());
+            }
+            EventType::UBIClaimed => {
+                let e: UBIClaimedEvent = decode(event)?;
+                let region_id = state.users.get(&e.person_id)
+                    .ok_or_else(|| unknown_person(event, &e.person_id))?
+                    .region_id;
+                
+                state.ubi_claims
+                    .entry(e.person_id.clone())
+                    .or_default()
+                    .insert(e.epoch, e.amount_ue.clone());
+                state.last_claimed_epoch
+                    .entry(e.person_id)
+                    .or_default()
+                    .insert(region_id, e.epoch);
+                post(state, &JournalEntry::new().transfer(
+                    Unit::UE,
+                    Account::Mint,
+                    Account::Wallet(e.wallet_address),
+                    parse(&e.amount_ue)?,
+                ))?;
+            }
+            EventType::ConversionRequested => {
+                let e: ConversionRequestedEvent = decode(event)?;
+                
+                // Events written before conversion ids were recorded follow insertion order
+                let conversion_id = e.conversion_id.unwrap_or_else(|| {
+                    state.pending_conversions.keys().next_back().map_or(1, |id| id + 1)
+                });
+                
+                // Events before v3 carry no fee: the whole amount counts as burned
+                let amount_ue = parse(&e.amount_ue)?;
+                let fee_ue = parse(e.fee_ue.as_deref().unwrap_or("0"))?;
+                let mut entry = JournalEntry::new()
+                    .transfer(Unit::UE, Account::Wallet(e.wallet_address.clone()), Account::Burn, amount_ue - fee_ue)
+                    .transfer(Unit::UE, Account::Wallet(e.wallet_address.clone()), Account::Fees, fee_ue);
+                // Events before v4 reserved nothing
+                if e.bu_reserved {
+                    entry = entry.transfer(Unit::BU, Account::Treasury, Account::Reserved, parse(&e.amount_bu)?);
+                }
+                post(state, &entry)?;
+                credit(
+                    state.converted_this_epoch.entry(e.person_id.clone()).or_default(),
+                    &(e.unlock_epoch - CONVERSION_DELAY_EPOCHS),
+                    &e.amount_ue,
+                )?;
+                state.pending_conversions.insert(conversion_id, ReplayConversion {
+                    person_id: e.person_id,
+                    amount_ue: e.amount_ue,
+                    amount_bu: e.amount_bu,
+                    rate_index: e.rate_index,
+                    unlock_epoch: e.unlock_epoch,
+                    status: if e.queued { "queued" } else { "pending" }.to_string(),
+                    reserved: e.bu_reserved,
+                });
+            }
+            EventType::ConversionClaimed => {
+                let e: ConversionClaimedEvent = decode(event)?;
+                let conversion = state.pending_conversions.get_mut(&e.conversion_id)
+                    .ok_or_else(|| UBIError::Other(format!(
+                        "Event {}: unknown conversion {}", event.id, e.conversion_id
+                    )))?;
+                
+                conversion.status = "claimed".to_string();
+                let source = if conversion.reserved { Account::Reserved } else { Account::Treasury };
+                post(state, &JournalEntry::new().transfer(
+                    Unit::BU,
+                    source,
+                    Account::Wallet(e.wallet_address),
+                    parse(&e.amount_bu)?,
+                ))?;
+            }
+            EventType::ConversionCancelled => {
+                let e: ConversionCancelledEvent = decode(event)?;
+                let conversion = state.pending_conversions.get_mut(&e.conversion_id)
+                    .ok_or_else(|| UBIError::Other(format!(
+                        "Event {}: unknown conversion {}", event.id, e.conversion_id
+                    )))?;
+                conversion.status = "cancelled".to_string();
+                let reserved_bu = if conversion.reserved { parse(&conversion.amount_bu)? } else { Decimal::ZERO };
+                
+                let amount_ue = parse(&e.amount_ue)?;
+                let fee_ue = parse(&e.fee_ue)?;
+                let mut entry = JournalEntry::new()
+                    .transfer(Unit::UE, Account::Burn, Account::Wallet(e.wallet_address.clone()), amount_ue - fee_ue)
+                    .transfer(Unit::BU, Account::Reserved, Account::Treasury, reserved_bu);
+                if e.fee_refunded {
+                    entry = entry.transfer(Unit::UE, Account::Fees, Account::Wallet(e.wallet_address), fee_ue);
+                }
+                post(state, &entry)?;
+                credit(
+                    state.converted_this_epoch.entry(e.person_id).or_default(),
+                    &e.cap_epoch,
+                    &(-amount_ue).to_string(),
+                )?;
+            }
+            EventType::ConversionUnlocked => {
+                let e: ConversionUnlockedEvent = decode(event)?;
+                let conversion = state.pending_conversions.get_mut(&e.conversion_id)
+                    .ok_or_else(|| UBIError::Other(format!(
+                        "Event {}: unknown conversion {}", event.id, e.conversion_id
+                    )))?;
+                conversion.status = "unlocked".to_string();
+            }
+            EventType::ConversionRationed => {
+                let e: ConversionRationedEvent = decode(event)?;
+                let conversion = state.pending_conversions.get_mut(&e.conversion_id)
+                    .ok_or_else(|| UBIError::Other(format!(
+                        "Event {}: unknown conversion {}", event.id, e.conversion_id
+                    )))?;
+                
+                // A conversion filled with nothing keeps its amounts as a record of the request
+                let filled_bu = parse(&e.filled_bu)?;
+                let refund_ue = parse(&e.refund_ue)?;
+                let fee_refund_ue = parse(&e.fee_refund_ue)?;
+                let kept_ue = parse(&conversion.amount_ue)? - refund_ue;
+                if kept_ue.is_zero() {
+                    conversion.status = "refunded".to_string();
+                } else {
+                    conversion.amount_ue = kept_ue.to_string();
+                    conversion.amount_bu = e.filled_bu;
+                    conversion.status = "pending".to_string();
+                    conversion.reserved = true;
+                }
+                
+                post(state, &JournalEntry::new()
+                    .transfer(Unit::UE, Account::Burn, Account::Wallet(e.wallet_address.clone()), refund_ue - fee_refund_ue)
+                    .transfer(Unit::UE, Account::Fees, Account::Wallet(e.wallet_address), fee_refund_ue)
+                    .transfer(Unit::BU, Account::Treasury, Account::Reserved, filled_bu))?;
+                credit(
+                    state.converted_this_epoch.entry(e.person_id).or_default(),
+                    &e.epoch,
+                    &(-refund_ue).to_string(),
+                )?;
+            }
+            // Records the treasury side of a claim or fill; the paired journal entry moves the BU
+            EventType::TreasuryDebited => {
+                let _: TreasuryDebitedEvent = decode(event)?;
+            }
+            EventType::WalletReset => {
+                let e: WalletResetEvent = decode(event)?;
+                let epoch = epoch_at(event.created_at.timestamp(), self.genesis_timestamp);
+                
+                // Mirrors RegistryService::reset_wallet: the whole UE balance moves to the new wallet
+                let old_balance = parse(state.ue_balances.get(&e.old_wallet).map_or("0", String::as_str))?;
+                state.ue_balances.entry(e.new_wallet.clone()).or_insert_with(|| "0".to_string());
+                post(state, &JournalEntry::new().transfer(
+                    Unit::UE,
+                    Account::Wallet(e.old_wallet),
+                    Account::Wallet(e.new_wallet.clone()),
+                    old_balance,
+                ))?;
+                
+                let user = state.users.get_mut(&e.person_id)
+                    .ok_or_else(|| unknown_person(event, &e.person_id))?;
+                user.wallet_address = e.new_wallet;
+                user.last_reset_epoch = epoch;
+            }
+            EventType::RateIndexUpdated => {
+                let e: RateIndexUpdatedEvent = decode(event)?;
+                
+                let entry = state.rate_index.entry(e.region_id).or_insert_with(|| ReplayRateIndex {
+                    rate_index_wad: String::new(),
+                    last_epoch: e.epoch,
+                    current_decay_rate_wad: String::new(),
+                    last_decay_update_epoch: e.epoch,
+                });
+                entry.rate_index_wad = e.rate_index;
+                entry.last_epoch = e.epoch;
+                entry.current_decay_rate_wad = e.decay_rate;
+            }
+            EventType::DecayRateUpdated => {
+                let e: DecayRateUpdatedEvent = decode(event)?;
+                let entry = state.rate_index.get_mut(&e.region_id)
+                    .ok_or_else(|| UBIError::Other(format!(
+                        "Event {}: decay update for uninitialized region {}", event.id, e.region_id
+                    )))?;
+                
+                entry.current_decay_rate_wad = e.decay_rate;
+                entry.last_decay_update_epoch = e.epoch;
+            }
+            EventType::OracleDataSubmitted => {
+                let e: OracleDataSubmittedEvent = decode(event)?;
+                
+                state.region_oracle_data.insert(e.region_id, ReplayOracleData {
+                    current_basket_index_wad: e.basket_index_wad,
+                    current_inflation_rate_wad: e.inflation_rate_wad,
+                    last_update_timestamp: e.timestamp,
+                });
+            }
+            // Checkpoints commit to state without changing it
+            EventType::CheckpointCreated => {}
+            // Supply alerts report on state without changing it
+            EventType::SupplyInvariantViolated => {}
+            EventType::ForkCreated => {
+                let e: ForkCreatedEvent = decode(event)?;
+                
+                state.forks.insert(e.parent_head_hash, ReplayFork {
+                    parent_head_event_id: e.parent_head_event_id,
+                    events_imported: e.events_imported,
+                });
+            }
+            EventType::ConstitutionAmended => {
+                let e: ConstitutionAmendedEvent = decode(event)?;
+                
+                state.constitution.insert(e.effective_epoch, ConstitutionParameters {
+                    effective_epoch: e.effective_epoch,
+                    ue_mint_per_epoch: parse(&e.ue_mint_per_epoch)?,
+                    conversion_fee_bps: e.conversion_fee_bps,
+                    conversion_cap_ue: parse(&e.conversion_cap_ue)?,
+                    conversion_delay_epochs: e.conversion_delay_epochs,
+                    cancellation_refunds_fee: e.cancellation_refunds_fee,
+                    rationing_threshold_bu: parse(&e.rationing_threshold_bu)?,
+                });
+            }
+            EventType::UEPaid => {
+                let e: UEPaidEvent = decode(event)?;
+                
+                post(state, &JournalEntry::new().transfer(
+                    Unit::UE,
+                    Account::Wallet(e.from_wallet.clone()),
+                    Account::Wallet(e.to_wallet.clone()),
+                    parse(&e.amount_ue)?,
+                ))?;
+                state.ue_payments.insert(e.payment_id, ReplayPayment {
+                    from_wallet: e.from_wallet,
+                    to_wallet: e.to_wallet,
+                    amount_ue: e.amount_ue,
+                    memo: e.memo,
+                });
+            }
+            EventType::BUTransferred => {
+                let e: BUTransferredEvent = decode(event)?;
+                
+                post(state, &JournalEntry::new().transfer(
+                    Unit::BU,
+                    Account::Wallet(e.from_wallet.clone()),
+                    Account::Wallet(e.to_wallet.clone()),
+                    parse(&e.amount_bu)?,
+                ))?;
+                state.bu_transfers.insert(e.transfer_id, ReplayTransfer {
+                    from_wallet: e.from_wallet,
+                    to_wallet: e.to_wallet,
+                    amount_bu: e.amount_bu,
+                    memo: e.memo,
+                });
+            }
+            EventType::InvoiceCreated => {
+                let e: InvoiceCreatedEvent = decode(event)?;
+                
+                state.invoices.insert(e.invoice_id, ReplayInvoice {
+                    merchant_wallet: e.merchant_wallet,
+                    amount_ue: e.amount_ue,
+                    reference: e.reference,
+                    expiry_epoch: e.expiry_epoch,
+                    status: "open".to_string(),
+                    payment_id: None,
+                });
+            }
+            // The payment itself replays from its UEPaid event
+            EventType::InvoicePaid => {
+                let e: InvoicePaidEvent = decode(event)?;
+                
+                let invoice = state.invoices.get_mut(&e.invoice_id)
+                    .ok_or_else(|| UBIError::Other(format!("Invoice {} paid before it was created", e.invoice_id)))?;
+                invoice.status = "paid".to_string();
+                invoice.payment_id = Some(e.payment_id);
+            }
+            EventType::InvoiceExpired => {
+                let e: InvoiceExpiredEvent = decode(event)?;
+                
+                let invoice = state.invoices.get_mut(&e.invoice_id)
+                    .ok_or_else(|| UBIError::Other(format!("Invoice {} expired before it was created", e.invoice_id)))?;
+                invoice.status = "expired".to_string();
+            }
+            EventType::StandingOrderCreated => {
+                let e: StandingOrderCreatedEvent = decode(event)?;
+                
+                state.standing_orders.insert(e.order_id, ReplayStandingOrder {
+                    person_id: e.person_id,
+                    to_wallet: e.to_wallet,
+                    amount_ue: e.amount_ue,
+                    memo: e.memo,
+                    offset_seconds: e.offset_seconds,
+                    next_epoch: e.first_epoch,
+                    status: "active".to_string(),
+                });
+            }
+            // The payment itself replays from its UEPaid event
+            EventType::StandingOrderExecuted => {
+                let e: StandingOrderExecutedEvent = decode(event)?;
+                
+                standing_order_ran(state, e.order_id, e.execution_id, e.epoch, Some(e.payment_id), None)?;
+            }
+            EventType::StandingOrderSkipped => {
+                let e: StandingOrderSkippedEvent = decode(event)?;
+                
+                standing_order_ran(state, e.order_id, e.execution_id, e.epoch, None, Some(e.reason))?;
+            }
+            EventType::StandingOrderCancelled => {
+                let e: StandingOrderCancelledEvent = decode(event)?;
+                
+                let order = state.standing_orders.get_mut(&e.order_id)
+                    .ok_or_else(|| UBIError::Other(format!("Standing order {} cancelled before it was created", e.order_id)))?;
+                order.status = "cancelled".to_string();
+            }
+            EventType::AllowanceApproved => {
+                let e: AllowanceApprovedEvent = decode(event)?;
+                
+                state.ue_allowances.insert(e.allowance_id, ReplayAllowance {
+                    person_id: e.person_id,
+                    spender_wallet: e.spender_wallet,
+                    amount_per_epoch: e.amount_per_epoch,
+                    status: "active".to_string(),
+                });
+            }
+            EventType::AllowanceRevoked => {
+                let e: AllowanceRevokedEvent = decode(event)?;
+                
+                let allowance = state.ue_allowances.get_mut(&e.allowance_id)
+                    .ok_or_else(|| UBIError::Other(format!("Allowance {} revoked before it was approved", e.allowance_id)))?;
+                allowance.status = "revoked".to_string();
+            }
+            // The payment itself replays from its UEPaid event
+            EventType::AllowanceSpent => {
+                let e: AllowanceSpentEvent = decode(event)?;
+                
+                state.allowance_spends.insert(e.spend_id, ReplayAllowanceSpend {
+                    allowance_id: e.allowance_id,
+                    epoch: e.epoch,
+                    payment_id: e.payment_id,
+                    amount_ue: e.amount_ue,
+                });
+            }
+        }
+        
+        Ok(true)
+    }
+    
+    /// Read the live tables into the same shape as replayed state
+    pub async fn load_live_state(&self) -> Result<ReplayState, UBIError> {
+        let mut conn = self.pool.acquire().await?;
+        read_live_state(&mut conn).await
+    }
+}
+
+/// Read the live tables into the same shape as replayed state
+///
+/// Run inside a REPEATABLE READ transaction for a consistent snapshot
+pub async fn read_live_state(conn: &mut PgConnection) -> Result<ReplayState, UBIError> {
+    let mut state = ReplayState::default();
+    
+    let users = sqlx::query!(
+        "SELECT person_id, wallet_address, region_id, expiry_epoch, last_reset_epoch, is_active FROM users"
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    for row in users {
+        state.users.insert(hex::encode(&row.person_id), ReplayUser {
+            wallet_address: row.wallet_address,
+            region_id: row.region_id,
+            expiry_epoch: row.expiry_epoch,
+            last_reset_epoch: row.last_reset_epoch,
+            is_active: row.is_active,
+        });
+    }
+    
+    let ue_balances = sqlx::query!("SELECT wallet_address, balance FROM ue_balances")
+        .fetch_all(&mut *conn)
+        .await?;
+    for row in ue_balances {
+        state.ue_balances.insert(row.wallet_address, row.balance.to_string());
+    }
+    
+    let bu_balances = sqlx::query!("SELECT wallet_address, balance FROM bu_balances")
+        .fetch_all(&mut *conn)
+        .await?;
+    for row in bu_balances {
+        state.bu_balances.insert(row.wallet_address, row.balance.to_string());
+    }
+    
+    let claims = sqlx::query!("SELECT person_id, epoch, amount_ue FROM ubi_claims")
+        .fetch_all(&mut *conn)
+        .await?;
+    for row in claims {
+        state.ubi_claims
+            .entry(hex::encode(&row.person_id))
+            .or_default()
+            .insert(row.epoch, row.amount_ue.to_string());
+    }
+    
+    let last_claimed = sqlx::query!("SELECT person_id, region_id, epoch FROM last_claimed_epoch")
+        .fetch_all(&mut *conn)
+        .await?;
+    for row in last_claimed {
+        state.last_claimed_epoch
+            .entry(hex::encode(&row.person_id))
+            .or_default()
+            .insert(row.region_id, row.epoch);
+    }
+    
+    let conversions = sqlx::query!(
+        "SELECT id, person_id, amount_ue, amount_bu, rate_index, unlock_epoch, status, reserved FROM pending_conversions"
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    for row in conversions {
+        state.pending_conversions.insert(row.id, ReplayConversion {
+            person_id: hex::encode(&row.person_id),
+            amount_ue: row.amount_ue.to_string(),
+            amount_bu: row.amount_bu.to_string(),
+            rate_index: row.rate_index.to_string(),
+            unlock_epoch: row.unlock_epoch,
+            status: row.status,
+            reserved: row.reserved,
+        });
+    }
+    
+    let converted = sqlx::query!("SELECT person_id, epoch, amount_ue FROM converted_this_epoch")
+        .fetch_all(&mut *conn)
+        .await?;
+    for row in converted {
+        state.converted_this_epoch
+            .entry(hex::encode(&row.person_id))
+            .or_default()
+            .insert(row.epoch, row.amount_ue.to_string());
+    }
+    
+    let rate_indexes = sqlx::query!(
+        "SELECT region_id, rate_index_wad, last_epoch, current_decay_rate_wad, last_decay_update_epoch FROM rate_index"
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    for row in rate_indexes {
+        state.rate_index.insert(row.region_id, ReplayRateIndex {
+            rate_index_wad: row.rate_index_wad.to_string(),
+            last_epoch: row.last_epoch,
+            current_decay_rate_wad: row.current_decay_rate_wad.to_string(),
+            last_decay_update_epoch: row.last_decay_update_epoch,
+        });
+    }
+    
+    let oracle_data = sqlx::query!(
+        "SELECT region_id, current_basket_index_wad, current_inflation_rate_wad, last_update_timestamp FROM region_oracle_data"
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    for row in oracle_data {
+        state.region_oracle_data.insert(row.region_id, ReplayOracleData {
+            current_basket_index_wad: row.current_basket_index_wad.to_string(),
+            current_inflation_rate_wad: row.current_inflation_rate_wad.to_string(),
+            last_update_timestamp: row.last_update_timestamp,
+        });
+    }
+    
+    let treasury = sqlx::query!(
+        "SELECT balance_bu, reserved_bu FROM treasury ORDER BY id DESC LIMIT 1"
+    )
+    .fetch_optional(&mut *conn)
+    .await?;
+    let (treasury_bu, treasury_reserved_bu) = treasury
+        .map_or((Decimal::ZERO, Decimal::ZERO), |row| (row.balance_bu, row.reserved_bu));
+    state.treasury_bu = treasury_bu.to_string();
+    state.treasury_reserved_bu = treasury_reserved_bu.to_string();
+    
+    let constitution = sqlx::query_as!(
+        ConstitutionParameters,
+        r#"
+        SELECT effective_epoch, ue_mint_per_epoch, conversion_fee_bps, conversion_cap_ue, conversion_delay_epochs,
+               cancellation_refunds_fee, rationing_threshold_bu
+        FROM constitution_parameters
+        "#
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    for row in constitution {
+        state.constitution.insert(row.effective_epoch, row);
+    }
+    
+    let forks = sqlx::query!("SELECT parent_head_hash, parent_head_event_id, events_imported FROM fork_genesis")
+        .fetch_all(&mut *conn)
+        .await?;
+    for row in forks {
+        state.forks.insert(row.parent_head_hash, ReplayFork {
+            parent_head_event_id: row.parent_head_event_id,
+            events_imported: row.events_imported,
+        });
+    }
+    
+    let payments = sqlx::query!("SELECT id, from_wallet, to_wallet, amount_ue, memo FROM ue_payments")
+        .fetch_all(&mut *conn)
+        .await?;
+    for row in payments {
+        state.ue_payments.insert(row.id, ReplayPayment {
+            from_wallet: row.from_wallet,
+            to_wallet: row.to_wallet,
+            amount_ue: row.amount_ue.to_string(),
+            memo: row.memo,
+        });
+    }
+    
+    let transfers = sqlx::query!("SELECT id, from_wallet, to_wallet, amount_bu, memo FROM bu_transfers")
+        .fetch_all(&mut *conn)
+        .await?;
+    for row in transfers {
+        state.bu_transfers.insert(row.id, ReplayTransfer {
+            from_wallet: row.from_wallet,
+            to_wallet: row.to_wallet,
+            amount_bu: row.amount_bu.to_string(),
+            memo: row.memo,
+        });
+    }
+    
+    let invoices = sqlx::query!(
+        "SELECT id, merchant_wallet, amount_ue, reference, expiry_epoch, status, payment_id FROM invoices"
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    for row in invoices {
+        state.invoices.insert(row.id, ReplayInvoice {
+            merchant_wallet: row.merchant_wallet,
+            amount_ue: row.amount_ue.to_string(),
+            reference: row.reference,
+            expiry_epoch: row.expiry_epoch,
+            status: row.status,
+            payment_id: row.payment_id,
+        });
+    }
+    
+    let standing_orders = sqlx::query!(
+        "SELECT id, person_id, to_wallet, amount_ue, memo, offset_seconds, next_epoch, status FROM standing_orders"
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    for row in standing_orders {
+        state.standing_orders.insert(row.id, ReplayStandingOrder {
+            person_id: hex::encode(&row.person_id),
+            to_wallet: row.to_wallet,
+            amount_ue: row.amount_ue.to_string(),
+            memo: row.memo,
+            offset_seconds: row.offset_seconds,
+            next_epoch: row.next_epoch,
+            status: row.status,
+        });
+    }
+    
+    let executions = sqlx::query!(
+        "SELECT id, order_id, epoch, payment_id, skip_reason FROM standing_order_executions"
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    for row in executions {
+        state.standing_order_executions.insert(row.id, ReplayStandingOrderExecution {
+            order_id: row.order_id,
+            epoch: row.epoch,
+            payment_id: row.payment_id,
+            skip_reason: row.skip_reason,
+        });
+    }
+    
+    let allowances = sqlx::query!(
+        "SELECT id, person_id, spender_wallet, amount_per_epoch, status FROM ue_allowances"
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    for row in allowances {
+        state.ue_allowances.insert(row.id, ReplayAllowance {
+            person_id: hex::encode(&row.person_id),
+            spender_wallet: row.spender_wallet,
+            amount_per_epoch: row.amount_per_epoch.to_string(),
+            status: row.status,
+        });
+    }
+    
+    let spends = sqlx::query!(
+        "SELECT id, allowance_id, epoch, payment_id, amount_ue FROM allowance_spends"
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    for row in spends {
+        state.allowance_spends.insert(row.id, ReplayAllowanceSpend {
+            allowance_id: row.allowance_id,
+            epoch: row.epoch,
+            payment_id: row.payment_id,
+            amount_ue: row.amount_ue.to_string(),
+        });
+    }
+    
+    let ledger = sqlx::query!(
+        r#"SELECT unit, account, SUM(amount)::TEXT AS "balance!" FROM journal_postings GROUP BY unit, account"#
+    )
+    .fetch_all(&mut *conn)
+    .await?;
+    for row in ledger {
+        state.ledger.insert(format!("{}|{}", row.unit, row.account), row.balance);
+    }
+    
+    Ok(state)
+}
+
+/// Write replayed state into freshly migrated tables (fork bootstrap)
+///
+/// Users are written without MFA secrets; they re-enroll on the fork
+pub async fn write_state(conn: &mut PgConnection, state: &ReplayState) -> Result<(), UBIError> {
+    for (person_id, user) in &state.users {
+        let person_id = hex::decode(person_id).map_err(|_| UBIError::InvalidPersonId)?;
+        sqlx::query!(
+            r#"
+            INSERT INTO users (person_id, wallet_address, region_id, expiry_epoch, last_reset_epoch, is_active)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            "#,
+            person_id,
+            &user.wallet_address,
+            user.region_id,
+            user.expiry_epoch,
+            user.last_reset_epoch,
+            user.is_active
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+    
+    for (wallet, balance) in &state.ue_balances {
+        sqlx::query!(
+            "INSERT INTO ue_balances (wallet_address, balance) VALUES ($1, $2)",
+            wallet,
+            parse(balance)?
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+    
+    for (wallet, balance) in &state.bu_balances {
+        sqlx::query!(
+            "INSERT INTO bu_balances (wallet_address, balance) VALUES ($1, $2)",
+            wallet,
+            parse(balance)?
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+    
+    for (person_id, claims) in &state.ubi_claims {
+        let person_id = hex::decode(person_id).map_err(|_| UBIError::InvalidPersonId)?;
+        for (epoch, amount) in claims {
+            sqlx::query!(
+                "INSERT INTO ubi_claims (person_id, epoch, amount_ue) VALUES ($1, $2, $3)",
+                &person_id,
+                epoch,
+                parse(amount)?
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+    }
+    
+    for (person_id, regions) in &state.last_claimed_epoch {
+        let person_id = hex::decode(person_id).map_err(|_| UBIError::InvalidPersonId)?;
+        for (region_id, epoch) in regions {
+            sqlx::query!(
+                "INSERT INTO last_claimed_epoch (person_id, region_id, epoch) VALUES ($1, $2, $3)",
+                &person_id,
+                region_id,
+                epoch
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+    }
+    
+    for (id, conversion) in &state.pending_conversions {
+        let person_id = hex::decode(&conversion.person_id).map_err(|_| UBIError::InvalidPersonId)?;
+        sqlx::query!(
+            r#"
+            INSERT INTO pending_conversions (id, person_id, amount_ue, amount_bu, rate_index, unlock_epoch, status, reserved)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            id,
+            person_id,
+            parse(&conversion.amount_ue)?,
+            parse(&conversion.amount_bu)?,
+            parse(&conversion.rate_index)?,
+            conversion.unlock_epoch,
+            &conversion.status,
+            conversion.reserved
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+    sqlx::query!(
+        "SELECT setval('pending_conversions_id_seq', GREATEST((SELECT MAX(id) FROM pending_conversions), 1))"
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    
+    for (person_id, epochs) in &state.converted_this_epoch {
+        let person_id = hex::decode(person_id).map_err(|_| UBIError::InvalidPersonId)?;
+        for (epoch, amount) in epochs {
+            sqlx::query!(
+                "INSERT INTO converted_this_epoch (person_id, epoch, amount_ue) VALUES ($1, $2, $3)",
+                &person_id,
+                epoch,
+                parse(amount)?
+            )
+            .execute(&mut *conn)
+            .await?;
+        }
+    }
+    
+    for (region_id, rate) in &state.rate_index {
+        sqlx::query!(
+            r#"
+            INSERT INTO rate_index (region_id, rate_index_wad, last_epoch, current_decay_rate_wad, last_decay_update_epoch)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            region_id,
+            parse(&rate.rate_index_wad)?,
+            rate.last_epoch,
+            parse(&rate.current_decay_rate_wad)?,
+            rate.last_decay_update_epoch
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+    
+    for (region_id, oracle) in &state.region_oracle_data {
+        sqlx::query!(
+            r#"
+            INSERT INTO region_oracle_data (region_id, current_basket_index_wad, current_inflation_rate_wad, last_update_timestamp)
+            VALUES ($1, $2, $3, $4)
+            "#,
+            region_id,
+            parse(&oracle.current_basket_index_wad)?,
+            parse(&oracle.current_inflation_rate_wad)?,
+            oracle.last_update_timestamp
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+    
+    // The latest treasury row is the live balance
+    sqlx::query!(
+        "INSERT INTO treasury (balance_bu, reserved_bu) VALUES ($1, $2)",
+        parse(&state.treasury_bu)?,
+        parse(&state.treasury_reserved_bu)?
+    )
+    .execute(&mut *conn)
+    .await?;
+    
+    for parameters in state.constitution.values() {
+        sqlx::query!(
+            r#"
+            INSERT INTO constitution_parameters
+                (effective_epoch, ue_mint_per_epoch, conversion_fee_bps, conversion_cap_ue, conversion_delay_epochs,
+                 cancellation_refunds_fee, rationing_threshold_bu)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            parameters.effective_epoch,
+            parameters.ue_mint_per_epoch,
+            parameters.conversion_fee_bps,
+            parameters.conversion_cap_ue,
+            parameters.conversion_delay_epochs,
+            parameters.cancellation_refunds_fee,
+            parameters.rationing_threshold_bu
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+    
+    for (parent_head_hash, fork) in &state.forks {
+        sqlx::query!(
+            "INSERT INTO fork_genesis (parent_head_hash, parent_head_event_id, events_imported) VALUES ($1, $2, $3)",
+            parent_head_hash,
+            fork.parent_head_event_id,
+            fork.events_imported
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+    
+    for (id, payment) in &state.ue_payments {
+        sqlx::query!(
+            "INSERT INTO ue_payments (id, from_wallet, to_wallet, amount_ue, memo) VALUES ($1, $2, $3, $4, $5)",
+            id,
+            &payment.from_wallet,
+            &payment.to_wallet,
+            parse(&payment.amount_ue)?,
+            payment.memo.as_deref()
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+    sqlx::query!(
+        "SELECT setval('ue_payments_id_seq', GREATEST((SELECT MAX(id) FROM ue_payments), 1))"
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    
+    for (id, transfer) in &state.bu_transfers {
+        sqlx::query!(
+            "INSERT INTO bu_transfers (id, from_wallet, to_wallet, amount_bu, memo) VALUES ($1, $2, $3, $4, $5)",
+            id,
+            &transfer.from_wallet,
+            &transfer.to_wallet,
+            parse(&transfer.amount_bu)?,
+            transfer.memo.as_deref()
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+    sqlx::query!(
+        "SELECT setval('bu_transfers_id_seq', GREATEST((SELECT MAX(id) FROM bu_transfers), 1))"
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    
+    for (id, invoice) in &state.invoices {
+        sqlx::query!(
+            r#"
+            INSERT INTO invoices (id, merchant_wallet, amount_ue, reference, expiry_epoch, status, payment_id)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+            "#,
+            id,
+            &invoice.merchant_wallet,
+            parse(&invoice.amount_ue)?,
+            &invoice.reference,
+            invoice.expiry_epoch,
+            &invoice.status,
+            invoice.payment_id
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+    sqlx::query!(
+        "SELECT setval('invoices_id_seq', GREATEST((SELECT MAX(id) FROM invoices), 1))"
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    
+    for (id, order) in &state.standing_orders {
+        let person_id = hex::decode(&order.person_id).map_err(|_| UBIError::InvalidPersonId)?;
+        sqlx::query!(
+            r#"
+            INSERT INTO standing_orders (id, person_id, to_wallet, amount_ue, memo, offset_seconds, next_epoch, status)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            id,
+            person_id,
+            &order.to_wallet,
+            parse(&order.amount_ue)?,
+            order.memo.as_deref(),
+            order.offset_seconds,
+            order.next_epoch,
+            &order.status
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+    sqlx::query!(
+        "SELECT setval('standing_orders_id_seq', GREATEST((SELECT MAX(id) FROM standing_orders), 1))"
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    
+    for (id, execution) in &state.standing_order_executions {
+        sqlx::query!(
+            r#"
+            INSERT INTO standing_order_executions (id, order_id, epoch, payment_id, skip_reason)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            id,
+            execution.order_id,
+            execution.epoch,
+            execution.payment_id,
+            execution.skip_reason.as_deref()
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+    sqlx::query!(
+        "SELECT setval('standing_order_executions_id_seq', GREATEST((SELECT MAX(id) FROM standing_order_executions), 1))"
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    
+    for (id, allowance) in &state.ue_allowances {
+        let person_id = hex::decode(&allowance.person_id).map_err(|_| UBIError::InvalidPersonId)?;
+        sqlx::query!(
+            r#"
+            INSERT INTO ue_allowances (id, person_id, spender_wallet, amount_per_epoch, status)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            id,
+            person_id,
+            &allowance.spender_wallet,
+            parse(&allowance.amount_per_epoch)?,
+            &allowance.status
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+    sqlx::query!(
+        "SELECT setval('ue_allowances_id_seq', GREATEST((SELECT MAX(id) FROM ue_allowances), 1))"
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    
+    for (id, spend) in &state.allowance_spends {
+        sqlx::query!(
+            r#"
+            INSERT INTO allowance_spends (id, allowance_id, epoch, payment_id, amount_ue)
+            VALUES ($1, $2, $3, $4, $5)
+            "#,
+            id,
+            spend.allowance_id,
+            spend.epoch,
+            spend.payment_id,
+            parse(&spend.amount_ue)?
+        )
+        .execute(&mut *conn)
+        .await?;
+    }
+    sqlx::query!(
+        "SELECT setval('allowance_spends_id_seq', GREATEST((SELECT MAX(id) FROM allowance_spends), 1))"
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    
+    // The journal opens with the replayed account balances; the tables above are its projections
+    let mut accounts = Vec::new();
+    let mut units = Vec::new();
+    let mut amounts = Vec::new();
+    for (key, balance) in &state.ledger {
+        if parse(balance)?.is_zero() {
+            continue;
+        }
+        let (unit, account) = key.split_once('|')
+            .ok_or_else(|| UBIError::Other(format!("Invalid ledger key: {}", key)))?;
+        units.push(unit.to_string());
+        accounts.push(account.to_string());
+        amounts.push(balance.clone());
+    }
+    // Migrations open the journal with the seeded treasury; the replayed balances
+    // already hold it, so their entry replaces that one rather than adding to it
+    sqlx::query!("TRUNCATE journal_postings, journal_entries RESTART IDENTITY")
+        .execute(&mut *conn)
+        .await?;
+    let entry_id = sqlx::query_scalar!(
+        "INSERT INTO journal_entries (description) VALUES ('Opening balances') RETURNING id"
+    )
+    .fetch_one(&mut *conn)
+    .await?;
+    sqlx::query!(
+        r#"
+        INSERT INTO journal_postings (entry_id, account, unit, amount)
+        SELECT $1, account, unit, amount::NUMERIC
+        FROM UNNEST($2::TEXT[], $3::TEXT[], $4::TEXT[]) AS p(account, unit, amount)
+        "#,
+        entry_id,
+        &accounts,
+        &units,
+        &amounts
+    )
+    .execute(&mut *conn)
+    .await?;
+    
+    Ok(())
+}
+
+/// Compare replayed state with live state, table by table
+pub fn diff_states(replayed: &ReplayState, live: &ReplayState) -> Result<Vec<Divergence>, UBIError> {
+    let mut out = Vec::new();
+    
+    diff_rows("users", &replayed.users, &live.users, &mut out);
+    diff_amounts("ue_balances", &replayed.ue_balances, &live.ue_balances, &mut out)?;
+    diff_amounts("bu_balances", &replayed.bu_balances, &live.bu_balances, &mut out)?;
+    diff_rows("ubi_claims", &replayed.ubi_claims, &live.ubi_claims, &mut out);
+    diff_rows("last_claimed_epoch", &replayed.last_claimed_epoch, &live.last_claimed_epoch, &mut out);
+    diff_rows("pending_conversions", &replayed.pending_conversions, &live.pending_conversions, &mut out);
+    diff_rows("converted_this_epoch", &replayed.converted_this_epoch, &live.converted_this_epoch, &mut out);
+    diff_rows("rate_index", &replayed.rate_index, &live.rate_index, &mut out);
+    diff_rows("region_oracle_data", &replayed.region_oracle_data, &live.region_oracle_data, &mut out);
+    diff_rows("constitution_parameters", &replayed.constitution, &live.constitution, &mut out);
+    diff_rows("fork_genesis", &replayed.forks, &live.forks, &mut out);
+    diff_rows("ue_payments", &replayed.ue_payments, &live.ue_payments, &mut out);
+    diff_rows("bu_transfers", &replayed.bu_transfers, &live.bu_transfers, &mut out);
+    diff_rows("invoices", &replayed.invoices, &live.invoices, &mut out);
+    diff_rows("standing_orders", &replayed.standing_orders, &live.standing_orders, &mut out);
+    diff_rows(
+        "standing_order_executions",
+        &replayed.standing_order_executions,
+        &live.standing_order_executions,
+        &mut out,
+    );
+    diff_rows("ue_allowances", &replayed.ue_allowances, &live.ue_allowances, &mut out);
+    diff_rows("allowance_spends", &replayed.allowance_spends, &live.allowance_spends, &mut out);
+    diff_amounts("journal_postings", &nonzero(&replayed.ledger)?, &nonzero(&live.ledger)?, &mut out)?;
+    
+    if parse(&replayed.treasury_bu)? != parse(&live.treasury_bu)? {
+        out.push(Divergence {
+            table: "treasury".to_string(),
+            key: "balance_bu".to_string(),
+            replayed: Some(replayed.treasury_bu.clone()),
+            live: Some(live.treasury_bu.clone()),
+        });
+    }
+    if parse(&replayed.treasury_reserved_bu)? != parse(&live.treasury_reserved_bu)? {
+        out.push(Divergence {
+            table: "treasury".to_string(),
+            key: "reserved_bu".to_string(),
+            replayed: Some(replayed.treasury_reserved_bu.clone()),
+            live: Some(live.treasury_reserved_bu.clone()),
+        });
+    }
+    
+    Ok(out)
+}
+
+/// Diff two keyed tables row by row
+fn diff_rows<K, V>(
+    table: &str,
+    replayed: &BTreeMap<K, V>,
+    live: &BTreeMap<K, V>,
+    out: &mut Vec<Divergence>,
+) where
+    K: Ord + ToString,
+    V: PartialEq + Serialize,
+{
+    let render = |v: &V| serde_json::to_string(v).unwrap_or_default();
+    
+    for (key, value) in replayed {
+        match live.get(key) {
+            Some(live_value) if live_value == value => {}
+            live_value => out.push(Divergence {
+                table: table.to_string(),
+                key: key.to_string(),
+                replayed: Some(render(value)),
+                live: live_value.map(render),
+            }),
+        }
+    }
+    
+    for (key, value) in live {
+        if !replayed.contains_key(key) {
+            out.push(Divergence {
+                table: table.to_string(),
+                key: key.to_string(),
+                replayed: None,
+                live: Some(render(value)),
+            });
+        }
+    }
+}
+
+/// Diff two balance tables numerically, so "0" and "0.0" agree
+fn diff_amounts(
+    table: &str,
+    replayed: &BTreeMap<String, String>,
+    live: &BTreeMap<String, String>,
+    out: &mut Vec<Divergence>,
+) -> Result<(), UBIError> {
+    for (key, value) in replayed {
+        let matches = match live.get(key) {
+            Some(live_value) => parse(live_value)? == parse(value)?,
+            None => false,
+        };
+        if !matches {
+            out.push(Divergence {
+                table: table.to_string(),
+                key: key.clone(),
+                replayed: Some(value.clone()),
+                live: live.get(key).cloned(),
+            });
+        }
+    }
+    
+    for (key, value) in live {
+        if !replayed.contains_key(key) {
+            out.push(Divergence {
+                table: table.to_string(),
+                key: key.clone(),
+                replayed: None,
+                live: Some(value.clone()),
+            });
+        }
+    }
+    
+    Ok(())
+}
+
+/// Drop zero balances, which the journal only records for accounts that have moved
+fn nonzero(balances: &BTreeMap<String, String>) -> Result<BTreeMap<String, String>, UBIError> {
+    let mut out = BTreeMap::new();
+    for (key, balance) in balances {
+        if !parse(balance)?.is_zero() {
+            out.insert(key.clone(), balance.clone());
+        }
+    }
+    Ok(out)
+}
+
+fn decode<P: EventPayload>(event: &Event) -> Result<P, UBIError> {
+    event.decode()
+        .map_err(|e| UBIError::Other(format!("Event {} ({}): {}", event.id, event.event_type, e)))
+}
+
+fn unknown_person(event: &Event, person_id: &str) -> UBIError {
+    UBIError::Other(format!("Event {}: unknown person {}", event.id, person_id))
+}
+
+pub(crate) fn parse(amount: &str) -> Result<Decimal, UBIError> {
+    amount.parse()
+        .map_err(|_| UBIError::Other(format!("Invalid amount: {}", amount)))
+}
+
+fn credit<K: Ord + Clone>(balances: &mut BTreeMap<K, String>, key: &K, amount: &str) -> Result<(), UBIError> {
+    let current = balances.get(key).map(String::as_str).unwrap_or("0");
+    let updated = (parse(current)? + parse(amount)?).to_string();
+    balances.insert(key.clone(), updated);
+    Ok(())
+}
+
+/// Apply a journal entry to the replayed ledger and its projections
+///
+/// Mirrors journal::post_entry
+fn post(state: &mut ReplayState, entry: &JournalEntry) -> Result<(), UBIError> {
+    if !entry.is_balanced() {
+        return Err(UBIError::Other("Unbalanced journal entry".to_string()));
+    }
+    
+    for posting in &entry.postings {
+        let amount = posting.amount.to_string();
+        credit(&mut state.ledger, &ledger_key(posting.unit, &posting.account), &amount)?;
+        match (&posting.account, posting.unit) {
+            (Account::Wallet(wallet), Unit::UE) => credit(&mut state.ue_balances, wallet, &amount)?,
+            (Account::Wallet(wallet), Unit::BU) => credit(&mut state.bu_balances, wallet, &amount)?,
+            (Account::Treasury, Unit::BU) => {
+                state.treasury_bu = (parse(&state.treasury_bu)? + posting.amount).to_string();
+            }
+            (Account::Reserved, Unit::BU) => {
+                state.treasury_reserved_bu = (parse(&state.treasury_reserved_bu)? + posting.amount).to_string();
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
+
+
+/// Record one run of a standing order and move it on to the next epoch
+fn standing_order_ran(
+    state: &mut ReplayState,
+    order_id: i64,
+    execution_id: i64,
+    epoch: i32,
+    payment_id: Option<i64>,
+    skip_reason: Option<String>,
+) -> Result<(), UBIError> {
+    let order = state.standing_orders.get_mut(&order_id)
+        .ok_or_else(|| UBIError::Other(format!("Standing order {} ran before it was created", order_id)))?;
+    order.next_epoch = epoch + 1;
+    state.standing_order_executions.insert(execution_id, ReplayStandingOrderExecution {
+        order_id,
+        epoch,
+        payment_id,
+        skip_reason,
+    });
+    Ok(())
+}
+
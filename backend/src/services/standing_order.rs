@@ -0,0 +1,369 @@
+//! Standing order service
+//!
+//! Recurring UE payments from a person to a fixed wallet. An order runs
+//! once per epoch, when its chosen point in the epoch has passed and the
+//! payer has claimed UBI for that epoch, so it pays out of the fresh UE.
+//! If the payer makes no claim, it runs once the epoch is over. A run
+//! that cannot pay is skipped with its reason recorded, and so is one for
+//! an epoch that closed before the last, so downtime never charges a
+//! payer for several epochs at once.
+
+use crate::models::standing_order::{StandingOrder, StandingOrderExecution, StandingOrderRequest};
+use crate::services::registry::RegistryService;
+use crate::services::payment::{check_transfer_amount, send_payment, PAYMENT_MEMO_MAX_CHARS};
+use crate::utils::{epoch::{current_epoch, epoch_start_timestamp}, errors::UBIError};
+use crate::constants::EPOCH_LENGTH_SECONDS;
+use crate::events::{
+    emit_event, lock_event_chain, StandingOrderCancelledEvent, StandingOrderCreatedEvent,
+    StandingOrderExecutedEvent, StandingOrderSkippedEvent,
+};
+use sqlx::{Connection, PgPool};
+use std::time::Duration;
+use log::{error, info};
+use hex;
+
+/// Orders run per worker pass
+const STANDING_ORDER_BATCH_SIZE: usize = 100;
+
+/// How often the worker looks for due orders
+const STANDING_ORDER_POLL_INTERVAL: Duration = Duration::from_secs(60);
+
+pub struct StandingOrderService {
+    pool: PgPool,
+    registry: RegistryService,
+    genesis_timestamp: i64,
+}
+
+impl StandingOrderService {
+    pub fn new(pool: PgPool, registry: RegistryService, genesis_timestamp: i64) -> Self {
+        Self {
+            pool,
+            registry,
+            genesis_timestamp,
+        }
+    }
+    
+    /// Run due orders until the process exits
+    pub async fn run_worker(self) {
+        loop {
+            match self.execute_due(None, STANDING_ORDER_BATCH_SIZE).await {
+                // A full batch means more are probably waiting
+                Ok(executed) if executed == STANDING_ORDER_BATCH_SIZE => continue,
+                Ok(_) => {}
+                Err(e) => error!("Standing order pass failed: {}", e),
+            }
+            tokio::time::sleep(STANDING_ORDER_POLL_INTERVAL).await;
+        }
+    }
+    
+    /// Set up a standing order paying from `wallet`'s person
+    pub async fn create(&self, wallet: &str, req: StandingOrderRequest) -> Result<StandingOrder, UBIError> {
+        let to_wallet = req.to_wallet.trim();
+        if to_wallet.is_empty() {
+            return Err(UBIError::InvalidTransfer("Recipient wallet is required".to_string()));
+        }
+        if to_wallet == wallet {
+            return Err(UBIError::InvalidTransfer("Cannot pay your own wallet".to_string()));
+        }
+        let amount = check_transfer_amount(req.amount_ue)?;
+        let memo = req.memo
+            .map(|memo| memo.trim().to_string())
+            .filter(|memo| !memo.is_empty());
+        if memo.as_ref().is_some_and(|memo| memo.chars().count() > PAYMENT_MEMO_MAX_CHARS) {
+            return Err(UBIError::InvalidTransfer(
+                format!("Memo longer than {} characters", PAYMENT_MEMO_MAX_CHARS)
+            ));
+        }
+        if req.offset_seconds < 0 || req.offset_seconds as i64 >= EPOCH_LENGTH_SECONDS {
+            return Err(UBIError::InvalidTransfer(
+                format!("Offset must be between 0 and {} seconds", EPOCH_LENGTH_SECONDS - 1)
+            ));
+        }
+        
+        let payer = self.registry
+            .get_user_by_wallet(wallet)
+            .await?
+            .filter(|user| user.is_active)
+            .ok_or(UBIError::WalletNotActive(wallet.to_string()))?;
+        
+        // The first run is this epoch's
+        let epoch = current_epoch(self.genesis_timestamp);
+        
+        // Start transaction
+        let mut tx = self.pool.begin().await?;
+        lock_event_chain(&mut *tx).await?;
+        
+        let order = sqlx::query_as!(
+            StandingOrder,
+            r#"
+            INSERT INTO standing_orders (person_id, to_wallet, amount_ue, memo, offset_seconds, next_epoch)
+            VALUES ($1, $2, $3, $4, $5, $6)
+            RETURNING id, person_id, to_wallet, amount_ue, memo, offset_seconds, next_epoch, status, created_at
+            "#,
+            payer.person_id.as_slice(),
+            to_wallet,
+            amount,
+            memo,
+            req.offset_seconds,
+            epoch
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        
+        // Emit event
+        emit_event(&mut *tx, &StandingOrderCreatedEvent {
+            order_id: order.id,
+            person_id: hex::encode(&order.person_id),
+            to_wallet: order.to_wallet.clone(),
+            amount_ue: order.amount_ue.to_string(),
+            memo: order.memo.clone(),
+            offset_seconds: order.offset_seconds,
+            first_epoch: order.next_epoch,
+        }).await?;
+        
+        // Commit transaction
+        tx.commit().await?;
+        
+        info!("Standing order {} created: {} UE per epoch from {} to {}",
+              order.id, order.amount_ue, wallet, order.to_wallet);
+        
+        Ok(order)
+    }
+    
+    /// Standing orders of `wallet`'s person, newest first
+    pub async fn list(&self, wallet: &str) -> Result<Vec<StandingOrder>, UBIError> {
+        let user = self.registry
+            .get_user_by_wallet(wallet)
+            .await?
+            .ok_or(UBIError::WalletNotActive(wallet.to_string()))?;
+        
+        let orders = sqlx::query_as!(
+            StandingOrder,
+            r#"
+            SELECT id, person_id, to_wallet, amount_ue, memo, offset_seconds, next_epoch, status, created_at
+            FROM standing_orders
+            WHERE person_id = $1
+            ORDER BY id DESC
+            "#,
+            user.person_id.as_slice()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(orders)
+    }
+    
+    /// Runs of one of `wallet`'s person's standing orders, oldest first
+    pub async fn executions(&self, wallet: &str, order_id: i64) -> Result<Vec<StandingOrderExecution>, UBIError> {
+        let user = self.registry
+            .get_user_by_wallet(wallet)
+            .await?
+            .ok_or(UBIError::WalletNotActive(wallet.to_string()))?;
+        
+        let executions = sqlx::query_as!(
+            StandingOrderExecution,
+            r#"
+            SELECT x.id, x.order_id, x.epoch, x.payment_id, x.skip_reason, x.created_at
+            FROM standing_order_executions x
+            JOIN standing_orders o ON o.id = x.order_id
+            WHERE x.order_id = $1 AND o.person_id = $2
+            ORDER BY x.epoch
+            "#,
+            order_id,
+            user.person_id.as_slice()
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(executions)
+    }
+    
+    /// Cancel one of `wallet`'s person's standing orders
+    pub async fn cancel(&self, wallet: &str, order_id: i64) -> Result<(), UBIError> {
+        let user = self.registry
+            .get_user_by_wallet(wallet)
+            .await?
+            .ok_or(UBIError::WalletNotActive(wallet.to_string()))?;
+        
+        // Start transaction
+        let mut tx = self.pool.begin().await?;
+        lock_event_chain(&mut *tx).await?;
+        
+        let cancelled = sqlx::query_scalar!(
+            r#"
+            UPDATE standing_orders SET status = 'cancelled'
+            WHERE id = $1 AND person_id = $2 AND status = 'active'
+            RETURNING id
+            "#,
+            order_id,
+            user.person_id.as_slice()
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        
+        if cancelled.is_none() {
+            return Err(UBIError::Other("Standing order not found".to_string()));
+        }
+        
+        // Emit event
+        emit_event(&mut *tx, &StandingOrderCancelledEvent {
+            order_id,
+            person_id: hex::encode(&user.person_id),
+        }).await?;
+        
+        // Commit transaction
+        tx.commit().await?;
+        
+        info!("Standing order {} cancelled", order_id);
+        
+        Ok(())
+    }
+    
+    /// Run up to `limit` due orders, of one person or of everyone
+    ///
+    /// Each run commits on its own, and rows are claimed with SKIP LOCKED,
+    /// so the worker and a claim can run orders side by side
+    pub async fn execute_due(&self, person_id: Option<&[u8]>, limit: usize) -> Result<usize, UBIError> {
+        let mut executed = 0;
+        while executed < limit {
+            if !self.execute_next(person_id).await? {
+                break;
+            }
+            executed += 1;
+        }
+        Ok(executed)
+    }
+    
+    /// Run the oldest due order, if any
+    async fn execute_next(&self, person_id: Option<&[u8]>) -> Result<bool, UBIError> {
+        let epoch = current_epoch(self.genesis_timestamp);
+        let seconds_into_epoch = (chrono::Utc::now().timestamp()
+            - epoch_start_timestamp(epoch, self.genesis_timestamp)) as i32;
+        
+        // Start transaction
+        let mut tx = self.pool.begin().await?;
+        lock_event_chain(&mut *tx).await?;
+        
+        // Due: an earlier epoch never ran, or this epoch's point has passed and the payer has claimed
+        let order = sqlx::query_as!(
+            StandingOrder,
+            r#"
+            SELECT o.id, o.person_id, o.to_wallet, o.amount_ue, o.memo, o.offset_seconds, o.next_epoch,
+                   o.status, o.created_at
+            FROM standing_orders o
+            WHERE o.status = 'active'
+              AND ($3::BYTEA IS NULL OR o.person_id = $3)
+              AND (o.next_epoch < $1
+                   OR (o.next_epoch = $1 AND o.offset_seconds <= $2
+                       AND EXISTS (SELECT 1 FROM ubi_claims c WHERE c.person_id = o.person_id AND c.epoch = $1)))
+            ORDER BY o.next_epoch, o.id
+            LIMIT 1
+            FOR UPDATE OF o SKIP LOCKED
+            "#,
+            epoch,
+            seconds_into_epoch,
+            person_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        
+        let Some(order) = order else {
+            return Ok(false);
+        };
+        
+        let payer = sqlx::query!(
+            "SELECT wallet_address, is_active FROM users WHERE person_id = $1",
+            order.person_id.as_slice()
+        )
+        .fetch_optional(&mut *tx)
+        .await?;
+        
+        let outcome = match payer {
+            // Only this epoch's run and the last epoch's end-of-epoch run still pay
+            _ if order.next_epoch < epoch - 1 => {
+                Err(format!("Missed: epoch {} ended while standing orders were not running", order.next_epoch))
+            }
+            Some(payer) if !payer.is_active => Err("Payer is not active".to_string()),
+            None => Err("Payer is not registered".to_string()),
+            Some(payer) if payer.wallet_address == order.to_wallet => {
+                Err("Recipient is the payer's wallet".to_string())
+            }
+            Some(payer) => {
+                // A failed payment must not leave partial writes behind the skip record
+                let mut savepoint = tx.begin().await?;
+                match send_payment(
+                    &mut savepoint,
+                    &order.person_id,
+                    &payer.wallet_address,
+                    &order.to_wallet,
+                    order.amount_ue,
+                    order.memo.clone(),
+                ).await {
+                    Ok(payment) => {
+                        savepoint.commit().await?;
+                        Ok(payment.id)
+                    }
+                    Err(UBIError::InsufficientBalance) => {
+                        savepoint.rollback().await?;
+                        Err(UBIError::InsufficientBalance.to_string())
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+        };
+        
+        let (payment_id, skip_reason) = match &outcome {
+            Ok(payment_id) => (Some(*payment_id), None),
+            Err(reason) => (None, Some(reason.clone())),
+        };
+        let execution_id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO standing_order_executions (order_id, epoch, payment_id, skip_reason)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id
+            "#,
+            order.id,
+            order.next_epoch,
+            payment_id,
+            skip_reason
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        
+        sqlx::query!(
+            "UPDATE standing_orders SET next_epoch = $2 WHERE id = $1",
+            order.id,
+            order.next_epoch + 1
+        )
+        .execute(&mut *tx)
+        .await?;
+        
+        // Emit event
+        match outcome {
+            Ok(payment_id) => {
+                emit_event(&mut *tx, &StandingOrderExecutedEvent {
+                    order_id: order.id,
+                    execution_id,
+                    epoch: order.next_epoch,
+                    payment_id,
+                }).await?;
+                info!("Standing order {} paid {} UE for epoch {}", order.id, order.amount_ue, order.next_epoch);
+            }
+            Err(reason) => {
+                emit_event(&mut *tx, &StandingOrderSkippedEvent {
+                    order_id: order.id,
+                    execution_id,
+                    epoch: order.next_epoch,
+                    reason: reason.clone(),
+                }).await?;
+                info!("Standing order {} skipped for epoch {}: {}", order.id, order.next_epoch, reason);
+            }
+        }
+        
+        // Commit transaction
+        tx.commit().await?;
+        
+        Ok(true)
+    }
+}
+
@@ -0,0 +1,117 @@
+//! Wallet statement service
+//!
+//! Statements are built from the event log, not the balance tables: every
+//! event is folded through the replay service and each change to the
+//! wallet's UE or BU balance becomes a statement line
+
+use crate::models::statement::{Statement, StatementLine};
+use crate::models::replay::ReplayState;
+use crate::services::replay::{parse, ReplayService};
+use crate::utils::{epoch::{current_epoch, epoch_at}, errors::UBIError};
+use sqlx::PgPool;
+use rust_decimal::Decimal;
+
+pub struct StatementService {
+    replay: ReplayService,
+    genesis_timestamp: i64,
+}
+
+impl StatementService {
+    pub fn new(pool: PgPool, genesis_timestamp: i64) -> Self {
+        Self {
+            replay: ReplayService::new(pool, genesis_timestamp),
+            genesis_timestamp,
+        }
+    }
+    
+    /// Statement for `wallet` covering epochs `from_epoch..=to_epoch`
+    ///
+    /// `to_epoch` defaults to the current epoch
+    pub async fn statement(
+        &self,
+        wallet: &str,
+        from_epoch: i32,
+        to_epoch: Option<i32>,
+    ) -> Result<Statement, UBIError> {
+        let to_epoch = to_epoch.unwrap_or_else(|| current_epoch(self.genesis_timestamp));
+        if from_epoch < 0 || from_epoch > to_epoch {
+            return Err(UBIError::Other(format!("Invalid epoch range {}..{}", from_epoch, to_epoch)));
+        }
+        
+        let events = self.replay.load_events().await?;
+        let (mut state, _) = self.replay.fold(&[])?;
+        
+        let mut ue = wallet_balance(&state, wallet, "UE")?;
+        let mut bu = wallet_balance(&state, wallet, "BU")?;
+        let (mut opening_ue, mut opening_bu) = (ue, bu);
+        let mut lines = Vec::new();
+        
+        for event in &events {
+            let epoch = epoch_at(event.created_at.timestamp(), self.genesis_timestamp);
+            if epoch > to_epoch {
+                break;
+            }
+            
+            self.replay.apply(&mut state, event)?;
+            
+            for (unit, balance) in [("UE", &mut ue), ("BU", &mut bu)] {
+                let updated = wallet_balance(&state, wallet, unit)?;
+                if updated == *balance {
+                    continue;
+                }
+                if epoch >= from_epoch {
+                    lines.push(StatementLine {
+                        event_id: event.id,
+                        event_type: event.event_type.clone(),
+                        epoch,
+                        created_at: event.created_at,
+                        unit: unit.to_string(),
+                        amount: updated - *balance,
+                        balance: updated,
+                    });
+                }
+                *balance = updated;
+            }
+            
+            if epoch < from_epoch {
+                opening_ue = ue;
+                opening_bu = bu;
+            }
+        }
+        
+        Ok(Statement {
+            wallet_address: wallet.to_string(),
+            from_epoch,
+            to_epoch,
+            opening_ue,
+            opening_bu,
+            closing_ue: ue,
+            closing_bu: bu,
+            lines,
+        })
+    }
+}
+
+fn wallet_balance(state: &ReplayState, wallet: &str, unit: &str) -> Result<Decimal, UBIError> {
+    let balances = if unit == "UE" { &state.ue_balances } else { &state.bu_balances };
+    balances.get(wallet).map_or(Ok(Decimal::ZERO), |balance| parse(balance))
+}
+
+/// Render a statement's lines as CSV, one row per line
+pub fn statement_csv(statement: &Statement) -> String {
+    let mut csv = String::from("event_id,event_type,epoch,created_at,unit,amount,balance\n");
+    for line in &statement.lines {
+        csv.push_str(&format!(
+            "{},{},{},{},{},{},{}\n",
+            line.event_id,
+            line.event_type,
+            line.epoch,
+            line.created_at.to_rfc3339(),
+            line.unit,
+            line.amount,
+            line.balance,
+        ));
+    }
+    csv
+}
+
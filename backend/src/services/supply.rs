@@ -0,0 +1,308 @@
+//! Supply accounting service
+//!
+//! CONSTITUTIONAL: BU supply fixed at genesis
+//! Totals UE minted, burned and taken as fees, and where every BU sits,
+//! per epoch from the journal. A worker checks the supply invariants
+//! against the live tables, keeps its latest report for `/health` and
+//! raises SupplyInvariantViolated when one starts failing.
+
+use crate::models::supply::{EpochSupply, SupplyReport, SupplyViolation};
+use crate::utils::{epoch::current_epoch, errors::UBIError};
+use crate::constants::{BU_TOTAL_SUPPLY, EPOCH_LENGTH_SECONDS};
+use crate::events::{emit_event, lock_event_chain, SupplyInvariantViolatedEvent};
+use sqlx::PgPool;
+use std::collections::{BTreeMap, BTreeSet};
+use std::sync::{Arc, RwLock};
+use std::time::Duration;
+use log::{error, info};
+
+/// How often the worker checks the supply invariants
+const SUPPLY_CHECK_INTERVAL: Duration = Duration::from_secs(60);
+
+/// Outcome of the worker's latest check, shared with `/health`
+#[derive(Debug, Clone, Default)]
+pub struct SupplyStatus(Arc<RwLock<Option<Result<SupplyReport, String>>>>);
+
+impl SupplyStatus {
+    /// Latest report or check error; None until the first check finishes
+    pub fn latest(&self) -> Option<Result<SupplyReport, String>> {
+        self.0.read().unwrap_or_else(|e| e.into_inner()).clone()
+    }
+    
+    fn record(&self, outcome: Result<SupplyReport, String>) {
+        *self.0.write().unwrap_or_else(|e| e.into_inner()) = Some(outcome);
+    }
+}
+
+pub struct SupplyService {
+    pool: PgPool,
+    genesis_timestamp: i64,
+}
+
+impl SupplyService {
+    pub fn new(pool: PgPool, genesis_timestamp: i64) -> Self {
+        Self {
+            pool,
+            genesis_timestamp,
+        }
+    }
+    
+    /// Check the invariants until the process exits, recording each outcome in `status`
+    ///
+    /// Alerts once per invariant when it starts failing; an invariant that
+    /// recovers alerts again if it fails later
+    pub async fn run_worker(self, status: SupplyStatus) {
+        let mut alerted = BTreeSet::new();
+        loop {
+            match self.check().await {
+                Ok(report) => {
+                    if let Err(e) = self.raise_alerts(&report, &mut alerted).await {
+                        error!("Supply alert failed: {}", e);
+                    }
+                    status.record(Ok(report));
+                }
+                Err(e) => {
+                    error!("Supply check failed: {}", e);
+                    status.record(Err(e.to_string()));
+                }
+            }
+            tokio::time::sleep(SUPPLY_CHECK_INTERVAL).await;
+        }
+    }
+    
+    async fn raise_alerts(
+        &self,
+        report: &SupplyReport,
+        alerted: &mut BTreeSet<String>,
+    ) -> Result<(), UBIError> {
+        let new: Vec<&SupplyViolation> = report.violations.iter()
+            .filter(|violation| !alerted.contains(&violation.invariant))
+            .collect();
+        
+        if !new.is_empty() {
+            // Start transaction
+            let mut tx = self.pool.begin().await?;
+            lock_event_chain(&mut *tx).await?;
+            
+            for violation in &new {
+                emit_event(&mut *tx, &SupplyInvariantViolatedEvent {
+                    epoch: report.epoch,
+                    invariant: violation.invariant.clone(),
+                    expected: violation.expected.to_string(),
+                    actual: violation.actual.to_string(),
+                }).await?;
+            }
+            
+            // Commit transaction
+            tx.commit().await?;
+            
+            for violation in &new {
+                error!("Supply invariant {} violated: expected {}, actual {}",
+                       violation.invariant, violation.expected, violation.actual);
+            }
+        }
+        
+        let recovered: Vec<String> = alerted.iter()
+            .filter(|invariant| !report.violations.iter().any(|v| &v.invariant == *invariant))
+            .cloned()
+            .collect();
+        for invariant in recovered {
+            info!("Supply invariant {} holds again", invariant);
+            alerted.remove(&invariant);
+        }
+        alerted.extend(new.into_iter().map(|violation| violation.invariant.clone()));
+        
+        Ok(())
+    }
+    
+    /// Live totals checked against the supply invariants
+    ///
+    /// - `bu_fixed_supply`: treasury (free and reserved) and wallet BU add
+    ///   up to the genesis supply
+    /// - `bu_pending_covered`: the treasury holds at least the BU owed to
+    ///   pending conversions (expected is that minimum)
+    /// - `bu_reserved`: the reserve matches the BU of reserved conversions
+    ///   not yet claimed or cancelled
+    /// - `ue_minted`: the mint account matches the UE recorded by UBI claims
+    /// - `ue_circulating`: wallet UE equals minted minus burned minus fees
+    pub async fn check(&self) -> Result<SupplyReport, UBIError> {
+        // One statement, so every total comes from the same snapshot. Sums
+        // can pass what a Decimal holds, so they stay NUMERIC in SQL and
+        // come back as text
+        let totals = sqlx::query!(
+            r#"
+            WITH totals AS (
+                SELECT
+                    (SELECT COALESCE(-SUM(amount), 0) FROM journal_postings WHERE account = 'mint') AS ue_minted,
+                    (SELECT COALESCE(SUM(amount), 0) FROM journal_postings WHERE account = 'burn') AS ue_burned,
+                    (SELECT COALESCE(SUM(amount), 0) FROM journal_postings WHERE account = 'fees') AS ue_fees,
+                    (SELECT COALESCE(SUM(amount_ue), 0) FROM ubi_claims) AS ue_claimed,
+                    (SELECT COALESCE(SUM(balance), 0) FROM ue_balances) AS ue_circulating,
+                    COALESCE((SELECT balance_bu FROM treasury ORDER BY id DESC LIMIT 1), 0) AS bu_treasury,
+                    COALESCE((SELECT reserved_bu FROM treasury ORDER BY id DESC LIMIT 1), 0) AS bu_reserved,
+                    (SELECT COALESCE(SUM(amount_bu), 0) FROM pending_conversions WHERE status IN ('pending', 'unlocked')) AS bu_pending,
+                    (SELECT COALESCE(SUM(amount_bu), 0) FROM pending_conversions
+                     WHERE status IN ('pending', 'unlocked') AND reserved) AS bu_pending_reserved,
+                    (SELECT COALESCE(SUM(balance), 0) FROM bu_balances) AS bu_wallets
+            )
+            SELECT
+                ue_minted::TEXT AS "ue_minted!",
+                ue_burned::TEXT AS "ue_burned!",
+                ue_fees::TEXT AS "ue_fees!",
+                ue_claimed::TEXT AS "ue_claimed!",
+                ue_circulating::TEXT AS "ue_circulating!",
+                bu_treasury::TEXT AS "bu_treasury!",
+                bu_reserved::TEXT AS "bu_reserved!",
+                bu_pending::TEXT AS "bu_pending!",
+                bu_pending_reserved::TEXT AS "bu_pending_reserved!",
+                bu_wallets::TEXT AS "bu_wallets!",
+                (bu_treasury + bu_reserved + bu_wallets)::TEXT AS "bu_held!",
+                (bu_treasury + bu_reserved)::TEXT AS "bu_backing!",
+                (ue_minted - ue_burned - ue_fees)::TEXT AS "ue_outstanding!",
+                bu_treasury + bu_reserved + bu_wallets = $1::TEXT::NUMERIC AS "bu_fixed_supply!",
+                bu_treasury + bu_reserved >= bu_pending AS "bu_pending_covered!",
+                bu_reserved = bu_pending_reserved AS "bu_reserved_matches!",
+                ue_minted = ue_claimed AS "ue_minted_matches!",
+                ue_circulating = ue_minted - ue_burned - ue_fees AS "ue_circulating_matches!"
+            FROM totals
+            "#,
+            BU_TOTAL_SUPPLY
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        
+        let mut violations = Vec::new();
+        let mut expect = |invariant: &str, holds: bool, expected: &str, actual: &str| {
+            if !holds {
+                violations.push(SupplyViolation {
+                    invariant: invariant.to_string(),
+                    expected: expected.to_string(),
+                    actual: actual.to_string(),
+                });
+            }
+        };
+        
+        expect("bu_fixed_supply", totals.bu_fixed_supply, BU_TOTAL_SUPPLY, &totals.bu_held);
+        expect("bu_pending_covered", totals.bu_pending_covered, &totals.bu_pending, &totals.bu_backing);
+        expect("bu_reserved", totals.bu_reserved_matches, &totals.bu_pending_reserved, &totals.bu_reserved);
+        expect("ue_minted", totals.ue_minted_matches, &totals.ue_claimed, &totals.ue_minted);
+        expect("ue_circulating", totals.ue_circulating_matches, &totals.ue_outstanding, &totals.ue_circulating);
+        
+        Ok(SupplyReport {
+            epoch: current_epoch(self.genesis_timestamp),
+            ue_minted: totals.ue_minted,
+            ue_burned: totals.ue_burned,
+            ue_fees: totals.ue_fees,
+            ue_circulating: totals.ue_circulating,
+            bu_treasury: totals.bu_treasury,
+            bu_reserved: totals.bu_reserved,
+            bu_pending: totals.bu_pending,
+            bu_wallets: totals.bu_wallets,
+            healthy: violations.is_empty(),
+            violations,
+        })
+    }
+    
+    /// Supply for every epoch from genesis to the current one
+    pub async fn epochs(&self) -> Result<Vec<EpochSupply>, UBIError> {
+        let current = current_epoch(self.genesis_timestamp);
+        
+        // Journal movements, bucketed by the epoch they were posted in, with
+        // balances as running sums; kept NUMERIC in SQL like `check`
+        let flows = sqlx::query!(
+            r#"
+            WITH flows AS (
+                SELECT
+                    GREATEST(FLOOR((EXTRACT(EPOCH FROM e.created_at) - $1::BIGINT) / $2::BIGINT), 0)::INTEGER AS epoch,
+                    COALESCE(-SUM(p.amount) FILTER (WHERE p.account = 'mint'), 0) AS ue_minted,
+                    COALESCE(SUM(p.amount) FILTER (WHERE p.account = 'burn'), 0) AS ue_burned,
+                    COALESCE(SUM(p.amount) FILTER (WHERE p.account = 'fees'), 0) AS ue_fees,
+                    COALESCE(SUM(p.amount) FILTER (WHERE p.unit = 'UE' AND p.account LIKE 'wallet:%'), 0) AS ue_wallets,
+                    COALESCE(SUM(p.amount) FILTER (WHERE p.unit = 'BU' AND p.account = 'treasury'), 0) AS bu_treasury,
+                    COALESCE(SUM(p.amount) FILTER (WHERE p.unit = 'BU' AND p.account = 'reserved'), 0) AS bu_reserved,
+                    COALESCE(SUM(p.amount) FILTER (WHERE p.unit = 'BU' AND p.account LIKE 'wallet:%'), 0) AS bu_wallets
+                FROM journal_postings p
+                JOIN journal_entries e ON e.id = p.entry_id
+                GROUP BY 1
+            )
+            SELECT
+                epochs.epoch AS "epoch!",
+                COALESCE(f.ue_minted, 0)::TEXT AS "ue_minted!",
+                COALESCE(f.ue_burned, 0)::TEXT AS "ue_burned!",
+                COALESCE(f.ue_fees, 0)::TEXT AS "ue_fees!",
+                COALESCE(SUM(f.ue_wallets) OVER running, 0)::TEXT AS "ue_circulating!",
+                COALESCE(SUM(f.bu_treasury) OVER running, 0)::TEXT AS "bu_treasury!",
+                COALESCE(SUM(f.bu_reserved) OVER running, 0)::TEXT AS "bu_reserved!",
+                COALESCE(SUM(f.bu_wallets) OVER running, 0)::TEXT AS "bu_wallets!"
+            FROM generate_series(0, $3::INTEGER) AS epochs(epoch)
+            LEFT JOIN flows f ON f.epoch = epochs.epoch
+            WINDOW running AS (ORDER BY epochs.epoch)
+            ORDER BY epochs.epoch
+            "#,
+            self.genesis_timestamp,
+            EPOCH_LENGTH_SECONDS,
+            current
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        
+        // BU owed at each epoch's end: requested by then, not refunded by rationing,
+        // and not yet claimed or cancelled. A conversion adds its BU in the epoch
+        // it was requested and takes it back in the one it settled, found once
+        // per conversion through the partial index on those events
+        let pending = sqlx::query!(
+            r#"
+            WITH settled AS (
+                SELECT (event_data->>'conversion_id')::BIGINT AS conversion_id, MIN(created_at) AS settled_at
+                FROM events
+                WHERE event_type IN ('ConversionClaimed', 'ConversionCancelled')
+                GROUP BY 1
+            ),
+            changes AS (
+                SELECT
+                    GREATEST(FLOOR((EXTRACT(EPOCH FROM pc.created_at) - $1::BIGINT) / $2::BIGINT), 0)::INTEGER AS epoch,
+                    pc.amount_bu AS change
+                FROM pending_conversions pc
+                WHERE pc.status <> 'refunded'
+                UNION ALL
+                SELECT
+                    GREATEST(FLOOR((EXTRACT(EPOCH FROM s.settled_at) - $1::BIGINT) / $2::BIGINT), 0)::INTEGER,
+                    -pc.amount_bu
+                FROM pending_conversions pc
+                JOIN settled s ON s.conversion_id = pc.id
+                WHERE pc.status <> 'refunded'
+            ),
+            per_epoch AS (
+                SELECT epoch, SUM(change) AS change FROM changes GROUP BY epoch
+            )
+            SELECT
+                epochs.epoch AS "epoch!",
+                COALESCE(SUM(c.change) OVER (ORDER BY epochs.epoch), 0)::TEXT AS "bu_pending!"
+            FROM generate_series(0, $3::INTEGER) AS epochs(epoch)
+            LEFT JOIN per_epoch c ON c.epoch = epochs.epoch
+            ORDER BY epochs.epoch
+            "#,
+            self.genesis_timestamp,
+            EPOCH_LENGTH_SECONDS,
+            current
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        let mut pending: BTreeMap<i32, String> = pending.into_iter()
+            .map(|row| (row.epoch, row.bu_pending))
+            .collect();
+        
+        Ok(flows.into_iter().map(|flow| EpochSupply {
+            epoch: flow.epoch,
+            ue_minted: flow.ue_minted,
+            ue_burned: flow.ue_burned,
+            ue_fees: flow.ue_fees,
+            ue_circulating: flow.ue_circulating,
+            bu_treasury: flow.bu_treasury,
+            bu_reserved: flow.bu_reserved,
+            bu_pending: pending.remove(&flow.epoch).unwrap_or_else(|| "0".to_string()),
+            bu_wallets: flow.bu_wallets,
+        }).collect())
+    }
+}
+
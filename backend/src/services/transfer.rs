@@ -0,0 +1,125 @@
+//! BU transfer service
+//!
+//! BU is freely transferable: any wallet holding BU can send it to any
+//! other wallet, registered or not, once it has a token. Persons get one
+//! for their current wallet or one they reset away from; other wallets
+//! are key wallets (see `utils::auth::key_wallet_address`). Both balances
+//! change in one transaction together with the BUTransferred event.
+
+use crate::models::transfer::{BUTransfer, BUTransferRequest};
+use crate::models::journal::{Account, JournalEntry, Unit};
+use crate::services::journal::post_entry;
+use crate::services::payment::{check_transfer_amount, PAYMENT_MEMO_MAX_CHARS};
+use crate::utils::errors::UBIError;
+use crate::events::{emit_event, lock_event_chain, BUTransferredEvent};
+use sqlx::PgPool;
+use rust_decimal::Decimal;
+use log::info;
+
+pub struct TransferService {
+    pool: PgPool,
+}
+
+impl TransferService {
+    pub fn new(pool: PgPool) -> Self {
+        Self { pool }
+    }
+    
+    /// Send BU from `wallet` to another wallet
+    pub async fn transfer_bu(&self, wallet: &str, req: BUTransferRequest) -> Result<BUTransfer, UBIError> {
+        info!("BU transfer request: {} BU from {} to {}", req.amount_bu, wallet, req.to_wallet);
+        
+        let to_wallet = req.to_wallet.trim();
+        if to_wallet.is_empty() {
+            return Err(UBIError::InvalidTransfer("Recipient wallet is required".to_string()));
+        }
+        if to_wallet == wallet {
+            return Err(UBIError::InvalidTransfer("Cannot transfer to the same wallet".to_string()));
+        }
+        let amount = check_transfer_amount(req.amount_bu)?;
+        let memo = req.memo
+            .map(|memo| memo.trim().to_string())
+            .filter(|memo| !memo.is_empty());
+        if memo.as_ref().is_some_and(|memo| memo.chars().count() > PAYMENT_MEMO_MAX_CHARS) {
+            return Err(UBIError::InvalidTransfer(
+                format!("Memo longer than {} characters", PAYMENT_MEMO_MAX_CHARS)
+            ));
+        }
+        
+        // Start transaction
+        let mut tx = self.pool.begin().await?;
+        lock_event_chain(&mut *tx).await?;
+        
+        // Make sure both rows exist, then lock them in a fixed order so
+        // opposite transfers between the same wallets cannot deadlock
+        sqlx::query!(
+            "INSERT INTO bu_balances (wallet_address, balance) VALUES ($1, 0) ON CONFLICT (wallet_address) DO NOTHING",
+            to_wallet
+        )
+        .execute(&mut *tx)
+        .await?;
+        
+        let wallets = vec![wallet.to_string(), to_wallet.to_string()];
+        let rows = sqlx::query!(
+            r#"
+            SELECT wallet_address, balance FROM bu_balances
+            WHERE wallet_address = ANY($1)
+            ORDER BY wallet_address
+            FOR UPDATE
+            "#,
+            &wallets
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        
+        let sender_balance = rows.iter()
+            .find(|row| row.wallet_address == wallet)
+            .map_or(Decimal::ZERO, |row| row.balance);
+        
+        if sender_balance < amount {
+            return Err(UBIError::InsufficientBalance);
+        }
+        
+        // Record transfer
+        let transfer = sqlx::query_as!(
+            BUTransfer,
+            r#"
+            INSERT INTO bu_transfers (from_wallet, to_wallet, amount_bu, memo)
+            VALUES ($1, $2, $3, $4)
+            RETURNING id, from_wallet, to_wallet, amount_bu, memo, created_at
+            "#,
+            wallet,
+            to_wallet,
+            amount,
+            memo
+        )
+        .fetch_one(&mut *tx)
+        .await?;
+        
+        // Emit event
+        let event_id = emit_event(&mut *tx, &BUTransferredEvent {
+            transfer_id: transfer.id,
+            from_wallet: transfer.from_wallet.clone(),
+            to_wallet: transfer.to_wallet.clone(),
+            amount_bu: transfer.amount_bu.to_string(),
+            memo: transfer.memo.clone(),
+        }).await?;
+        
+        let entry = JournalEntry::new().transfer(
+            Unit::BU,
+            Account::Wallet(transfer.from_wallet.clone()),
+            Account::Wallet(transfer.to_wallet.clone()),
+            amount,
+        );
+        post_entry(&mut *tx, event_id, "BU transfer", &entry).await?;
+        
+        // Commit transaction
+        tx.commit().await?;
+        
+        info!("BU transfer {} successful: {} BU from {} to {}",
+              transfer.id, transfer.amount_bu, transfer.from_wallet, transfer.to_wallet);
+        
+        Ok(transfer)
+    }
+}
+
@@ -2,6 +2,7 @@
 
 use crate::models::treasury::Treasury;
 use crate::utils::errors::UBIError;
+use rust_decimal::Decimal;
 use sqlx::PgPool;
 
 pub struct TreasuryService {
@@ -14,13 +15,13 @@ impl TreasuryService {
     }
     
     /// Get treasury balance
-    pub async fn get_balance(&self) -> Result<String, UBIError> {
+    pub async fn get_balance(&self) -> Result<Decimal, UBIError> {
         let balance = sqlx::query_scalar!(
             "SELECT balance_bu FROM treasury ORDER BY id DESC LIMIT 1"
         )
         .fetch_optional(&self.pool)
         .await?
-        .unwrap_or_else(|| "0".to_string());
+        .unwrap_or(Decimal::ZERO);
         
         Ok(balance)
     }
@@ -1,15 +1,18 @@
 //! UBI issuance service
 //! 
 //! CONSTITUTIONAL: One person can claim UBI once per epoch
-//! UE issuance is fixed: 696 UE per epoch
+//! UE issuance is fixed: 696 UE per epoch (unless a fork amends it)
 
 use crate::models::claim::{UBIClaim, ClaimResponse};
 use crate::services::registry::RegistryService;
+use crate::services::constitution::parameters_at;
+use crate::services::standing_order::StandingOrderService;
+use crate::services::journal::post_entry;
+use crate::models::journal::{Account, JournalEntry, Unit};
 use crate::utils::{epoch::current_epoch, errors::UBIError, wad};
-use crate::constants::UE_MINT_PER_EPOCH;
-use crate::events::{emit_event, UBIClaimedEvent};
+use crate::events::{emit_event, lock_event_chain, UBIClaimedEvent};
 use sqlx::PgPool;
-use log::info;
+use log::{error, info};
 use hex;
 
 pub struct UBIService {
@@ -31,9 +34,6 @@ impl UBIService {
     pub async fn claim_ubi(&self, wallet: &str) -> Result<ClaimResponse, UBIError> {
         info!("UBI claim request for wallet: {}", wallet);
         
-        // Start transaction
-        let mut tx = self.pool.begin().await?;
-        
         // Get user
         let user = self.registry
             .get_user_by_wallet(wallet)
@@ -50,17 +50,27 @@ impl UBIService {
             return Err(UBIError::RegistrationExpired);
         }
         
-        // Check if already claimed
-        let last_claimed = self.registry
-            .get_last_claimed_epoch(&user.person_id, user.region_id)
-            .await?;
+        // Start transaction
+        let mut tx = self.pool.begin().await?;
+        lock_event_chain(&mut *tx).await?;
+        
+        // Check if already claimed, on the transaction: the chain lock is held from
+        // here, and waiting on a second pool connection could starve the pool
+        let last_claimed = sqlx::query_scalar!(
+            "SELECT epoch FROM last_claimed_epoch WHERE person_id = $1 AND region_id = $2",
+            user.person_id.as_slice(),
+            user.region_id
+        )
+        .fetch_optional(&mut *tx)
+        .await?
+        .unwrap_or(0);
         
         if last_claimed >= epoch {
             return Err(UBIError::AlreadyClaimed(epoch));
         }
         
-        // UBI amount is fixed: 696 UE per epoch
-        let ubi_amount = UE_MINT_PER_EPOCH.to_string();
+        // UBI amount is fixed by the constitution in force this epoch
+        let ubi_amount = parameters_at(&mut *tx, epoch).await?.ue_mint_per_epoch;
         
         // Record claim
         sqlx::query!(
@@ -70,7 +80,7 @@ impl UBIService {
             "#,
             user.person_id.as_slice(),
             epoch,
-            &ubi_amount
+            ubi_amount
         )
         .execute(&mut *tx)
         .await?;
@@ -90,35 +100,34 @@ impl UBIService {
         .execute(&mut *tx)
         .await?;
         
-        // Update UE balance
-        sqlx::query!(
-            r#"
-            INSERT INTO ue_balances (wallet_address, balance)
-            VALUES ($1, $2)
-            ON CONFLICT (wallet_address)
-            DO UPDATE SET balance = ue_balances.balance + $2
-            "#,
-            wallet,
-            &ubi_amount
-        )
-        .execute(&mut *tx)
-        .await?;
-        
         // Emit event
-        let event_data = serde_json::to_value(crate::events::UBIClaimedEvent {
+        let event_id = emit_event(&mut *tx, &UBIClaimedEvent {
             person_id: hex::encode(&user.person_id),
             wallet_address: wallet.to_string(),
             epoch,
-            amount_ue: ubi_amount.clone(),
-        }).unwrap();
+            amount_ue: ubi_amount.to_string(),
+        }).await?;
         
-        emit_event(&mut *tx, "UBIClaimed", &event_data).await?;
+        // Mint UE into the wallet
+        let entry = JournalEntry::new()
+            .transfer(Unit::UE, Account::Mint, Account::Wallet(wallet.to_string()), ubi_amount);
+        post_entry(&mut *tx, event_id, "UBI claim", &entry).await?;
         
         // Commit transaction
         tx.commit().await?;
         
         info!("UBI claim successful: {} UE to wallet {} for epoch {}", ubi_amount, wallet, epoch);
         
+        // Standing orders waiting on this claim run now; the claim stands either way
+        let standing_orders = StandingOrderService::new(
+            self.pool.clone(),
+            RegistryService::new(self.pool.clone()),
+            self.genesis_timestamp,
+        );
+        if let Err(e) = standing_orders.execute_due(Some(&user.person_id), usize::MAX).await {
+            error!("Standing orders after claim by {} failed: {}", wallet, e);
+        }
+        
         // Get created claim
         let claim = sqlx::query_as!(
             UBIClaim,
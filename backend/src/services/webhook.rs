@@ -0,0 +1,313 @@
+//! Webhook service
+//!
+//! Delivers events from the transactional outbox to registered endpoints.
+//! Delivery is at least once: each POST is signed with the endpoint's
+//! secret, failures are retried with exponential backoff, and deliveries
+//! that exhaust their retries move to the dead-letter list.
+
+use crate::models::webhook::{DeadLetter, WebhookEndpoint, WebhookRegistered, WebhookRegistration};
+use crate::events::{Event, EventType};
+use crate::utils::errors::UBIError;
+use chrono::Utc;
+use hmac::{Hmac, Mac};
+use rand::Rng;
+use sha2::Sha256;
+use sqlx::PgPool;
+use std::time::Duration;
+use log::{error, info, warn};
+use hex;
+
+/// Deliveries attempted per worker pass
+const WEBHOOK_BATCH_SIZE: i64 = 100;
+
+/// How often the worker looks for due deliveries
+const WEBHOOK_POLL_INTERVAL: Duration = Duration::from_secs(5);
+
+/// Per-request timeout for webhook POSTs
+const WEBHOOK_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// When to give up on a delivery and how long to wait between attempts
+#[derive(Debug, Clone, Copy)]
+pub struct RetryPolicy {
+    pub max_attempts: i32,
+    pub base_backoff: Duration, // doubled after every failed attempt
+    pub max_backoff: Duration,
+}
+
+impl Default for RetryPolicy {
+    fn default() -> Self {
+        Self {
+            max_attempts: 10,
+            base_backoff: Duration::from_secs(30),
+            max_backoff: Duration::from_secs(6 * 60 * 60),
+        }
+    }
+}
+
+impl RetryPolicy {
+    /// Delay before the next attempt, after `attempts` failures
+    pub fn backoff(&self, attempts: i32) -> Duration {
+        let factor = 2u32.saturating_pow(attempts.saturating_sub(1).max(0) as u32);
+        self.base_backoff.saturating_mul(factor).min(self.max_backoff)
+    }
+}
+
+pub struct WebhookService {
+    pool: PgPool,
+    client: reqwest::Client,
+    policy: RetryPolicy,
+}
+
+impl WebhookService {
+    pub fn new(pool: PgPool, policy: RetryPolicy) -> Self {
+        let client = reqwest::Client::builder()
+            .timeout(WEBHOOK_TIMEOUT)
+            .build()
+            .unwrap_or_default();
+        
+        Self {
+            pool,
+            client,
+            policy,
+        }
+    }
+    
+    /// Deliver due events until the process exits
+    pub async fn run_worker(self) {
+        loop {
+            match self.deliver_due(WEBHOOK_BATCH_SIZE).await {
+                // A full batch means more are probably waiting
+                Ok(delivered) if delivered as i64 == WEBHOOK_BATCH_SIZE => continue,
+                Ok(_) => {}
+                Err(e) => error!("Webhook delivery pass failed: {}", e),
+            }
+            tokio::time::sleep(WEBHOOK_POLL_INTERVAL).await;
+        }
+    }
+    
+    /// Register an endpoint; events emitted from now on are queued for it
+    pub async fn register(&self, registration: WebhookRegistration) -> Result<WebhookRegistered, UBIError> {
+        let url = reqwest::Url::parse(&registration.url)
+            .map_err(|e| UBIError::Other(format!("Invalid webhook URL: {}", e)))?;
+        if url.scheme() != "http" && url.scheme() != "https" {
+            return Err(UBIError::Other("Webhook URL must be http or https".to_string()));
+        }
+        if let Some(event_types) = &registration.event_types {
+            for event_type in event_types {
+                event_type.parse::<EventType>().map_err(UBIError::Other)?;
+            }
+        }
+        
+        let secret_bytes: [u8; 32] = rand::thread_rng().gen();
+        let secret = hex::encode(secret_bytes);
+        
+        let id = sqlx::query_scalar!(
+            r#"
+            INSERT INTO webhook_endpoints (url, secret, event_types)
+            VALUES ($1, $2, $3)
+            RETURNING id
+            "#,
+            &registration.url,
+            &secret,
+            registration.event_types.as_deref()
+        )
+        .fetch_one(&self.pool)
+        .await?;
+        
+        info!("Webhook endpoint {} registered: {}", id, registration.url);
+        
+        Ok(WebhookRegistered {
+            id,
+            url: registration.url,
+            secret,
+        })
+    }
+    
+    /// Registered endpoints
+    pub async fn list_endpoints(&self) -> Result<Vec<WebhookEndpoint>, UBIError> {
+        let endpoints = sqlx::query_as!(
+            WebhookEndpoint,
+            "SELECT id, url, event_types, is_active, created_at FROM webhook_endpoints ORDER BY id"
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(endpoints)
+    }
+    
+    /// Stop queueing and delivering events to an endpoint
+    pub async fn deactivate(&self, endpoint_id: i64) -> Result<(), UBIError> {
+        let updated = sqlx::query!(
+            "UPDATE webhook_endpoints SET is_active = false WHERE id = $1",
+            endpoint_id
+        )
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        
+        if updated == 0 {
+            return Err(UBIError::Other("Webhook endpoint not found".to_string()));
+        }
+        Ok(())
+    }
+    
+    /// Attempt every due delivery once, returning how many were attempted
+    ///
+    /// Rows are claimed with SKIP LOCKED, so several workers can run side by side
+    pub async fn deliver_due(&self, limit: i64) -> Result<usize, UBIError> {
+        let mut tx = self.pool.begin().await?;
+        
+        let due = sqlx::query!(
+            r#"
+            SELECT o.id, o.attempts, w.url, w.secret,
+                   e.id AS event_id, e.event_type, e.event_data, e.created_at, e.prev_hash, e.hash,
+                   e.hash_version
+            FROM webhook_outbox o
+            JOIN webhook_endpoints w ON w.id = o.endpoint_id
+            JOIN events e ON e.id = o.event_id
+            WHERE o.status = 'pending' AND o.next_attempt_at <= NOW() AND w.is_active
+            ORDER BY o.id
+            LIMIT $1
+            FOR UPDATE OF o SKIP LOCKED
+            "#,
+            limit
+        )
+        .fetch_all(&mut *tx)
+        .await?;
+        
+        for row in &due {
+            let event = Event {
+                id: row.event_id,
+                event_type: row.event_type.clone(),
+                event_data: row.event_data.clone(),
+                created_at: row.created_at,
+                prev_hash: row.prev_hash.clone(),
+                hash: row.hash.clone(),
+                hash_version: row.hash_version,
+            };
+            let attempts = row.attempts + 1;
+            
+            match self.post(&row.url, &row.secret, &event).await {
+                Ok(()) => {
+                    sqlx::query!(
+                        r#"
+                        UPDATE webhook_outbox
+                        SET status = 'delivered', attempts = $2, delivered_at = NOW(), last_error = NULL
+                        WHERE id = $1
+                        "#,
+                        row.id,
+                        attempts
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                Err(reason) if attempts >= self.policy.max_attempts => {
+                    warn!("Webhook delivery {} dead after {} attempts: {}", row.id, attempts, reason);
+                    sqlx::query!(
+                        "UPDATE webhook_outbox SET status = 'dead', attempts = $2, last_error = $3 WHERE id = $1",
+                        row.id,
+                        attempts,
+                        reason
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                }
+                Err(reason) => {
+                    let backoff = self.policy.backoff(attempts).as_secs_f64();
+                    sqlx::query!(
+                        r#"
+                        UPDATE webhook_outbox
+                        SET attempts = $2, last_error = $3, next_attempt_at = NOW() + make_interval(secs => $4)
+                        WHERE id = $1
+                        "#,
+                        row.id,
+                        attempts,
+                        reason,
+                        backoff
+                    )
+                    .execute(&mut *tx)
+                    .await?;
+                }
+            }
+        }
+        
+        tx.commit().await?;
+        Ok(due.len())
+    }
+    
+    /// Deliveries that exhausted their retries
+    pub async fn dead_letters(&self) -> Result<Vec<DeadLetter>, UBIError> {
+        let dead = sqlx::query_as!(
+            DeadLetter,
+            r#"
+            SELECT o.id, o.event_id, o.endpoint_id, w.url, o.attempts, o.last_error, o.created_at
+            FROM webhook_outbox o
+            JOIN webhook_endpoints w ON w.id = o.endpoint_id
+            WHERE o.status = 'dead'
+            ORDER BY o.id
+            "#
+        )
+        .fetch_all(&self.pool)
+        .await?;
+        
+        Ok(dead)
+    }
+    
+    /// Put a dead letter back in the queue with a fresh set of attempts
+    pub async fn retry_dead_letter(&self, delivery_id: i64) -> Result<(), UBIError> {
+        let updated = sqlx::query!(
+            r#"
+            UPDATE webhook_outbox
+            SET status = 'pending', attempts = 0, next_attempt_at = NOW()
+            WHERE id = $1 AND status = 'dead'
+            "#,
+            delivery_id
+        )
+        .execute(&self.pool)
+        .await?
+        .rows_affected();
+        
+        if updated == 0 {
+            return Err(UBIError::Other("Dead letter not found".to_string()));
+        }
+        Ok(())
+    }
+    
+    /// POST one event; any non-2xx response is a failure
+    async fn post(&self, url: &str, secret: &str, event: &Event) -> Result<(), String> {
+        let body = serde_json::to_string(event).map_err(|e| e.to_string())?;
+        let timestamp = Utc::now().timestamp();
+        let signature = webhook_signature(secret, timestamp, &body);
+        
+        let response = self.client
+            .post(url)
+            .header("Content-Type", "application/json")
+            .header("X-UBI-Event-Id", event.id.to_string())
+            .header("X-UBI-Timestamp", timestamp.to_string())
+            .header("X-UBI-Signature", format!("sha256={}", signature))
+            .body(body)
+            .send()
+            .await
+            .map_err(|e| e.to_string())?;
+        
+        if response.status().is_success() {
+            Ok(())
+        } else {
+            Err(format!("HTTP {}", response.status()))
+        }
+    }
+}
+
+/// HMAC-SHA256 over `{timestamp}.{body}`, hex-encoded
+///
+/// Receivers recompute this with their secret and compare it with the
+/// X-UBI-Signature header (after the `sha256=` prefix)
+pub fn webhook_signature(secret: &str, timestamp: i64, body: &str) -> String {
+    let mut mac = Hmac::<Sha256>::new_from_slice(secret.as_bytes())
+        .expect("HMAC accepts keys of any length");
+    mac.update(timestamp.to_string().as_bytes());
+    mac.update(b".");
+    mac.update(body.as_bytes());
+    hex::encode(mac.finalize().into_bytes())
+}
+
@@ -1,21 +1,125 @@
 //! JWT authentication
 
 use jsonwebtoken::{encode, decode, Header, EncodingKey, DecodingKey, Validation};
+use actix_web::http::header::{self, HeaderMap};
+use actix_web::dev::Payload;
+use actix_web::{web, FromRequest, HttpRequest, HttpResponse};
+use crate::config::Config;
+use crate::services::registry::RegistryService;
+use crate::utils::errors::UBIError;
+use futures::future::LocalBoxFuture;
 use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
+use sqlx::PgPool;
 use chrono::{Utc, Duration};
+use std::fmt;
+
+/// Optional header naming the wallet a request acts for
+const WALLET_ADDRESS_HEADER: &str = "x-wallet-address";
+
+/// Wallet making the request, authenticated by its bearer token
+///
+/// Use as a handler argument; requests without a valid
+/// `Authorization: Bearer` token, or with one the registry has since
+/// revoked, are rejected with 401. An X-Wallet-Address header, if sent,
+/// must name the token's wallet (403)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WalletAddress(pub String);
+
+impl fmt::Display for WalletAddress {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl FromRequest for WalletAddress {
+    type Error = actix_web::Error;
+    type Future = LocalBoxFuture<'static, Result<Self, Self::Error>>;
+    
+    fn from_request(req: &HttpRequest, _: &mut Payload) -> Self::Future {
+        let (Some(config), Some(pool)) = (req.app_data::<web::Data<Config>>(), req.app_data::<web::Data<PgPool>>()) else {
+            return Box::pin(async { Err(actix_web::error::ErrorInternalServerError("Configuration missing")) });
+        };
+        let claims = authenticate(req.headers(), &config.jwt_secret);
+        let registry = RegistryService::new(pool.get_ref().clone());
+        
+        Box::pin(async move {
+            let result = match claims {
+                Ok(claims) => registry.check_token(&claims).await.map(|()| WalletAddress(claims.wallet_address)),
+                Err(e) => Err(e),
+            };
+            result.map_err(|e| {
+                let response = match e {
+                    UBIError::Forbidden(_) => HttpResponse::Forbidden(),
+                    UBIError::Unauthorized => HttpResponse::Unauthorized(),
+                    _ => HttpResponse::InternalServerError(),
+                }
+                .json(serde_json::json!({ "error": e.to_string() }));
+                actix_web::error::InternalError::from_response(e, response).into()
+            })
+        })
+    }
+}
+
+/// Claims of the bearer token on these request headers
+///
+/// Checks the signature, expiry and X-Wallet-Address only; whether the
+/// registry still honours the token is `RegistryService::check_token`
+pub fn authenticate(headers: &HeaderMap, secret: &str) -> Result<Claims, UBIError> {
+    let token = headers
+        .get(header::AUTHORIZATION)
+        .and_then(|value| value.to_str().ok())
+        .and_then(|value| value.strip_prefix("Bearer "))
+        .map(str::trim)
+        .filter(|token| !token.is_empty())
+        .ok_or(UBIError::Unauthorized)?;
+    let claims = verify_token(token, secret).map_err(|_| UBIError::Unauthorized)?;
+    
+    if let Some(named) = headers.get(WALLET_ADDRESS_HEADER) {
+        if named.to_str().ok().map(str::trim) != Some(claims.wallet_address.as_str()) {
+            return Err(UBIError::Forbidden("X-Wallet-Address does not match the token".to_string()));
+        }
+    }
+    
+    Ok(claims)
+}
+
+/// Wallet address controlled by an Ed25519 public key: the last 20 bytes of its SHA-256
+pub fn key_wallet_address(public_key_hex: &str) -> Option<String> {
+    let public_key: [u8; 32] = hex::decode(public_key_hex.trim()).ok()?.try_into().ok()?;
+    let digest = Sha256::digest(public_key);
+    Some(format!("0x{}", hex::encode(&digest[12..])))
+}
+
+/// Message a key wallet signs to get a token
+pub fn wallet_token_message(wallet_address: &str, timestamp: i64) -> String {
+    format!("tw-ubi wallet token\n{}\n{}", wallet_address, timestamp)
+}
+
+/// Who a token was issued to
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum TokenHolder {
+    /// The person `user_id`, for as long as `registry_wallet` stays their wallet
+    Person { registry_wallet: String },
+    /// Whoever holds the key `user_id` that the wallet address derives from
+    WalletKey,
+}
 
 #[derive(Debug, Serialize, Deserialize)]
 pub struct Claims {
-    pub user_id: String,
+    pub user_id: String, // person_id, or the public key of a key wallet
     pub wallet_address: String,
+    pub issued_to: TokenHolder,
     pub exp: i64,
 }
 
 impl Claims {
-    pub fn new(user_id: String, wallet_address: String) -> Self {
+    pub fn new(user_id: String, wallet_address: String, issued_to: TokenHolder) -> Self {
         Self {
             user_id,
             wallet_address,
+            issued_to,
             exp: (Utc::now() + Duration::days(30)).timestamp(),
         }
     }
@@ -0,0 +1,87 @@
+//! Canonical byte encoding
+//!
+//! Deterministic JSON that independent implementations can reproduce
+//! byte for byte (JSONB keeps neither key order nor number formatting):
+//! - no whitespace
+//! - object keys sorted by their UTF-8 bytes
+//! - integers as decimal strings (`5` encodes as `"5"`); other numbers are rejected
+//! - strings escape only `"`, `\` and U+0000..U+001F (as `\u00xx`, lowercase hex)
+//! - everything else is literal UTF-8
+
+use serde::Serialize;
+use serde_json::Value;
+use anyhow::{anyhow, Result};
+
+/// Encode any serializable value canonically
+pub fn to_canonical_bytes<T: Serialize>(value: &T) -> Result<Vec<u8>> {
+    let value = serde_json::to_value(value)?;
+    let mut out = Vec::new();
+    write_value(&value, &mut out)?;
+    Ok(out)
+}
+
+/// Canonical encoding as a string (always valid UTF-8)
+pub fn to_canonical_string<T: Serialize>(value: &T) -> Result<String> {
+    Ok(String::from_utf8(to_canonical_bytes(value)?)?)
+}
+
+fn write_value(value: &Value, out: &mut Vec<u8>) -> Result<()> {
+    match value {
+        Value::Null => out.extend_from_slice(b"null"),
+        Value::Bool(true) => out.extend_from_slice(b"true"),
+        Value::Bool(false) => out.extend_from_slice(b"false"),
+        Value::Number(n) => {
+            let digits = if let Some(i) = n.as_i64() {
+                i.to_string()
+            } else if let Some(u) = n.as_u64() {
+                u.to_string()
+            } else {
+                return Err(anyhow!("Non-integer number {} has no canonical form", n));
+            };
+            write_string(&digits, out);
+        }
+        Value::String(s) => write_string(s, out),
+        Value::Array(items) => {
+            out.push(b'[');
+            for (i, item) in items.iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                write_value(item, out)?;
+            }
+            out.push(b']');
+        }
+        Value::Object(map) => {
+            let mut entries: Vec<(&String, &Value)> = map.iter().collect();
+            entries.sort_by(|a, b| a.0.as_bytes().cmp(b.0.as_bytes()));
+            out.push(b'{');
+            for (i, (key, item)) in entries.into_iter().enumerate() {
+                if i > 0 {
+                    out.push(b',');
+                }
+                write_string(key, out);
+                out.push(b':');
+                write_value(item, out)?;
+            }
+            out.push(b'}');
+        }
+    }
+    Ok(())
+}
+
+fn write_string(s: &str, out: &mut Vec<u8>) {
+    out.push(b'"');
+    for c in s.chars() {
+        match c {
+            '"' => out.extend_from_slice(b"\\\""),
+            '\\' => out.extend_from_slice(b"\\\\"),
+            c if (c as u32) < 0x20 => out.extend_from_slice(format!("\\u{:04x}", c as u32).as_bytes()),
+            c => {
+                let mut buf = [0u8; 4];
+                out.extend_from_slice(c.encode_utf8(&mut buf).as_bytes());
+            }
+        }
+    }
+    out.push(b'"');
+}
+
@@ -6,11 +6,15 @@ use crate::constants::EPOCH_LENGTH_SECONDS;
 
 /// Get current epoch number
 pub fn current_epoch(genesis_timestamp: i64) -> i32 {
-    let now = chrono::Utc::now().timestamp();
-    if now < genesis_timestamp {
+    epoch_at(chrono::Utc::now().timestamp(), genesis_timestamp)
+}
+
+/// Get epoch containing a given unix timestamp
+pub fn epoch_at(timestamp: i64, genesis_timestamp: i64) -> i32 {
+    if timestamp < genesis_timestamp {
         return 0;
     }
-    ((now - genesis_timestamp) / EPOCH_LENGTH_SECONDS) as i32
+    ((timestamp - genesis_timestamp) / EPOCH_LENGTH_SECONDS) as i32
 }
 
 /// Get epoch start timestamp
@@ -25,6 +25,9 @@ pub enum UBIError {
     #[error("Slippage too high: expected {0}, got {1}")]
     SlippageTooHigh(String, String),
     
+    #[error("Treasury depleted: {0}")]
+    TreasuryDepleted(String),
+    
     #[error("Rate index not initialized for region {0}")]
     RateIndexNotInitialized(i32),
     
@@ -37,6 +40,21 @@ pub enum UBIError {
     #[error("Unauthorized")]
     Unauthorized,
     
+    #[error("Forbidden: {0}")]
+    Forbidden(String),
+    
+    #[error("No checkpoint for epoch {0}")]
+    CheckpointNotFound(i32),
+    
+    #[error("Checkpoint failed verification: {0}")]
+    CheckpointInvalid(String),
+    
+    #[error("Invalid transfer: {0}")]
+    InvalidTransfer(String),
+    
+    #[error("Invalid input: {0}")]
+    InvalidInput(String),
+    
     #[error("Database error: {0}")]
     Database(#[from] sqlx::Error),
     
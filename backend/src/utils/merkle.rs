@@ -0,0 +1,170 @@
+//! Merkle trees over state leaves
+//!
+//! SHA-256 with domain separation: leaves hash 0x00 || data,
+//! inner nodes hash 0x01 || left || right. An unpaired node is promoted
+//! to the next level unchanged (never duplicated).
+
+use sha2::{Digest, Sha256};
+
+/// Root of a tree with no leaves
+pub const EMPTY_ROOT: [u8; 32] = [0u8; 32];
+
+/// Hash a leaf's canonical bytes
+pub fn leaf_hash(data: &[u8]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0x00]);
+    hasher.update(data);
+    hasher.finalize().into()
+}
+
+/// Hash two child nodes
+pub fn node_hash(left: &[u8; 32], right: &[u8; 32]) -> [u8; 32] {
+    let mut hasher = Sha256::new();
+    hasher.update([0x01]);
+    hasher.update(left);
+    hasher.update(right);
+    hasher.finalize().into()
+}
+
+/// Compute the root over leaf hashes, in the given order
+pub fn merkle_root(leaves: &[[u8; 32]]) -> [u8; 32] {
+    if leaves.is_empty() {
+        return EMPTY_ROOT;
+    }
+    
+    let mut level = leaves.to_vec();
+    while level.len() > 1 {
+        level = next_level(&level);
+    }
+    level[0]
+}
+
+/// One step of an inclusion proof
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ProofStep {
+    pub sibling: [u8; 32],
+    pub sibling_is_left: bool,
+}
+
+/// Sibling path from the leaf at `index` to the root
+///
+/// Levels where the node is unpaired contribute no step
+pub fn merkle_proof(leaves: &[[u8; 32]], index: usize) -> Option<Vec<ProofStep>> {
+    if index >= leaves.len() {
+        return None;
+    }
+    
+    let mut proof = Vec::new();
+    let mut level = leaves.to_vec();
+    let mut index = index;
+    while level.len() > 1 {
+        let sibling = index ^ 1;
+        if sibling < level.len() {
+            proof.push(ProofStep {
+                sibling: level[sibling],
+                sibling_is_left: sibling < index,
+            });
+        }
+        level = next_level(&level);
+        index /= 2;
+    }
+    Some(proof)
+}
+
+/// Check that a leaf hash and its proof lead to `root`
+pub fn verify_proof(leaf: [u8; 32], proof: &[ProofStep], root: &[u8; 32]) -> bool {
+    let computed = proof.iter().fold(leaf, |node, step| {
+        if step.sibling_is_left {
+            node_hash(&step.sibling, &node)
+        } else {
+            node_hash(&node, &step.sibling)
+        }
+    });
+    &computed == root
+}
+
+/// Hash one level of the tree into the level above it
+fn next_level(level: &[[u8; 32]]) -> Vec<[u8; 32]> {
+    level
+        .chunks(2)
+        .map(|pair| match pair {
+            [left, right] => node_hash(left, right),
+            [single] => *single,
+            _ => unreachable!(),
+        })
+        .collect()
+}
+
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    
+    fn leaves(n: usize) -> Vec<[u8; 32]> {
+        (0..n).map(|i| leaf_hash(format!("leaf|{}", i).as_bytes())).collect()
+    }
+    
+    #[test]
+    fn empty_tree_has_the_empty_root_and_no_proofs() {
+        assert_eq!(merkle_root(&[]), EMPTY_ROOT);
+        assert_eq!(merkle_proof(&[], 0), None);
+    }
+    
+    #[test]
+    fn single_leaf_is_its_own_root() {
+        let leaves = leaves(1);
+        assert_eq!(merkle_root(&leaves), leaves[0]);
+        assert_eq!(merkle_proof(&leaves, 0), Some(Vec::new()));
+        assert!(verify_proof(leaves[0], &[], &leaves[0]));
+    }
+    
+    #[test]
+    fn even_count_pairs_every_node() {
+        let l = leaves(4);
+        let root = node_hash(&node_hash(&l[0], &l[1]), &node_hash(&l[2], &l[3]));
+        assert_eq!(merkle_root(&l), root);
+        assert_eq!(merkle_proof(&l, 2).unwrap(), [
+            ProofStep { sibling: l[3], sibling_is_left: false },
+            ProofStep { sibling: node_hash(&l[0], &l[1]), sibling_is_left: true },
+        ]);
+    }
+    
+    #[test]
+    fn odd_count_promotes_the_unpaired_node() {
+        let l = leaves(5);
+        let left = node_hash(&node_hash(&l[0], &l[1]), &node_hash(&l[2], &l[3]));
+        assert_eq!(merkle_root(&l), node_hash(&left, &l[4]));
+        // The promoted leaf skips the levels where it has no sibling
+        assert_eq!(merkle_proof(&l, 4).unwrap(), [ProofStep { sibling: left, sibling_is_left: true }]);
+    }
+    
+    #[test]
+    fn every_index_round_trips() {
+        for n in 1..=9 {
+            let l = leaves(n);
+            let root = merkle_root(&l);
+            for (index, leaf) in l.iter().enumerate() {
+                let proof = merkle_proof(&l, index).unwrap();
+                assert!(verify_proof(*leaf, &proof, &root), "{} leaves, index {}", n, index);
+            }
+        }
+    }
+    
+    #[test]
+    fn tampered_sibling_fails() {
+        let l = leaves(6);
+        let root = merkle_root(&l);
+        for (index, leaf) in l.iter().enumerate() {
+            let mut proof = merkle_proof(&l, index).unwrap();
+            proof[0].sibling[0] ^= 1;
+            assert!(!verify_proof(*leaf, &proof, &root));
+        }
+    }
+    
+    #[test]
+    fn out_of_range_index_has_no_proof() {
+        assert_eq!(merkle_proof(&leaves(3), 3), None);
+        assert_eq!(merkle_proof(&leaves(3), usize::MAX), None);
+    }
+}
+
@@ -3,10 +3,16 @@ pub mod wad;
 pub mod errors;
 pub mod auth;
 pub mod mfa;
+pub mod merkle;
+pub mod signing;
+pub mod canonical;
 
 pub use epoch::*;
 pub use wad::*;
 pub use errors::*;
 pub use auth::*;
 pub use mfa::*;
+pub use merkle::*;
+pub use signing::*;
+pub use canonical::*;
 
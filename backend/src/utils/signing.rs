@@ -0,0 +1,39 @@
+//! Server signing key (Ed25519)
+//! 
+//! Signs published commitments so observers can check they came from
+//! this deployment
+
+use ed25519_dalek::{Signature, Signer, SigningKey, Verifier, VerifyingKey};
+use anyhow::{anyhow, Result};
+
+/// Load a signing key from a hex-encoded 32-byte seed
+pub fn signing_key_from_hex(seed_hex: &str) -> Result<SigningKey> {
+    let seed: [u8; 32] = hex::decode(seed_hex.trim())?
+        .try_into()
+        .map_err(|_| anyhow!("Signing key seed must be 32 bytes"))?;
+    Ok(SigningKey::from_bytes(&seed))
+}
+
+/// Hex-encoded public key
+pub fn public_key_hex(key: &SigningKey) -> String {
+    hex::encode(key.verifying_key().to_bytes())
+}
+
+/// Sign a message, returning the hex-encoded signature
+pub fn sign_hex(key: &SigningKey, message: &[u8]) -> String {
+    hex::encode(key.sign(message).to_bytes())
+}
+
+/// Verify a hex-encoded signature against a hex-encoded public key
+pub fn verify_hex(public_key_hex: &str, message: &[u8], signature_hex: &str) -> bool {
+    let public_key: Option<[u8; 32]> = hex::decode(public_key_hex).ok().and_then(|b| b.try_into().ok());
+    let signature: Option<[u8; 64]> = hex::decode(signature_hex).ok().and_then(|b| b.try_into().ok());
+    
+    match (public_key, signature) {
+        (Some(public_key), Some(signature)) => VerifyingKey::from_bytes(&public_key)
+            .map(|key| key.verify(message, &Signature::from_bytes(&signature)).is_ok())
+            .unwrap_or(false),
+        _ => false,
+    }
+}
+
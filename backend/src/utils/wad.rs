@@ -1,54 +1,50 @@
 //! WAD math (fixed-point arithmetic, 1e18)
-//! 
+//!
 //! STRICT: Integer math only, NO FLOATS
+//! Results are truncated to whole base units, matching NUMERIC(78,0) storage.
+//! The WAD divisor is applied to one operand first: a raw product of two
+//! WAD values would not fit in a Decimal.
 
 use crate::constants::WAD;
 use rust_decimal::Decimal;
-use anyhow::Result;
+use anyhow::{anyhow, Result};
 
 /// Multiply two WAD values: (a * b) / WAD
-pub fn mul_wad(a: &str, b: &str) -> Result<String> {
-    let a_dec: Decimal = a.parse()?;
-    let b_dec: Decimal = b.parse()?;
-    let wad = Decimal::from(WAD);
-    let result = (a_dec * b_dec) / wad;
-    Ok(result.to_string())
+pub fn mul_wad(a: Decimal, b: Decimal) -> Result<Decimal> {
+    let product = a.checked_mul(b / Decimal::from(WAD))
+        .ok_or_else(|| anyhow!("WAD multiplication overflow"))?;
+    Ok(product.trunc())
 }
 
 /// Divide two WAD values: (a * WAD) / b
-pub fn div_wad(a: &str, b: &str) -> Result<String> {
-    let a_dec: Decimal = a.parse()?;
-    let b_dec: Decimal = b.parse()?;
-    let wad = Decimal::from(WAD);
-    let result = (a_dec * wad) / b_dec;
-    Ok(result.to_string())
+pub fn div_wad(a: Decimal, b: Decimal) -> Result<Decimal> {
+    if b.is_zero() {
+        return Err(anyhow!("WAD division by zero"));
+    }
+    let quotient = a.checked_div(b / Decimal::from(WAD))
+        .ok_or_else(|| anyhow!("WAD division overflow"))?;
+    Ok(quotient.trunc())
 }
 
 /// Apply decay: rateIndex *= (1 - decayRate) for N epochs
-pub fn apply_decay(rate_index: &str, decay_rate: &str, epochs: i32) -> Result<String> {
-    let rate_dec: Decimal = rate_index.parse()?;
-    let decay_dec: Decimal = decay_rate.parse()?;
-    let wad = Decimal::from(WAD);
-    let decay_factor = wad - decay_dec;
+pub fn apply_decay(rate_index: Decimal, decay_rate: Decimal, epochs: i32) -> Result<Decimal> {
+    let decay_factor = Decimal::from(WAD) - decay_rate;
     
-    let mut result = rate_dec;
+    let mut result = rate_index;
     for _ in 0..epochs {
-        result = (result * decay_factor) / wad;
+        result = mul_wad(result, decay_factor)?;
     }
     
-    Ok(result.to_string())
+    Ok(result)
 }
 
 /// Convert amount to WAD: amount * WAD
-pub fn to_wad(amount: Decimal) -> String {
-    let wad = Decimal::from(WAD);
-    (amount * wad).to_string()
+pub fn to_wad(amount: Decimal) -> Decimal {
+    amount * Decimal::from(WAD)
 }
 
 /// Convert WAD to amount: wad / WAD
-pub fn from_wad(wad_str: &str) -> Result<Decimal> {
-    let wad_dec: Decimal = wad_str.parse()?;
-    let wad = Decimal::from(WAD);
-    Ok(wad_dec / wad)
+pub fn from_wad(wad: Decimal) -> Decimal {
+    wad / Decimal::from(WAD)
 }
 
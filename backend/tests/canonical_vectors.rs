@@ -0,0 +1,212 @@
+//! Golden vectors for the canonical encoding
+//!
+//! These pin exact bytes. If one fails, the encoding changed: every
+//! independent implementation and every version-2 event hash breaks with it.
+
+use ubi_backend::events::*;
+use ubi_backend::utils::canonical::{to_canonical_bytes, to_canonical_string};
+
+const PERSON_ID: &str = "a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1";
+const WALLET: &str = "0x1111111111111111111111111111111111111111";
+
+fn assert_canonical<T: serde::Serialize>(value: &T, expected: &str) {
+    assert_eq!(to_canonical_string(value).unwrap(), expected);
+}
+
+#[test]
+fn person_registered() {
+    assert_canonical(
+        &PersonRegisteredEvent {
+            person_id: PERSON_ID.to_string(),
+            wallet_address: WALLET.to_string(),
+            region_id: 840,
+            expiry_epoch: 12,
+        },
+        r#"{"expiry_epoch":"12","person_id":"a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1","region_id":"840","wallet_address":"0x1111111111111111111111111111111111111111"}"#,
+    );
+}
+
+#[test]
+fn ubi_claimed() {
+    assert_canonical(
+        &UBIClaimedEvent {
+            person_id: PERSON_ID.to_string(),
+            wallet_address: WALLET.to_string(),
+            epoch: 3,
+            amount_ue: "696000000000000000000".to_string(),
+        },
+        r#"{"amount_ue":"696000000000000000000","epoch":"3","person_id":"a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1","wallet_address":"0x1111111111111111111111111111111111111111"}"#,
+    );
+}
+
+#[test]
+fn conversion_requested() {
+    assert_canonical(
+        &ConversionRequestedEvent {
+            conversion_id: Some(7),
+            person_id: PERSON_ID.to_string(),
+            wallet_address: WALLET.to_string(),
+            amount_ue: "1000000000000000000".to_string(),
+            amount_bu: "995000000000000000".to_string(),
+            rate_index: "1000000000000000000".to_string(),
+            unlock_epoch: 4,
+            fee_ue: Some("5000000000000000".to_string()),
+            bu_reserved: true,
+            queued: false,
+        },
+        r#"{"amount_bu":"995000000000000000","amount_ue":"1000000000000000000","bu_reserved":true,"conversion_id":"7","fee_ue":"5000000000000000","person_id":"a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1","queued":false,"rate_index":"1000000000000000000","unlock_epoch":"4","wallet_address":"0x1111111111111111111111111111111111111111"}"#,
+    );
+}
+
+#[test]
+fn conversion_claimed() {
+    assert_canonical(
+        &ConversionClaimedEvent {
+            person_id: PERSON_ID.to_string(),
+            wallet_address: WALLET.to_string(),
+            conversion_id: 7,
+            amount_bu: "995000000000000000".to_string(),
+        },
+        r#"{"amount_bu":"995000000000000000","conversion_id":"7","person_id":"a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1","wallet_address":"0x1111111111111111111111111111111111111111"}"#,
+    );
+}
+
+#[test]
+fn wallet_reset() {
+    assert_canonical(
+        &WalletResetEvent {
+            person_id: PERSON_ID.to_string(),
+            old_wallet: WALLET.to_string(),
+            new_wallet: "0x2222222222222222222222222222222222222222".to_string(),
+        },
+        r#"{"new_wallet":"0x2222222222222222222222222222222222222222","old_wallet":"0x1111111111111111111111111111111111111111","person_id":"a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1a3f1"}"#,
+    );
+}
+
+#[test]
+fn rate_index_updated() {
+    assert_canonical(
+        &RateIndexUpdatedEvent {
+            region_id: 840,
+            rate_index: "990000000000000000".to_string(),
+            decay_rate: "10000000000000000".to_string(),
+            epoch: 3,
+        },
+        r#"{"decay_rate":"10000000000000000","epoch":"3","rate_index":"990000000000000000","region_id":"840"}"#,
+    );
+}
+
+#[test]
+fn decay_rate_updated() {
+    assert_canonical(
+        &DecayRateUpdatedEvent {
+            region_id: 840,
+            previous_decay_rate: "10000000000000000".to_string(),
+            decay_rate: "11000000000000000".to_string(),
+            inflation_rate: "20000000000000000".to_string(),
+            epoch: 3,
+        },
+        r#"{"decay_rate":"11000000000000000","epoch":"3","inflation_rate":"20000000000000000","previous_decay_rate":"10000000000000000","region_id":"840"}"#,
+    );
+}
+
+#[test]
+fn oracle_data_submitted() {
+    assert_canonical(
+        &OracleDataSubmittedEvent {
+            region_id: 840,
+            basket_index_wad: "1020000000000000000".to_string(),
+            inflation_rate_wad: "20000000000000000".to_string(),
+            timestamp: 1700000000,
+        },
+        r#"{"basket_index_wad":"1020000000000000000","inflation_rate_wad":"20000000000000000","region_id":"840","timestamp":"1700000000"}"#,
+    );
+}
+
+#[test]
+fn treasury_debited() {
+    assert_canonical(
+        &TreasuryDebitedEvent {
+            amount_bu: "995000000000000000".to_string(),
+            balance_bu: "999999005000000000000000".to_string(),
+            conversion_id: 7,
+        },
+        r#"{"amount_bu":"995000000000000000","balance_bu":"999999005000000000000000","conversion_id":"7"}"#,
+    );
+}
+
+#[test]
+fn checkpoint_created() {
+    assert_canonical(
+        &CheckpointCreatedEvent {
+            epoch: 5,
+            merkle_root: "ab".repeat(32),
+            leaf_count: 42,
+            last_event_id: 1234,
+            last_event_hash: "cd".repeat(32),
+        },
+        r#"{"epoch":"5","last_event_hash":"cdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcd","last_event_id":"1234","leaf_count":"42","merkle_root":"abababababababababababababababababababababababababababababababab"}"#,
+    );
+}
+
+#[test]
+fn fork_created() {
+    assert_canonical(
+        &ForkCreatedEvent {
+            parent_head_event_id: 1234,
+            parent_head_hash: "cd".repeat(32),
+            events_imported: 1234,
+        },
+        r#"{"events_imported":"1234","parent_head_event_id":"1234","parent_head_hash":"cdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcdcd"}"#,
+    );
+}
+
+#[test]
+fn constitution_amended() {
+    assert_canonical(
+        &ConstitutionAmendedEvent {
+            effective_epoch: 24,
+            ue_mint_per_epoch: "750000000000000000000".to_string(),
+            conversion_fee_bps: 25,
+            conversion_cap_ue: "1000000000000000000000".to_string(),
+            conversion_delay_epochs: 1,
+            cancellation_refunds_fee: false,
+            rationing_threshold_bu: "100000000000000000000000".to_string(),
+        },
+        r#"{"cancellation_refunds_fee":false,"conversion_cap_ue":"1000000000000000000000","conversion_delay_epochs":"1","conversion_fee_bps":"25","effective_epoch":"24","rationing_threshold_bu":"100000000000000000000000","ue_mint_per_epoch":"750000000000000000000"}"#,
+    );
+}
+
+#[test]
+fn escapes_nesting_and_integers() {
+    let value = serde_json::json!({
+        "b": "quote\" backslash\\ nl\n tab\t nul\u{0} del\u{7f} é ✓ 😀 /",
+        "a": [true, false, null, -5, u64::MAX],
+        "": {},
+    });
+    assert_canonical(
+        &value,
+        "{\"\":{},\"a\":[true,false,null,\"-5\",\"18446744073709551615\"],\"b\":\"quote\\\" backslash\\\\ nl\\u000a tab\\u0009 nul\\u0000 del\u{7f} é ✓ 😀 /\"}",
+    );
+}
+
+#[test]
+fn non_integer_numbers_are_rejected() {
+    assert!(to_canonical_bytes(&serde_json::json!({ "x": 1.5 })).is_err());
+}
+
+#[test]
+fn event_hash_v2() {
+    let event_data = serde_json::json!({
+        "region_id": 840,
+        "basket_index_wad": "1020000000000000000",
+        "inflation_rate_wad": "20000000000000000",
+        "timestamp": 1700000000,
+        "schema_version": 1,
+    });
+    let created_at = chrono::DateTime::from_timestamp_micros(1700000000123456).unwrap();
+    
+    let hash = compute_event_hash(2, GENESIS_HASH, 42, "OracleDataSubmitted", &event_data, &created_at).unwrap();
+    assert_eq!(hash, "dc0d0b2f8e5b057298564b2d290f8e2e2eda6bfb5e0f2915f8841280f09f5073");
+}
+
@@ -0,0 +1,106 @@
+//! The chain verifier catches edits to a sealed history
+//!
+//! Chains are built in memory with the version-2 hash, then altered the
+//! ways an attacker with write access to the events table could.
+
+use chrono::{TimeZone, Utc};
+use ubi_backend::events::{compute_event_hash, Event, GENESIS_HASH};
+use ubi_backend::models::event_chain::ChainVerification;
+use ubi_backend::services::event_chain::verify_events;
+
+fn seal(event: &mut Event, prev_hash: &str) {
+    event.prev_hash = Some(prev_hash.to_string());
+    event.hash = Some(
+        compute_event_hash(
+            event.hash_version,
+            prev_hash,
+            event.id,
+            &event.event_type,
+            &event.event_data,
+            &event.created_at,
+        )
+        .unwrap(),
+    );
+}
+
+/// Five sealed UBIClaimed events, ids 1 to 5
+fn chain() -> Vec<Event> {
+    let mut prev_hash = GENESIS_HASH.to_string();
+    (1..=5)
+        .map(|id| {
+            let mut event = Event {
+                id,
+                event_type: "UBIClaimed".to_string(),
+                event_data: serde_json::json!({
+                    "person_id": "a3f1",
+                    "wallet_address": "0x1111111111111111111111111111111111111111",
+                    "epoch": id,
+                    "amount_ue": "696000000000000000000",
+                    "schema_version": 1,
+                }),
+                created_at: Utc.timestamp_opt(1_700_000_000 + id * 60, 0).unwrap(),
+                prev_hash: None,
+                hash: None,
+                hash_version: 2,
+            };
+            seal(&mut event, &prev_hash);
+            prev_hash = event.hash.clone().unwrap();
+            event
+        })
+        .collect()
+}
+
+fn broken_at(report: &ChainVerification) -> (i64, &str) {
+    assert!(!report.valid);
+    let link = report.first_broken_link.as_ref().unwrap();
+    (link.event_id, &link.reason)
+}
+
+#[test]
+fn intact_chain_verifies() {
+    let events = chain();
+    let report = verify_events(&events);
+    assert!(report.valid);
+    assert_eq!(report.events_checked, 5);
+    assert_eq!(report.head_hash, events[4].hash);
+}
+
+#[test]
+fn modified_payload_is_detected() {
+    let mut events = chain();
+    events[2].event_data["amount_ue"] = serde_json::json!("1696000000000000000000");
+    let report = verify_events(&events);
+    assert_eq!(broken_at(&report), (3, "Content hash mismatch (event modified)"));
+}
+
+#[test]
+fn modified_prev_hash_is_detected() {
+    let mut events = chain();
+    events[3].prev_hash = Some("ab".repeat(32));
+    let report = verify_events(&events);
+    let (event_id, reason) = broken_at(&report);
+    assert_eq!(event_id, 4);
+    assert!(reason.starts_with("prev_hash does not match"), "{}", reason);
+}
+
+#[test]
+fn resealed_edit_breaks_the_next_link() {
+    // Rehashing the edited event hides it there, but its successor still
+    // points at the original hash
+    let mut events = chain();
+    events[1].event_data["epoch"] = serde_json::json!(9);
+    let prev = events[0].hash.clone().unwrap();
+    seal(&mut events[1], &prev);
+    let report = verify_events(&events);
+    assert_eq!(broken_at(&report).0, 3);
+    assert_eq!(report.head_event_id, Some(2));
+}
+
+#[test]
+fn deleted_event_is_detected() {
+    let mut events = chain();
+    events.remove(2);
+    let report = verify_events(&events);
+    assert_eq!(broken_at(&report).0, 4);
+}
+
@@ -0,0 +1,138 @@
+//! Checkpoints commit to every open conversion and sign their full snapshot
+//!
+//! Each database test gets a freshly migrated database from DATABASE_URL.
+
+use ed25519_dalek::SigningKey;
+use rand::Rng;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use ubi_backend::constants::EPOCH_LENGTH_SECONDS;
+use ubi_backend::models::constitution::ConstitutionAmendment;
+use ubi_backend::models::conversion::ConversionRequest;
+use ubi_backend::models::oracle::OracleSubmission;
+use ubi_backend::models::replay::{ReplayConversion, ReplayState};
+use ubi_backend::services::checkpoint::{checkpoint_leaves, CheckpointService};
+use ubi_backend::services::constitution::ConstitutionService;
+use ubi_backend::services::conversion::ConversionService;
+use ubi_backend::services::oracle::OracleService;
+use ubi_backend::services::rate_index::RateIndexService;
+use ubi_backend::services::registry::RegistryService;
+use ubi_backend::services::ubi::UBIService;
+use ubi_backend::utils::errors::UBIError;
+
+/// `n` whole UE (or BU) in base units
+fn ue(n: u64) -> Decimal {
+    Decimal::from(n) * Decimal::from(1_000_000_000_000_000_000u64)
+}
+
+fn random_hex(len: usize) -> String {
+    let bytes: Vec<u8> = (0..len).map(|_| rand::thread_rng().gen()).collect();
+    hex::encode(bytes)
+}
+
+/// Genesis timestamp that puts "now" a minute into `epoch`
+fn genesis_for(epoch: i32) -> i64 {
+    chrono::Utc::now().timestamp() - 60 - epoch as i64 * EPOCH_LENGTH_SECONDS
+}
+
+fn conversion(status: &str) -> ReplayConversion {
+    ReplayConversion {
+        person_id: "ab".to_string(),
+        amount_ue: "100".to_string(),
+        amount_bu: "99".to_string(),
+        rate_index: "1".to_string(),
+        unlock_epoch: 4,
+        status: status.to_string(),
+        reserved: false,
+    }
+}
+
+#[test]
+fn version_3_leaves_cover_open_conversions_and_reserved_bu() {
+    let mut state = ReplayState {
+        treasury_bu: "900".to_string(),
+        treasury_reserved_bu: "99".to_string(),
+        ..ReplayState::default()
+    };
+    for (id, status) in [(1, "pending"), (2, "queued"), (3, "unlocked"), (4, "claimed"), (5, "cancelled")] {
+        state.pending_conversions.insert(id, conversion(status));
+    }
+    let data = |version| -> Vec<String> {
+        checkpoint_leaves(&state, version).unwrap().into_iter().map(|l| l.leaf_data).collect()
+    };
+
+    assert_eq!(data(3), [
+        "conversion|1|ab|pending|100|99|1|4",
+        "conversion|2|ab|queued|100|99|1|4",
+        "conversion|3|ab|unlocked|100|99|1|4",
+        "treasury|900|99",
+    ]);
+    // Earlier versions keep their leaf set, so their roots still verify
+    assert_eq!(data(2), ["conversion|1|ab|100|99|1|4", "treasury|900"]);
+}
+
+#[sqlx::test]
+async fn snapshot_outside_the_leaves_is_verified_too(pool: PgPool) {
+    let genesis = genesis_for(10);
+    // Every request queues for rationing
+    ConstitutionService::new(pool.clone())
+        .amend(ConstitutionAmendment {
+            effective_epoch: 0,
+            ue_mint_per_epoch: None,
+            conversion_fee_bps: None,
+            conversion_cap_ue: None,
+            conversion_delay_epochs: None,
+            cancellation_refunds_fee: None,
+            rationing_threshold_bu: Some(ue(1_000_000_000)),
+        })
+        .await
+        .unwrap();
+    let wallet = format!("0x{}", random_hex(20));
+    RegistryService::new(pool.clone()).register_person(&random_hex(32), &wallet, 1, 10_000).await.unwrap();
+    OracleService::new(pool.clone())
+        .submit_data(OracleSubmission {
+            region_id: 1,
+            basket_index_wad: ue(1),
+        })
+        .await
+        .unwrap();
+    RateIndexService::new(pool.clone(), genesis).roll_rate_index(1).await.unwrap();
+    UBIService::new(pool.clone(), RegistryService::new(pool.clone()), genesis)
+        .claim_ubi(&wallet)
+        .await
+        .unwrap();
+    let queued = ConversionService::new(
+        pool.clone(),
+        RegistryService::new(pool.clone()),
+        RateIndexService::new(pool.clone(), genesis),
+        genesis,
+    )
+    .request_conversion(&wallet, ConversionRequest {
+        amount_ue: ue(100),
+        min_bu_out: Decimal::ZERO,
+    })
+    .await
+    .unwrap();
+    assert_eq!(queued.status, "queued");
+
+    let checkpoints = CheckpointService::new(pool.clone(), genesis, Some(SigningKey::from_bytes(&[7; 32])));
+    let checkpoint = checkpoints.create_checkpoint(9).await.unwrap();
+    assert_eq!(checkpoint.leaf_version, 3);
+    let leaf_keys: Vec<String> = sqlx::query_scalar("SELECT leaf_key FROM checkpoint_leaves WHERE epoch = 9")
+        .fetch_all(&pool)
+        .await
+        .unwrap();
+    assert!(leaf_keys.contains(&format!("conversion|{}", queued.conversion_id)));
+
+    let (_, state) = checkpoints.load_verified_snapshot(9).await.unwrap();
+    assert_eq!(state.pending_conversions[&queued.conversion_id].status, "queued");
+
+    // No leaf covers the claim bookkeeping, but the signed hash does
+    sqlx::query("UPDATE state_checkpoints SET snapshot = jsonb_set(snapshot, '{last_claimed_epoch}', '{}') WHERE epoch = 9")
+        .execute(&pool)
+        .await
+        .unwrap();
+    let tampered = checkpoints.load_verified_snapshot(9).await;
+    assert!(matches!(tampered, Err(UBIError::CheckpointInvalid(ref e)) if e.contains("snapshot hash")), "{:?}", tampered);
+}
+
@@ -0,0 +1,98 @@
+//! A quote predicts exactly what the matching request then charges
+//!
+//! Each test gets a freshly migrated database from DATABASE_URL.
+
+use rand::Rng;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use ubi_backend::constants::EPOCH_LENGTH_SECONDS;
+use ubi_backend::models::conversion::ConversionRequest;
+use ubi_backend::models::oracle::OracleSubmission;
+use ubi_backend::services::conversion::ConversionService;
+use ubi_backend::services::oracle::OracleService;
+use ubi_backend::services::rate_index::RateIndexService;
+use ubi_backend::services::registry::RegistryService;
+use ubi_backend::services::ubi::UBIService;
+use ubi_backend::utils::errors::UBIError;
+
+/// `n` whole UE (or BU) in base units
+fn ue(n: u64) -> Decimal {
+    Decimal::from(n) * Decimal::from(1_000_000_000_000_000_000u64)
+}
+
+fn random_hex(len: usize) -> String {
+    let bytes: Vec<u8> = (0..len).map(|_| rand::thread_rng().gen()).collect();
+    hex::encode(bytes)
+}
+
+/// Genesis timestamp that puts "now" a minute into `epoch`
+fn genesis_for(epoch: i32) -> i64 {
+    chrono::Utc::now().timestamp() - 60 - epoch as i64 * EPOCH_LENGTH_SECONDS
+}
+
+#[sqlx::test]
+async fn request_charges_what_the_quote_said(pool: PgPool) {
+    let genesis = genesis_for(10);
+    let wallet = format!("0x{}", random_hex(20));
+    RegistryService::new(pool.clone()).register_person(&random_hex(32), &wallet, 1, 10_000).await.unwrap();
+    OracleService::new(pool.clone())
+        .submit_data(OracleSubmission {
+            region_id: 1,
+            basket_index_wad: ue(1),
+        })
+        .await
+        .unwrap();
+    RateIndexService::new(pool.clone(), genesis).roll_rate_index(1).await.unwrap();
+    UBIService::new(pool.clone(), RegistryService::new(pool.clone()), genesis)
+        .claim_ubi(&wallet)
+        .await
+        .unwrap();
+    let conversions = ConversionService::new(
+        pool.clone(),
+        RegistryService::new(pool.clone()),
+        RateIndexService::new(pool.clone(), genesis),
+        genesis,
+    );
+
+    let quote = conversions.quote(&wallet, ue(100)).await.unwrap();
+    assert!(!quote.rationed);
+    assert!(quote.fee_ue > Decimal::ZERO);
+
+    // Asking for one base unit more than quoted is refused as slippage
+    let greedy = conversions
+        .request_conversion(&wallet, ConversionRequest {
+            amount_ue: ue(100),
+            min_bu_out: quote.amount_bu + Decimal::ONE,
+        })
+        .await;
+    assert!(matches!(greedy, Err(UBIError::SlippageTooHigh(_, _))), "{:?}", greedy);
+
+    // The quoted amount itself is accepted, and is what the request gives
+    let conversion = conversions
+        .request_conversion(&wallet, ConversionRequest {
+            amount_ue: ue(100),
+            min_bu_out: quote.amount_bu,
+        })
+        .await
+        .unwrap();
+    assert_eq!((conversion.amount_bu, conversion.unlock_epoch), (quote.amount_bu, quote.unlock_epoch));
+
+    let rate_index: Decimal = sqlx::query_scalar("SELECT rate_index FROM pending_conversions WHERE id = $1")
+        .bind(conversion.conversion_id)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!(rate_index, quote.rate_index);
+    let fee_ue: String = sqlx::query_scalar(
+        "SELECT event_data->>'fee_ue' FROM events WHERE event_type = 'ConversionRequested'",
+    )
+    .fetch_one(&pool)
+    .await
+    .unwrap();
+    assert_eq!(fee_ue.parse::<Decimal>().unwrap(), quote.fee_ue);
+
+    // The cap the next quote sees is down by the converted amount
+    let next = conversions.quote(&wallet, ue(10)).await.unwrap();
+    assert_eq!(next.cap_remaining_ue, quote.cap_remaining_ue - ue(100));
+}
+
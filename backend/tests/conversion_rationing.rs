@@ -0,0 +1,132 @@
+//! Concurrent conversion requests followed by a rationing pass
+//!
+//! The constitution is amended so every request queues and the cap is
+//! small. Each test gets a freshly migrated database from DATABASE_URL.
+
+use std::time::Duration;
+
+use futures::future::join_all;
+use rand::Rng;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use ubi_backend::constants::EPOCH_LENGTH_SECONDS;
+use ubi_backend::models::constitution::ConstitutionAmendment;
+use ubi_backend::models::conversion::ConversionRequest;
+use ubi_backend::models::oracle::OracleSubmission;
+use ubi_backend::services::constitution::ConstitutionService;
+use ubi_backend::services::conversion::ConversionService;
+use ubi_backend::services::oracle::OracleService;
+use ubi_backend::services::rate_index::RateIndexService;
+use ubi_backend::services::registry::RegistryService;
+use ubi_backend::services::ubi::UBIService;
+use ubi_backend::utils::errors::UBIError;
+
+/// `n` whole UE (or BU) in base units
+fn ue(n: u64) -> Decimal {
+    Decimal::from(n) * Decimal::from(1_000_000_000_000_000_000u64)
+}
+
+fn random_hex(len: usize) -> String {
+    let bytes: Vec<u8> = (0..len).map(|_| rand::thread_rng().gen()).collect();
+    hex::encode(bytes)
+}
+
+/// Genesis timestamp that puts "now" a minute into `epoch`
+fn genesis_for(epoch: i32) -> i64 {
+    chrono::Utc::now().timestamp() - 60 - epoch as i64 * EPOCH_LENGTH_SECONDS
+}
+
+#[sqlx::test]
+async fn concurrent_requests_respect_the_cap_and_ration_cleanly(pool: PgPool) {
+    let epoch = 10;
+    ConstitutionService::new(pool.clone())
+        .amend(ConstitutionAmendment {
+            effective_epoch: epoch,
+            ue_mint_per_epoch: None,
+            conversion_fee_bps: None,
+            conversion_cap_ue: Some(ue(250)),
+            conversion_delay_epochs: None,
+            cancellation_refunds_fee: None,
+            rationing_threshold_bu: Some(ue(1_000_000_000)),
+        })
+        .await
+        .unwrap();
+
+    let genesis = genesis_for(epoch);
+    let region_id = 1;
+    let person_id = random_hex(32);
+    let wallet = format!("0x{}", random_hex(20));
+
+    RegistryService::new(pool.clone())
+        .register_person(&person_id, &wallet, region_id, epoch + 10)
+        .await
+        .unwrap();
+    OracleService::new(pool.clone())
+        .submit_data(OracleSubmission {
+            region_id,
+            basket_index_wad: ue(1),
+        })
+        .await
+        .unwrap();
+    // Initialised up front so the concurrent requests only contend on the cap
+    RateIndexService::new(pool.clone(), genesis).roll_rate_index(region_id).await.unwrap();
+    UBIService::new(pool.clone(), RegistryService::new(pool.clone()), genesis)
+        .claim_ubi(&wallet)
+        .await
+        .unwrap();
+
+    let conversion_service = |genesis| {
+        ConversionService::new(
+            pool.clone(),
+            RegistryService::new(pool.clone()),
+            RateIndexService::new(pool.clone(), genesis),
+            genesis,
+        )
+    };
+    let service = conversion_service(genesis);
+    let results = join_all((0..4).map(|_| {
+        service.request_conversion(&wallet, ConversionRequest {
+            amount_ue: ue(100),
+            min_bu_out: Decimal::ZERO,
+        })
+    }))
+    .await;
+
+    // A cap of 250 UE admits two requests of 100 UE however they interleave
+    let accepted: Vec<_> = results.iter().filter_map(|r| r.as_ref().ok()).collect();
+    assert_eq!(accepted.len(), 2, "{:?}", results);
+    assert!(accepted.iter().all(|c| c.status == "queued"));
+    assert!(results
+        .iter()
+        .filter_map(|r| r.as_ref().err())
+        .all(|e| matches!(e, UBIError::ConversionCapExceeded(_))));
+
+    let converted = || async {
+        sqlx::query_scalar::<_, Decimal>(
+            "SELECT amount_ue FROM converted_this_epoch WHERE person_id = $1 AND epoch = $2",
+        )
+        .bind(hex::decode(&person_id).unwrap())
+        .bind(epoch)
+        .fetch_one(&pool)
+        .await
+        .unwrap()
+    };
+    assert_eq!(converted().await, ue(200));
+
+    // Rationing only takes ended epochs, so end `epoch` just before the pass
+    tokio::time::sleep(Duration::from_millis(1_100)).await;
+    let ended_genesis = chrono::Utc::now().timestamp() - (epoch as i64 + 1) * EPOCH_LENGTH_SECONDS;
+    let rationed = conversion_service(ended_genesis).ration_queued().await.unwrap();
+    assert_eq!(rationed, 2);
+
+    let statuses = sqlx::query_scalar::<_, String>(
+        "SELECT status FROM pending_conversions WHERE person_id = $1 ORDER BY id",
+    )
+    .bind(hex::decode(&person_id).unwrap())
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    assert_eq!(statuses, ["pending", "pending"]);
+    assert_eq!(converted().await, ue(200));
+}
+
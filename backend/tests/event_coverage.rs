@@ -0,0 +1,377 @@
+//! Event coverage harness
+//!
+//! CONSTITUTIONAL: All state changes emit events
+//! Runs each service call against a migrated database, diffs every table
+//! before and after, and fails when a table changed without one of the
+//! events allowed to change it (see TABLE_EVENT_COVERAGE).
+//!
+//! Requires DATABASE_URL; skipped when unset.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use rand::Rng;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use totp_lite::{totp_custom, Sha1};
+use ubi_backend::constants::EPOCH_LENGTH_SECONDS;
+use ubi_backend::events::TABLE_EVENT_COVERAGE;
+use ubi_backend::models::allowance::AllowanceRequest;
+use ubi_backend::models::conversion::ConversionRequest;
+use ubi_backend::models::invoice::InvoiceRequest;
+use ubi_backend::models::oracle::OracleSubmission;
+use ubi_backend::models::payment::PaymentRequest;
+use ubi_backend::models::standing_order::StandingOrderRequest;
+use ubi_backend::models::transfer::BUTransferRequest;
+use ubi_backend::services::allowance::AllowanceService;
+use ubi_backend::services::conversion::ConversionService;
+use ubi_backend::services::invoice::InvoiceService;
+use ubi_backend::services::oracle::OracleService;
+use ubi_backend::services::payment::PaymentService;
+use ubi_backend::services::rate_index::RateIndexService;
+use ubi_backend::services::registry::RegistryService;
+use ubi_backend::services::standing_order::StandingOrderService;
+use ubi_backend::services::transfer::TransferService;
+use ubi_backend::services::ubi::UBIService;
+
+/// Tables that hold no system state and are exempt from coverage
+const UNTRACKED_TABLES: &[&str] = &["events", "webhook_endpoints", "webhook_outbox", "idempotency_keys"];
+
+struct Harness {
+    pool: PgPool,
+    last_event_id: i64,
+    snapshot: BTreeMap<&'static str, serde_json::Value>,
+}
+
+impl Harness {
+    async fn new(pool: PgPool) -> Self {
+        let mut harness = Self {
+            pool,
+            last_event_id: 0,
+            snapshot: BTreeMap::new(),
+        };
+        harness.last_event_id = harness.max_event_id().await;
+        harness.snapshot = harness.take_snapshot().await;
+        harness
+    }
+    
+    async fn max_event_id(&self) -> i64 {
+        sqlx::query_scalar::<_, Option<i64>>("SELECT MAX(id) FROM events")
+            .fetch_one(&self.pool)
+            .await
+            .unwrap()
+            .unwrap_or(0)
+    }
+    
+    async fn take_snapshot(&self) -> BTreeMap<&'static str, serde_json::Value> {
+        let mut snapshot = BTreeMap::new();
+        for (table, _) in TABLE_EVENT_COVERAGE {
+            let rows: serde_json::Value = sqlx::query_scalar(&format!(
+                "SELECT COALESCE(jsonb_agg(to_jsonb(t) ORDER BY to_jsonb(t)::text), '[]'::jsonb) FROM {} t",
+                table
+            ))
+            .fetch_one(&self.pool)
+            .await
+            .unwrap();
+            snapshot.insert(*table, rows);
+        }
+        snapshot
+    }
+    
+    /// Diff all tables against the previous step and check event coverage
+    async fn check(&mut self, step: &str) {
+        let after = self.take_snapshot().await;
+        let new_events: BTreeSet<String> = sqlx::query_scalar::<_, String>(
+            "SELECT event_type FROM events WHERE id > $1"
+        )
+        .bind(self.last_event_id)
+        .fetch_all(&self.pool)
+        .await
+        .unwrap()
+        .into_iter()
+        .collect();
+        
+        for (table, allowed) in TABLE_EVENT_COVERAGE {
+            if self.snapshot.get(table) == after.get(table) {
+                continue;
+            }
+            let covered = allowed.iter().any(|t| new_events.contains(t.as_str()));
+            assert!(
+                covered,
+                "{}: table `{}` changed but none of {:?} was emitted (got {:?})",
+                step, table, allowed, new_events
+            );
+        }
+        
+        self.last_event_id = self.max_event_id().await;
+        self.snapshot = after;
+    }
+}
+
+fn random_hex(len: usize) -> String {
+    let bytes: Vec<u8> = (0..len).map(|_| rand::thread_rng().gen()).collect();
+    hex::encode(bytes)
+}
+
+#[tokio::test]
+async fn every_table_has_event_coverage() {
+    let Ok(url) = std::env::var("DATABASE_URL") else {
+        eprintln!("DATABASE_URL not set, skipping");
+        return;
+    };
+    let pool = PgPool::connect(&url).await.unwrap();
+    
+    let tables: Vec<String> = sqlx::query_scalar(
+        "SELECT table_name::text FROM information_schema.tables WHERE table_schema = 'public' AND table_type = 'BASE TABLE'"
+    )
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    
+    for table in tables {
+        let covered = TABLE_EVENT_COVERAGE.iter().any(|(t, _)| *t == table);
+        assert!(
+            covered || UNTRACKED_TABLES.contains(&table.as_str()),
+            "table `{}` has no entry in TABLE_EVENT_COVERAGE",
+            table
+        );
+    }
+}
+
+#[tokio::test]
+async fn service_calls_emit_events_for_every_write() {
+    let Ok(url) = std::env::var("DATABASE_URL") else {
+        eprintln!("DATABASE_URL not set, skipping");
+        return;
+    };
+    let pool = PgPool::connect(&url).await.unwrap();
+    let mut harness = Harness::new(pool.clone()).await;
+    
+    // Epoch 0 now; each shift of genesis by one epoch moves "now" one epoch on
+    let genesis = chrono::Utc::now().timestamp() - 60;
+    let next_epoch_genesis = genesis - EPOCH_LENGTH_SECONDS;
+    let region_id = 100_000 + rand::thread_rng().gen_range(0..100_000);
+    let person_id = random_hex(32);
+    let wallet = format!("0x{}", random_hex(20));
+    let new_wallet = format!("0x{}", random_hex(20));
+    
+    let registry = RegistryService::new(pool.clone());
+    registry.register_person(&person_id, &wallet, region_id, 1_000).await.unwrap();
+    harness.check("register_person").await;
+    
+    OracleService::new(pool.clone())
+        .submit_data(OracleSubmission {
+            region_id,
+            basket_index_wad: Decimal::from(1_000_000_000_000_000_000u64),
+        })
+        .await
+        .unwrap();
+    harness.check("submit_oracle").await;
+    
+    RateIndexService::new(pool.clone(), genesis).roll_rate_index(region_id).await.unwrap();
+    harness.check("roll_rate_index (init)").await;
+    
+    RateIndexService::new(pool.clone(), next_epoch_genesis).roll_rate_index(region_id).await.unwrap();
+    harness.check("roll_rate_index (decay)").await;
+    
+    // Claims compare against a default last-claimed epoch of 0, so claim in epoch 1
+    UBIService::new(pool.clone(), RegistryService::new(pool.clone()), next_epoch_genesis)
+        .claim_ubi(&wallet)
+        .await
+        .unwrap();
+    harness.check("claim_ubi").await;
+    
+    let conversion_service = |genesis| {
+        ConversionService::new(
+            pool.clone(),
+            RegistryService::new(pool.clone()),
+            RateIndexService::new(pool.clone(), genesis),
+            genesis,
+        )
+    };
+    let conversion = conversion_service(next_epoch_genesis)
+        .request_conversion(&wallet, ConversionRequest {
+            amount_ue: Decimal::from(1_000_000_000_000_000_000u64),
+            min_bu_out: Decimal::ZERO,
+        })
+        .await
+        .unwrap();
+    harness.check("request_conversion").await;
+    
+    let cancelled = conversion_service(next_epoch_genesis)
+        .request_conversion(&wallet, ConversionRequest {
+            amount_ue: Decimal::from(1_000_000_000_000_000_000u64),
+            min_bu_out: Decimal::ZERO,
+        })
+        .await
+        .unwrap();
+    conversion_service(next_epoch_genesis)
+        .cancel_conversion(&wallet, cancelled.conversion_id)
+        .await
+        .unwrap();
+    harness.check("cancel_conversion").await;
+    
+    conversion_service(next_epoch_genesis - EPOCH_LENGTH_SECONDS)
+        .claim_converted_bu(&wallet, conversion.conversion_id)
+        .await
+        .unwrap();
+    harness.check("claim_converted_bu").await;
+    
+    for (step, auto_claim) in [("settle_due", false), ("settle_due_auto_claim", true)] {
+        conversion_service(next_epoch_genesis)
+            .request_conversion(&wallet, ConversionRequest {
+                amount_ue: Decimal::from(1_000_000_000_000_000_000u64),
+                min_bu_out: Decimal::ZERO,
+            })
+            .await
+            .unwrap();
+        conversion_service(next_epoch_genesis - EPOCH_LENGTH_SECONDS)
+            .settle_due(auto_claim, 1000)
+            .await
+            .unwrap();
+        harness.check(step).await;
+    }
+    
+    conversion_service(next_epoch_genesis - EPOCH_LENGTH_SECONDS)
+        .ration_queued()
+        .await
+        .unwrap();
+    harness.check("ration_queued").await;
+    
+    PaymentService::new(pool.clone(), RegistryService::new(pool.clone()))
+        .pay(&wallet, PaymentRequest {
+            to_wallet: format!("0x{}", random_hex(20)),
+            amount_ue: Decimal::from(1000),
+            memo: Some("coverage".to_string()),
+        })
+        .await
+        .unwrap();
+    harness.check("pay").await;
+    
+    TransferService::new(pool.clone())
+        .transfer_bu(&wallet, BUTransferRequest {
+            to_wallet: format!("0x{}", random_hex(20)),
+            amount_bu: Decimal::ONE,
+            memo: None,
+        })
+        .await
+        .unwrap();
+    harness.check("transfer_bu").await;
+    
+    let invoice_service = |genesis| {
+        InvoiceService::new(pool.clone(), RegistryService::new(pool.clone()), genesis)
+    };
+    let merchant = format!("0x{}", random_hex(20));
+    let invoice_request = || InvoiceRequest {
+        amount_ue: Decimal::from(1000),
+        reference: "coverage".to_string(),
+        expiry_epoch: 1,
+    };
+    let invoice = invoice_service(next_epoch_genesis)
+        .create(&merchant, invoice_request())
+        .await
+        .unwrap();
+    harness.check("create_invoice").await;
+    
+    invoice_service(next_epoch_genesis)
+        .pay(&wallet, invoice.invoice.id)
+        .await
+        .unwrap();
+    harness.check("pay_invoice").await;
+    
+    invoice_service(next_epoch_genesis)
+        .create(&merchant, invoice_request())
+        .await
+        .unwrap();
+    invoice_service(next_epoch_genesis - EPOCH_LENGTH_SECONDS)
+        .expire_overdue()
+        .await
+        .unwrap();
+    harness.check("expire_invoices").await;
+    
+    let standing_orders = StandingOrderService::new(
+        pool.clone(),
+        RegistryService::new(pool.clone()),
+        next_epoch_genesis,
+    );
+    let standing_order_request = |amount_ue| StandingOrderRequest {
+        to_wallet: format!("0x{}", random_hex(20)),
+        amount_ue,
+        memo: Some("rent".to_string()),
+        offset_seconds: 0,
+    };
+    let payer = hex::decode(&person_id).unwrap();
+    standing_orders.create(&wallet, standing_order_request(Decimal::from(1000))).await.unwrap();
+    harness.check("create_standing_order").await;
+    
+    standing_orders.execute_due(Some(&payer), usize::MAX).await.unwrap();
+    harness.check("execute_standing_order").await;
+    
+    // More than the wallet holds, so this epoch's run is skipped
+    let unaffordable = standing_orders
+        .create(&wallet, standing_order_request("1000000000000000000000000".parse().unwrap()))
+        .await
+        .unwrap();
+    standing_orders.execute_due(Some(&payer), usize::MAX).await.unwrap();
+    harness.check("skip_standing_order").await;
+    
+    standing_orders.cancel(&wallet, unaffordable.id).await.unwrap();
+    harness.check("cancel_standing_order").await;
+    
+    // Three epochs on, runs the worker never made are skipped as missed, not paid back to back
+    let missed = standing_orders.create(&wallet, standing_order_request(Decimal::from(1000))).await.unwrap();
+    StandingOrderService::new(
+        pool.clone(),
+        RegistryService::new(pool.clone()),
+        next_epoch_genesis - 3 * EPOCH_LENGTH_SECONDS,
+    )
+    .execute_due(Some(&payer), usize::MAX)
+    .await
+    .unwrap();
+    harness.check("skip_missed_standing_order").await;
+    let skip_reasons: Vec<Option<String>> = sqlx::query_scalar(
+        "SELECT skip_reason FROM standing_order_executions WHERE order_id = $1 ORDER BY epoch",
+    )
+    .bind(missed.id)
+    .fetch_all(&pool)
+    .await
+    .unwrap();
+    let is_missed = |reason: &Option<String>| reason.as_deref().is_some_and(|r| r.starts_with("Missed"));
+    assert_eq!(skip_reasons.len(), 3, "two missed epochs and the last epoch's end-of-epoch run");
+    assert!(skip_reasons[..2].iter().all(is_missed), "{:?}", skip_reasons);
+    assert!(!is_missed(&skip_reasons[2]), "{:?}", skip_reasons);
+    
+    let allowances = AllowanceService::new(pool.clone(), RegistryService::new(pool.clone()), next_epoch_genesis);
+    let spender = format!("0x{}", random_hex(20));
+    let allowance = allowances
+        .approve(&wallet, AllowanceRequest {
+            spender_wallet: spender.clone(),
+            amount_per_epoch: Decimal::from(1000),
+        })
+        .await
+        .unwrap();
+    harness.check("approve_allowance").await;
+    
+    allowances
+        .spend(&spender, allowance.id, PaymentRequest {
+            to_wallet: format!("0x{}", random_hex(20)),
+            amount_ue: Decimal::from(600),
+            memo: None,
+        })
+        .await
+        .unwrap();
+    harness.check("spend_allowance").await;
+    
+    allowances.revoke(&wallet, allowance.id).await.unwrap();
+    harness.check("revoke_allowance").await;
+    
+    let mfa_secret: String = sqlx::query_scalar("SELECT mfa_secret FROM users WHERE wallet_address = $1")
+        .bind(&wallet)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    let secret = base32::decode(base32::Alphabet::RFC4648 { padding: false }, &mfa_secret).unwrap();
+    let code = totp_custom::<Sha1>(30, 6, &secret, chrono::Utc::now().timestamp() as u64);
+    registry.reset_wallet(&person_id, &new_wallet, &code).await.unwrap();
+    harness.check("reset_wallet").await;
+}
+
@@ -0,0 +1,179 @@
+//! Fork bootstrap: the fork passes its own replay check, refuses a target
+//! with history and applies only amendments the constitution allows
+//!
+//! The parent gets a freshly migrated database from DATABASE_URL; each fork
+//! gets a second one created beside it and dropped at the end.
+
+use rand::Rng;
+use rust_decimal::Decimal;
+use sqlx::postgres::PgPoolOptions;
+use sqlx::PgPool;
+use ubi_backend::constants::EPOCH_LENGTH_SECONDS;
+use ubi_backend::events::Event;
+use ubi_backend::models::constitution::ConstitutionAmendment;
+use ubi_backend::models::conversion::ConversionRequest;
+use ubi_backend::models::oracle::OracleSubmission;
+use ubi_backend::services::constitution::ConstitutionService;
+use ubi_backend::services::conversion::ConversionService;
+use ubi_backend::services::fork::ForkService;
+use ubi_backend::services::oracle::OracleService;
+use ubi_backend::services::rate_index::RateIndexService;
+use ubi_backend::services::registry::RegistryService;
+use ubi_backend::services::replay::ReplayService;
+use ubi_backend::services::ubi::UBIService;
+use ubi_backend::utils::errors::UBIError;
+
+/// `n` whole UE (or BU) in base units
+fn ue(n: u64) -> Decimal {
+    Decimal::from(n) * Decimal::from(1_000_000_000_000_000_000u64)
+}
+
+fn random_hex(len: usize) -> String {
+    let bytes: Vec<u8> = (0..len).map(|_| rand::thread_rng().gen()).collect();
+    hex::encode(bytes)
+}
+
+/// Genesis timestamp that puts "now" a minute into `epoch`
+fn genesis_for(epoch: i32) -> i64 {
+    chrono::Utc::now().timestamp() - 60 - epoch as i64 * EPOCH_LENGTH_SECONDS
+}
+
+/// A parent history: a claim, a settled conversion and one still pending
+async fn parent_history(pool: &PgPool, genesis: i64) -> Vec<Event> {
+    let registry = RegistryService::new(pool.clone());
+    let wallet = format!("0x{}", random_hex(20));
+    registry.register_person(&random_hex(32), &wallet, 1, 10_000).await.unwrap();
+    OracleService::new(pool.clone())
+        .submit_data(OracleSubmission {
+            region_id: 1,
+            basket_index_wad: ue(1),
+        })
+        .await
+        .unwrap();
+    RateIndexService::new(pool.clone(), genesis).roll_rate_index(1).await.unwrap();
+    UBIService::new(pool.clone(), RegistryService::new(pool.clone()), genesis)
+        .claim_ubi(&wallet)
+        .await
+        .unwrap();
+    let conversions = |genesis| {
+        ConversionService::new(
+            pool.clone(),
+            RegistryService::new(pool.clone()),
+            RateIndexService::new(pool.clone(), genesis),
+            genesis,
+        )
+    };
+    let request = |amount| ConversionRequest {
+        amount_ue: ue(amount),
+        min_bu_out: Decimal::ZERO,
+    };
+    conversions(genesis).request_conversion(&wallet, request(100)).await.unwrap();
+    // Two epochs on, the first conversion settles and a second is left pending
+    let later = genesis_for(12);
+    conversions(later).settle_due(true, 10).await.unwrap();
+    conversions(later).request_conversion(&wallet, request(50)).await.unwrap();
+
+    let parent = ReplayService::new(pool.clone(), genesis);
+    assert!(parent.verify().await.unwrap().consistent);
+    parent.load_events().await.unwrap()
+}
+
+/// A second database beside the parent's, migrated the same way
+async fn migrated_database(pool: &PgPool) -> (PgPool, String) {
+    let name = format!("fork_{}", random_hex(8));
+    sqlx::query(&format!("CREATE DATABASE {}", name)).execute(pool).await.unwrap();
+    let fork = PgPoolOptions::new()
+        .connect_with((*pool.connect_options()).clone().database(&name))
+        .await
+        .unwrap();
+    sqlx::migrate!("./migrations").run(&fork).await.unwrap();
+    (fork, name)
+}
+
+async fn drop_database(pool: &PgPool, fork: PgPool, name: &str) {
+    fork.close().await;
+    sqlx::query(&format!("DROP DATABASE {}", name)).execute(pool).await.unwrap();
+}
+
+fn amendment(conversion_fee_bps: i32, conversion_delay_epochs: i32) -> ConstitutionAmendment {
+    ConstitutionAmendment {
+        effective_epoch: 20,
+        ue_mint_per_epoch: None,
+        conversion_fee_bps: Some(conversion_fee_bps),
+        conversion_cap_ue: None,
+        conversion_delay_epochs: Some(conversion_delay_epochs),
+        cancellation_refunds_fee: None,
+        rationing_threshold_bu: None,
+    }
+}
+
+#[sqlx::test]
+async fn fork_of_a_live_parent_replays_cleanly(pool: PgPool) {
+    let genesis = genesis_for(10);
+    let events = parent_history(&pool, genesis).await;
+    let (fork, name) = migrated_database(&pool).await;
+
+    let report = ForkService::new(fork.clone(), genesis).bootstrap(events.clone(), None).await.unwrap();
+    assert_eq!(report.events_imported, events.len() as i64);
+
+    let replayed = ReplayService::new(fork.clone(), genesis).verify().await.unwrap();
+    assert!(replayed.consistent, "{:?}", replayed.divergences);
+    assert_eq!(replayed.events_replayed, events.len() + 1);
+
+    drop_database(&pool, fork, &name).await;
+}
+
+#[sqlx::test]
+async fn fork_refuses_a_target_with_journal_or_treasury_history(pool: PgPool) {
+    let genesis = genesis_for(10);
+    let events = parent_history(&pool, genesis).await;
+
+    // Neither leaves an event or a user behind
+    let histories = [
+        "INSERT INTO treasury (balance_bu, reserved_bu) VALUES (999000000000000000000000, 0)",
+        r#"
+        WITH entry AS (INSERT INTO journal_entries (description) VALUES ('Legacy') RETURNING id)
+        INSERT INTO journal_postings (entry_id, account, unit, amount)
+        SELECT id, account, 'UE', amount FROM entry, (VALUES ('mint', -1), ('wallet:0xabc', 1)) AS p(account, amount)
+        "#,
+    ];
+    for history in histories {
+        let (fork, name) = migrated_database(&pool).await;
+        sqlx::query(history).execute(&fork).await.unwrap();
+
+        let refused = ForkService::new(fork.clone(), genesis).bootstrap(events.clone(), None).await;
+        assert!(matches!(refused, Err(UBIError::Other(ref e)) if e.contains("not empty")), "{:?}", refused);
+
+        drop_database(&pool, fork, &name).await;
+    }
+}
+
+#[sqlx::test]
+async fn amendments_must_keep_fee_and_delay_in_range(pool: PgPool) {
+    let constitution = ConstitutionService::new(pool.clone());
+    for (fee, delay) in [(10_001, 1), (-1, 1), (50, -1)] {
+        let rejected = constitution.amend(amendment(fee, delay)).await;
+        assert!(matches!(rejected, Err(UBIError::InvalidInput(_))), "{:?}", rejected);
+    }
+    let amended = constitution.amend(amendment(10_000, 0)).await.unwrap();
+    assert_eq!((amended.conversion_fee_bps, amended.conversion_delay_epochs), (10_000, 0));
+}
+
+#[sqlx::test]
+async fn fork_with_an_invalid_amendment_leaves_the_target_empty(pool: PgPool) {
+    let genesis = genesis_for(10);
+    let events = parent_history(&pool, genesis).await;
+    let (fork, name) = migrated_database(&pool).await;
+    let forks = ForkService::new(fork.clone(), genesis);
+
+    let rejected = forks.bootstrap(events.clone(), Some(amendment(20_000, 1))).await;
+    assert!(matches!(rejected, Err(UBIError::InvalidInput(_))), "{:?}", rejected);
+    let imported: i64 = sqlx::query_scalar("SELECT COUNT(*) FROM events").fetch_one(&fork).await.unwrap();
+    assert_eq!(imported, 0);
+
+    let report = forks.bootstrap(events, Some(amendment(100, 2))).await.unwrap();
+    assert_eq!(report.constitution.unwrap().conversion_fee_bps, 100);
+
+    drop_database(&pool, fork, &name).await;
+}
+
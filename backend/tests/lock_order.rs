@@ -0,0 +1,206 @@
+//! Concurrent writers on one wallet must not deadlock
+//!
+//! Claims, conversion requests, settlement, payments and BU transfers all
+//! lock balance rows and emit events. Every emitting transaction takes the event
+//! chain lock before its first row lock, so none of them can wait on another
+//! while holding what the other needs.
+//!
+//! Each test gets a freshly migrated database from DATABASE_URL.
+
+use std::future::Future;
+use std::pin::Pin;
+use std::time::Duration;
+
+use futures::future::join_all;
+use rand::Rng;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use ubi_backend::constants::EPOCH_LENGTH_SECONDS;
+use ubi_backend::models::conversion::ConversionRequest;
+use ubi_backend::models::oracle::OracleSubmission;
+use ubi_backend::models::payment::PaymentRequest;
+use ubi_backend::models::transfer::BUTransferRequest;
+use ubi_backend::services::conversion::ConversionService;
+use ubi_backend::services::oracle::OracleService;
+use ubi_backend::services::payment::PaymentService;
+use ubi_backend::services::rate_index::RateIndexService;
+use ubi_backend::services::registry::RegistryService;
+use ubi_backend::services::transfer::TransferService;
+use ubi_backend::services::ubi::UBIService;
+use ubi_backend::utils::errors::UBIError;
+
+/// `n` whole UE (or BU) in base units
+fn ue(n: u64) -> Decimal {
+    Decimal::from(n) * Decimal::from(1_000_000_000_000_000_000u64)
+}
+
+fn random_hex(len: usize) -> String {
+    let bytes: Vec<u8> = (0..len).map(|_| rand::thread_rng().gen()).collect();
+    hex::encode(bytes)
+}
+
+/// Genesis timestamp that puts "now" a minute into `epoch`
+fn genesis_for(epoch: i32) -> i64 {
+    chrono::Utc::now().timestamp() - 60 - epoch as i64 * EPOCH_LENGTH_SECONDS
+}
+
+fn is_deadlock(e: &UBIError) -> bool {
+    matches!(e, UBIError::Database(sqlx::Error::Database(db)) if db.code().as_deref() == Some("40P01"))
+}
+
+type Op = Pin<Box<dyn Future<Output = Result<(), UBIError>>>>;
+
+#[sqlx::test]
+async fn concurrent_claims_conversions_and_credits_do_not_deadlock(pool: PgPool) {
+    let registry = RegistryService::new(pool.clone());
+    let region_id = 1;
+    let wallet = format!("0x{}", random_hex(20));
+    let other_wallet = format!("0x{}", random_hex(20));
+    registry.register_person(&random_hex(32), &wallet, region_id, 10_000).await.unwrap();
+    registry.register_person(&random_hex(32), &other_wallet, region_id, 10_000).await.unwrap();
+    OracleService::new(pool.clone())
+        .submit_data(OracleSubmission {
+            region_id,
+            basket_index_wad: ue(1),
+        })
+        .await
+        .unwrap();
+
+    let start = 10;
+    RateIndexService::new(pool.clone(), genesis_for(start)).roll_rate_index(region_id).await.unwrap();
+    for wallet in [&wallet, &other_wallet] {
+        UBIService::new(pool.clone(), RegistryService::new(pool.clone()), genesis_for(start))
+            .claim_ubi(wallet)
+            .await
+            .unwrap();
+    }
+
+    let conversions = |epoch| {
+        ConversionService::new(
+            pool.clone(),
+            RegistryService::new(pool.clone()),
+            RateIndexService::new(pool.clone(), genesis_for(epoch)),
+            genesis_for(epoch),
+        )
+    };
+    // The other wallet holds BU to send while the rest runs
+    conversions(start)
+        .request_conversion(&other_wallet, ConversionRequest {
+            amount_ue: ue(100),
+            min_bu_out: Decimal::ZERO,
+        })
+        .await
+        .unwrap();
+    conversions(start + 100).settle_due(true, 10).await.unwrap();
+
+    let ops: Vec<Op> = (0..60)
+        .map(|i| {
+            let (pool, wallet, other_wallet) = (pool.clone(), wallet.clone(), other_wallet.clone());
+            let requests = conversions(start);
+            let settlement = conversions(start + 100);
+            match i % 5 {
+                // Each claim lands in an epoch of its own
+                0 => Box::pin(async move {
+                    UBIService::new(pool.clone(), RegistryService::new(pool), genesis_for(start + 1 + i))
+                        .claim_ubi(&wallet)
+                        .await
+                        .map(drop)
+                }) as Op,
+                1 | 2 => Box::pin(async move {
+                    requests
+                        .request_conversion(&wallet, ConversionRequest {
+                            amount_ue: ue(1),
+                            min_bu_out: Decimal::ZERO,
+                        })
+                        .await
+                        .map(drop)
+                }),
+                3 => Box::pin(async move { settlement.settle_due(true, 10).await.map(drop) }),
+                _ => Box::pin(async move {
+                    TransferService::new(pool)
+                        .transfer_bu(&other_wallet, BUTransferRequest {
+                            to_wallet: wallet,
+                            amount_bu: Decimal::ONE,
+                            memo: None,
+                        })
+                        .await
+                        .map(drop)
+                }),
+            }
+        })
+        .collect();
+
+    let results = tokio::time::timeout(Duration::from_secs(60), join_all(ops))
+        .await
+        .expect("concurrent writers stalled");
+    let deadlocks: Vec<_> = results
+        .iter()
+        .filter_map(|r| r.as_ref().err())
+        .filter(|e| is_deadlock(e))
+        .collect();
+    assert!(deadlocks.is_empty(), "{:?}", deadlocks);
+
+    // A claim that lands after a later epoch's is refused; nothing else fails
+    assert!(
+        results.iter().all(|r| matches!(r, Ok(()) | Err(UBIError::AlreadyClaimed(_)))),
+        "{:?}",
+        results
+    );
+}
+
+#[sqlx::test]
+async fn concurrent_payments_and_claims_do_not_deadlock(pool: PgPool) {
+    let registry = RegistryService::new(pool.clone());
+    let wallets = [format!("0x{}", random_hex(20)), format!("0x{}", random_hex(20))];
+    let start = 10;
+    for wallet in &wallets {
+        registry.register_person(&random_hex(32), wallet, 1, 10_000).await.unwrap();
+        UBIService::new(pool.clone(), RegistryService::new(pool.clone()), genesis_for(start))
+            .claim_ubi(wallet)
+            .await
+            .unwrap();
+    }
+
+    // Payments both ways lock both balance rows, in opposite orders
+    let ops: Vec<Op> = (0..60)
+        .map(|i| {
+            let pool = pool.clone();
+            let from = wallets[i as usize % 2].clone();
+            let to = wallets[(i as usize + 1) % 2].clone();
+            match i % 3 {
+                0 => Box::pin(async move {
+                    UBIService::new(pool.clone(), RegistryService::new(pool), genesis_for(start + 1 + i))
+                        .claim_ubi(&from)
+                        .await
+                        .map(drop)
+                }) as Op,
+                _ => Box::pin(async move {
+                    PaymentService::new(pool.clone(), RegistryService::new(pool))
+                        .pay(&from, PaymentRequest {
+                            to_wallet: to,
+                            amount_ue: ue(1),
+                            memo: None,
+                        })
+                        .await
+                        .map(drop)
+                }),
+            }
+        })
+        .collect();
+
+    let results = tokio::time::timeout(Duration::from_secs(60), join_all(ops))
+        .await
+        .expect("concurrent writers stalled");
+    let deadlocks: Vec<_> = results
+        .iter()
+        .filter_map(|r| r.as_ref().err())
+        .filter(|e| is_deadlock(e))
+        .collect();
+    assert!(deadlocks.is_empty(), "{:?}", deadlocks);
+    assert!(
+        results.iter().all(|r| matches!(r, Ok(()) | Err(UBIError::AlreadyClaimed(_)))),
+        "{:?}",
+        results
+    );
+}
+
@@ -0,0 +1,118 @@
+//! Wallet statements reconcile with the live balances
+//!
+//! Each test gets a freshly migrated database from DATABASE_URL.
+
+use rand::Rng;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use ubi_backend::constants::EPOCH_LENGTH_SECONDS;
+use ubi_backend::models::conversion::ConversionRequest;
+use ubi_backend::models::oracle::OracleSubmission;
+use ubi_backend::models::payment::PaymentRequest;
+use ubi_backend::models::statement::Statement;
+use ubi_backend::services::conversion::ConversionService;
+use ubi_backend::services::oracle::OracleService;
+use ubi_backend::services::payment::PaymentService;
+use ubi_backend::services::rate_index::RateIndexService;
+use ubi_backend::services::registry::RegistryService;
+use ubi_backend::services::statement::StatementService;
+use ubi_backend::services::ubi::UBIService;
+
+/// `n` whole UE (or BU) in base units
+fn ue(n: u64) -> Decimal {
+    Decimal::from(n) * Decimal::from(1_000_000_000_000_000_000u64)
+}
+
+fn random_hex(len: usize) -> String {
+    let bytes: Vec<u8> = (0..len).map(|_| rand::thread_rng().gen()).collect();
+    hex::encode(bytes)
+}
+
+/// Genesis timestamp that puts "now" a minute into `epoch`
+fn genesis_for(epoch: i32) -> i64 {
+    chrono::Utc::now().timestamp() - 60 - epoch as i64 * EPOCH_LENGTH_SECONDS
+}
+
+fn line_total(statement: &Statement, unit: &str) -> Decimal {
+    statement.lines.iter().filter(|l| l.unit == unit).map(|l| l.amount).sum()
+}
+
+#[sqlx::test]
+async fn statement_lines_add_up_to_the_live_balances(pool: PgPool) {
+    let genesis = genesis_for(10);
+    let (alice_wallet, bob_wallet) = (format!("0x{}", random_hex(20)), format!("0x{}", random_hex(20)));
+    for wallet in [&alice_wallet, &bob_wallet] {
+        RegistryService::new(pool.clone()).register_person(&random_hex(32), wallet, 1, 10_000).await.unwrap();
+    }
+    OracleService::new(pool.clone())
+        .submit_data(OracleSubmission {
+            region_id: 1,
+            basket_index_wad: ue(1),
+        })
+        .await
+        .unwrap();
+    RateIndexService::new(pool.clone(), genesis).roll_rate_index(1).await.unwrap();
+    for wallet in [&alice_wallet, &bob_wallet] {
+        UBIService::new(pool.clone(), RegistryService::new(pool.clone()), genesis)
+            .claim_ubi(wallet)
+            .await
+            .unwrap();
+    }
+
+    // Bob pays Alice, then she converts; the conversion settles two epochs on
+    PaymentService::new(pool.clone(), RegistryService::new(pool.clone()))
+        .pay(&bob_wallet, PaymentRequest {
+            to_wallet: alice_wallet.clone(),
+            amount_ue: ue(10),
+            memo: None,
+        })
+        .await
+        .unwrap();
+    let conversions = |genesis| {
+        ConversionService::new(
+            pool.clone(),
+            RegistryService::new(pool.clone()),
+            RateIndexService::new(pool.clone(), genesis),
+            genesis,
+        )
+    };
+    conversions(genesis)
+        .request_conversion(&alice_wallet, ConversionRequest {
+            amount_ue: ue(100),
+            min_bu_out: Decimal::ZERO,
+        })
+        .await
+        .unwrap();
+    conversions(genesis_for(12)).settle_due(true, 10).await.unwrap();
+
+    let statements = StatementService::new(pool.clone(), genesis);
+    let statement = statements.statement(&alice_wallet, 0, None).await.unwrap();
+    assert_eq!((statement.opening_ue, statement.opening_bu), (Decimal::ZERO, Decimal::ZERO));
+    assert_eq!(line_total(&statement, "UE"), statement.closing_ue - statement.opening_ue);
+    assert_eq!(line_total(&statement, "BU"), statement.closing_bu - statement.opening_bu);
+
+    let live_ue: Decimal = sqlx::query_scalar("SELECT balance FROM ue_balances WHERE wallet_address = $1")
+        .bind(&alice_wallet)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    let live_bu: Decimal = sqlx::query_scalar("SELECT balance FROM bu_balances WHERE wallet_address = $1")
+        .bind(&alice_wallet)
+        .fetch_one(&pool)
+        .await
+        .unwrap();
+    assert_eq!((statement.closing_ue, statement.closing_bu), (live_ue, live_bu));
+
+    let event_types: Vec<(&str, &str)> =
+        statement.lines.iter().map(|l| (l.event_type.as_str(), l.unit.as_str())).collect();
+    for expected in [("UBIClaimed", "UE"), ("UEPaid", "UE"), ("ConversionRequested", "UE"), ("ConversionClaimed", "BU")] {
+        assert!(event_types.contains(&expected), "no {:?} in {:?}", expected, event_types);
+    }
+
+    // A window after every event opens and closes on the same balances
+    let quiet = statements.statement(&alice_wallet, 11, Some(11)).await.unwrap();
+    assert!(quiet.lines.is_empty(), "{:?}", quiet.lines);
+    assert_eq!((quiet.opening_ue, quiet.opening_bu), (live_ue, live_bu));
+    assert_eq!((quiet.closing_ue, quiet.closing_bu), (live_ue, live_bu));
+}
+
@@ -0,0 +1,102 @@
+//! Supply identities hold through claim, conversion and settlement
+//!
+//! Each test gets a freshly migrated database from DATABASE_URL.
+
+use rand::Rng;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use ubi_backend::constants::{BU_TOTAL_SUPPLY, EPOCH_LENGTH_SECONDS};
+use ubi_backend::models::conversion::ConversionRequest;
+use ubi_backend::models::oracle::OracleSubmission;
+use ubi_backend::services::conversion::ConversionService;
+use ubi_backend::services::oracle::OracleService;
+use ubi_backend::services::rate_index::RateIndexService;
+use ubi_backend::services::registry::RegistryService;
+use ubi_backend::services::supply::SupplyService;
+use ubi_backend::services::ubi::UBIService;
+
+/// `n` whole UE (or BU) in base units
+fn ue(n: u64) -> Decimal {
+    Decimal::from(n) * Decimal::from(1_000_000_000_000_000_000u64)
+}
+
+fn random_hex(len: usize) -> String {
+    let bytes: Vec<u8> = (0..len).map(|_| rand::thread_rng().gen()).collect();
+    hex::encode(bytes)
+}
+
+/// Genesis timestamp that puts "now" a minute into `epoch`
+fn genesis_for(epoch: i32) -> i64 {
+    chrono::Utc::now().timestamp() - 60 - epoch as i64 * EPOCH_LENGTH_SECONDS
+}
+
+fn amount(text: &str) -> Decimal {
+    text.parse().unwrap()
+}
+
+#[sqlx::test]
+async fn supply_balances_after_claim_convert_and_settle(pool: PgPool) {
+    let genesis = genesis_for(10);
+    let wallet = format!("0x{}", random_hex(20));
+    RegistryService::new(pool.clone()).register_person(&random_hex(32), &wallet, 1, 10_000).await.unwrap();
+    OracleService::new(pool.clone())
+        .submit_data(OracleSubmission {
+            region_id: 1,
+            basket_index_wad: ue(1),
+        })
+        .await
+        .unwrap();
+    RateIndexService::new(pool.clone(), genesis).roll_rate_index(1).await.unwrap();
+    let claim = UBIService::new(pool.clone(), RegistryService::new(pool.clone()), genesis)
+        .claim_ubi(&wallet)
+        .await
+        .unwrap();
+
+    let conversions = |genesis| {
+        ConversionService::new(
+            pool.clone(),
+            RegistryService::new(pool.clone()),
+            RateIndexService::new(pool.clone(), genesis),
+            genesis,
+        )
+    };
+    let request = |amount| ConversionRequest {
+        amount_ue: ue(amount),
+        min_bu_out: Decimal::ZERO,
+    };
+    // The first conversion settles two epochs on; the second is left pending
+    let settled = conversions(genesis).request_conversion(&wallet, request(100)).await.unwrap();
+    let later = genesis_for(12);
+    assert_eq!(conversions(later).settle_due(true, 10).await.unwrap(), 1);
+    let pending = conversions(later).request_conversion(&wallet, request(50)).await.unwrap();
+
+    let report = SupplyService::new(pool.clone(), later).check().await.unwrap();
+    assert!(report.healthy, "{:?}", report.violations);
+
+    // UE: everything minted is held, burned or taken as fees
+    let ue_minted = amount(&report.ue_minted);
+    assert_eq!(ue_minted, claim.amount_ue);
+    assert_eq!(
+        ue_minted,
+        amount(&report.ue_circulating) + amount(&report.ue_burned) + amount(&report.ue_fees)
+    );
+    assert_eq!(amount(&report.ue_burned) + amount(&report.ue_fees), ue(150));
+
+    // BU: the genesis supply is split between treasury, reserve and wallets
+    assert_eq!(amount(&report.bu_wallets), settled.amount_bu);
+    assert_eq!(amount(&report.bu_reserved), pending.amount_bu);
+    assert_eq!(amount(&report.bu_pending), pending.amount_bu);
+    assert_eq!(
+        amount(&report.bu_treasury) + amount(&report.bu_reserved) + amount(&report.bu_wallets),
+        amount(BU_TOTAL_SUPPLY)
+    );
+
+    // The per-epoch history ends at the same totals
+    let epochs = SupplyService::new(pool.clone(), later).epochs().await.unwrap();
+    let last = epochs.last().unwrap();
+    assert_eq!(
+        (&last.ue_minted, &last.ue_circulating, &last.bu_reserved, &last.bu_wallets),
+        (&report.ue_minted, &report.ue_circulating, &report.bu_reserved, &report.bu_wallets)
+    );
+}
+
@@ -0,0 +1,95 @@
+//! Bearer tokens against the registry: revocation on wallet reset,
+//! tokens for former wallets and key wallets
+//!
+//! Each test gets a freshly migrated database from DATABASE_URL.
+
+use ed25519_dalek::SigningKey;
+use rand::Rng;
+use sqlx::PgPool;
+use totp_lite::{totp_custom, Sha1};
+use ubi_backend::services::registry::RegistryService;
+use ubi_backend::utils::auth::{key_wallet_address, Claims, TokenHolder};
+use ubi_backend::utils::errors::UBIError;
+
+fn random_hex(len: usize) -> String {
+    let bytes: Vec<u8> = (0..len).map(|_| rand::thread_rng().gen()).collect();
+    hex::encode(bytes)
+}
+
+async fn mfa_code(pool: &PgPool, person_id: &str) -> String {
+    let mfa_secret: String = sqlx::query_scalar("SELECT mfa_secret FROM users WHERE person_id = $1")
+        .bind(hex::decode(person_id).unwrap())
+        .fetch_one(pool)
+        .await
+        .unwrap();
+    let secret = base32::decode(base32::Alphabet::RFC4648 { padding: false }, &mfa_secret).unwrap();
+    totp_custom::<Sha1>(30, 6, &secret, chrono::Utc::now().timestamp() as u64)
+}
+
+#[sqlx::test]
+async fn wallet_reset_revokes_earlier_tokens(pool: PgPool) {
+    let registry = RegistryService::new(pool.clone());
+    let person_id = random_hex(32);
+    let old_wallet = format!("0x{}", random_hex(20));
+    let new_wallet = format!("0x{}", random_hex(20));
+    registry.register_person(&person_id, &old_wallet, 1, 1_000).await.unwrap();
+
+    let user = registry.authenticate(&person_id, &mfa_code(&pool, &person_id).await).await.unwrap();
+    let wallet = registry.token_wallet(&user, None).await.unwrap();
+    assert_eq!(wallet, old_wallet);
+    let before_reset = Claims::new(
+        person_id.clone(),
+        wallet,
+        TokenHolder::Person { registry_wallet: old_wallet.clone() },
+    );
+    registry.check_token(&before_reset).await.unwrap();
+
+    registry.reset_wallet(&person_id, &new_wallet, &mfa_code(&pool, &person_id).await).await.unwrap();
+    assert!(matches!(registry.check_token(&before_reset).await, Err(UBIError::Unauthorized)));
+
+    // A fresh token may still act for the wallet reset away from, to move its BU on
+    let user = registry.authenticate(&person_id, &mfa_code(&pool, &person_id).await).await.unwrap();
+    let wallet = registry.token_wallet(&user, Some(&old_wallet)).await.unwrap();
+    let former = Claims::new(
+        person_id.clone(),
+        wallet,
+        TokenHolder::Person { registry_wallet: new_wallet.clone() },
+    );
+    registry.check_token(&former).await.unwrap();
+
+    let never_held = format!("0x{}", random_hex(20));
+    assert!(matches!(
+        registry.token_wallet(&user, Some(&never_held)).await,
+        Err(UBIError::Forbidden(_))
+    ));
+
+    // Once another person registers the old wallet it is theirs alone
+    registry.register_person(&random_hex(32), &old_wallet, 1, 1_000).await.unwrap();
+    assert!(matches!(registry.check_token(&former).await, Err(UBIError::Unauthorized)));
+    assert!(matches!(
+        registry.token_wallet(&user, Some(&old_wallet)).await,
+        Err(UBIError::Forbidden(_))
+    ));
+}
+
+#[sqlx::test]
+async fn key_wallet_tokens_must_match_the_key(pool: PgPool) {
+    let registry = RegistryService::new(pool.clone());
+    let key = SigningKey::from_bytes(&rand::thread_rng().gen());
+    let public_key = hex::encode(key.verifying_key().to_bytes());
+    let wallet = key_wallet_address(&public_key).unwrap();
+
+    registry
+        .check_token(&Claims::new(public_key.clone(), wallet, TokenHolder::WalletKey))
+        .await
+        .unwrap();
+
+    let other_wallet = format!("0x{}", random_hex(20));
+    assert!(matches!(
+        registry
+            .check_token(&Claims::new(public_key, other_wallet, TokenHolder::WalletKey))
+            .await,
+        Err(UBIError::Unauthorized)
+    ));
+}
+
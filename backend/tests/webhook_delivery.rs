@@ -0,0 +1,198 @@
+//! Webhook delivery against a local HTTP receiver
+//!
+//! Requires DATABASE_URL; skipped when unset.
+
+use std::time::Duration;
+
+use rand::Rng;
+use rust_decimal::Decimal;
+use sqlx::PgPool;
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+use tokio::net::TcpListener;
+use tokio::sync::mpsc;
+use ubi_backend::models::oracle::OracleSubmission;
+use ubi_backend::models::webhook::WebhookRegistration;
+use ubi_backend::services::oracle::OracleService;
+use ubi_backend::services::webhook::{webhook_signature, RetryPolicy, WebhookService};
+
+/// A request as seen by the receiver
+struct Received {
+    headers: Vec<(String, String)>,
+    body: String,
+}
+
+impl Received {
+    fn header(&self, name: &str) -> Option<&str> {
+        self.headers
+            .iter()
+            .find(|(n, _)| n.eq_ignore_ascii_case(name))
+            .map(|(_, v)| v.as_str())
+    }
+}
+
+/// Minimal HTTP/1.1 receiver answering every request with `status`
+async fn spawn_receiver(status: u16) -> (String, mpsc::UnboundedReceiver<Received>) {
+    let listener = TcpListener::bind("127.0.0.1:0").await.unwrap();
+    let url = format!("http://{}/hook", listener.local_addr().unwrap());
+    let (tx, rx) = mpsc::unbounded_channel();
+    
+    tokio::spawn(async move {
+        loop {
+            let (mut socket, _) = listener.accept().await.unwrap();
+            let mut buf = Vec::new();
+            let mut chunk = [0u8; 4096];
+            let (header_end, content_length) = loop {
+                let n = socket.read(&mut chunk).await.unwrap();
+                if n == 0 {
+                    break (None, 0);
+                }
+                buf.extend_from_slice(&chunk[..n]);
+                if let Some(pos) = buf.windows(4).position(|w| w == b"\r\n\r\n") {
+                    let head = String::from_utf8_lossy(&buf[..pos]).to_lowercase();
+                    let length = head
+                        .lines()
+                        .find_map(|l| l.strip_prefix("content-length:"))
+                        .map_or(0, |v| v.trim().parse().unwrap());
+                    break (Some(pos + 4), length);
+                }
+            };
+            let Some(header_end) = header_end else { continue };
+            while buf.len() < header_end + content_length {
+                let n = socket.read(&mut chunk).await.unwrap();
+                buf.extend_from_slice(&chunk[..n]);
+            }
+            
+            let head = String::from_utf8_lossy(&buf[..header_end]).to_string();
+            let headers = head
+                .lines()
+                .skip(1)
+                .filter_map(|l| l.split_once(':'))
+                .map(|(n, v)| (n.trim().to_string(), v.trim().to_string()))
+                .collect();
+            let body = String::from_utf8_lossy(&buf[header_end..header_end + content_length]).to_string();
+            let _ = tx.send(Received { headers, body });
+            
+            let response = format!("HTTP/1.1 {} Status\r\nContent-Length: 0\r\nConnection: close\r\n\r\n", status);
+            let _ = socket.write_all(response.as_bytes()).await;
+        }
+    });
+    
+    (url, rx)
+}
+
+/// Submit oracle data for a fresh region and return the emitted event's id
+async fn emit_oracle_event(pool: &PgPool) -> i64 {
+    let region_id = 200_000 + rand::thread_rng().gen_range(0..100_000);
+    OracleService::new(pool.clone())
+        .submit_data(OracleSubmission {
+            region_id,
+            basket_index_wad: Decimal::from(1_000_000_000_000_000_000u64),
+        })
+        .await
+        .unwrap();
+    
+    sqlx::query_scalar(
+        "SELECT id FROM events WHERE event_type = 'OracleDataSubmitted' AND (event_data->>'region_id')::int = $1"
+    )
+    .bind(region_id)
+    .fetch_one(pool)
+    .await
+    .unwrap()
+}
+
+async fn delivery_status(pool: &PgPool, event_id: i64, endpoint_id: i64) -> (String, i32) {
+    sqlx::query_as("SELECT status, attempts FROM webhook_outbox WHERE event_id = $1 AND endpoint_id = $2")
+        .bind(event_id)
+        .bind(endpoint_id)
+        .fetch_one(pool)
+        .await
+        .unwrap()
+}
+
+#[tokio::test]
+async fn delivers_signed_events_to_receiver() {
+    let Ok(url) = std::env::var("DATABASE_URL") else {
+        eprintln!("DATABASE_URL not set, skipping");
+        return;
+    };
+    let pool = PgPool::connect(&url).await.unwrap();
+    let (receiver_url, mut received) = spawn_receiver(200).await;
+    
+    let webhooks = WebhookService::new(pool.clone(), RetryPolicy::default());
+    let endpoint = webhooks
+        .register(WebhookRegistration {
+            url: receiver_url,
+            event_types: Some(vec!["OracleDataSubmitted".to_string()]),
+        })
+        .await
+        .unwrap();
+    
+    let event_id = emit_oracle_event(&pool).await;
+    
+    // Other tests may queue events for this endpoint too; find ours
+    let request = loop {
+        webhooks.deliver_due(100).await.unwrap();
+        let request = tokio::time::timeout(Duration::from_secs(5), received.recv())
+            .await
+            .unwrap()
+            .unwrap();
+        if request.header("X-UBI-Event-Id") == Some(event_id.to_string().as_str()) {
+            break request;
+        }
+    };
+    
+    let timestamp: i64 = request.header("X-UBI-Timestamp").unwrap().parse().unwrap();
+    let expected = format!("sha256={}", webhook_signature(&endpoint.secret, timestamp, &request.body));
+    assert_eq!(request.header("X-UBI-Signature"), Some(expected.as_str()));
+    
+    let body: serde_json::Value = serde_json::from_str(&request.body).unwrap();
+    assert_eq!(body["id"], event_id);
+    assert_eq!(body["event_type"], "OracleDataSubmitted");
+    
+    assert_eq!(delivery_status(&pool, event_id, endpoint.id).await, ("delivered".to_string(), 1));
+    webhooks.deactivate(endpoint.id).await.unwrap();
+}
+
+#[tokio::test]
+async fn failing_deliveries_retry_then_dead_letter() {
+    let Ok(url) = std::env::var("DATABASE_URL") else {
+        eprintln!("DATABASE_URL not set, skipping");
+        return;
+    };
+    let pool = PgPool::connect(&url).await.unwrap();
+    let (receiver_url, _received) = spawn_receiver(500).await;
+    
+    let webhooks = WebhookService::new(pool.clone(), RetryPolicy {
+        max_attempts: 2,
+        base_backoff: Duration::ZERO,
+        max_backoff: Duration::ZERO,
+    });
+    let endpoint = webhooks
+        .register(WebhookRegistration {
+            url: receiver_url,
+            event_types: Some(vec!["OracleDataSubmitted".to_string()]),
+        })
+        .await
+        .unwrap();
+    
+    let event_id = emit_oracle_event(&pool).await;
+    
+    webhooks.deliver_due(1000).await.unwrap();
+    assert_eq!(delivery_status(&pool, event_id, endpoint.id).await, ("pending".to_string(), 1));
+    
+    webhooks.deliver_due(1000).await.unwrap();
+    assert_eq!(delivery_status(&pool, event_id, endpoint.id).await, ("dead".to_string(), 2));
+    
+    let dead = webhooks.dead_letters().await.unwrap();
+    let letter = dead
+        .iter()
+        .find(|d| d.event_id == event_id && d.endpoint_id == endpoint.id)
+        .expect("delivery is in the dead-letter list");
+    assert_eq!(letter.last_error.as_deref(), Some("HTTP 500 Internal Server Error"));
+    
+    webhooks.retry_dead_letter(letter.id).await.unwrap();
+    assert_eq!(delivery_status(&pool, event_id, endpoint.id).await, ("pending".to_string(), 0));
+    
+    webhooks.deactivate(endpoint.id).await.unwrap();
+}
+